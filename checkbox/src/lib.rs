@@ -1,15 +1,34 @@
 use ratatui_core::{buffer::Buffer, layout::Rect, style::Modifier, widgets::Widget};
 use tracing::{instrument, warn};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Checkbox {
     pub active: bool,
     pub set: bool,
+    pub set_mark: char,
+    pub unset_mark: char,
+}
+
+impl Default for Checkbox {
+    fn default() -> Self {
+        Self::new(false, false)
+    }
 }
 
 impl Checkbox {
     pub fn new(active: bool, set: bool) -> Self {
-        Self { active, set }
+        Self {
+            active,
+            set,
+            set_mark: 'X',
+            unset_mark: ' ',
+        }
+    }
+
+    pub fn with_marks(mut self, set_mark: char, unset_mark: char) -> Self {
+        self.set_mark = set_mark;
+        self.unset_mark = unset_mark;
+        self
     }
 
     pub fn set_active(&mut self, active: bool) -> &mut Self {
@@ -48,9 +67,11 @@ impl Widget for &Checkbox {
             buf[area].set_char('[');
             buf[(area.x + 2, area.y)].set_char(']');
             let mark = &mut buf[(area.x + 1, area.y)];
-            if self.set {
-                mark.set_char('X');
-            }
+            mark.set_char(if self.set {
+                self.set_mark
+            } else {
+                self.unset_mark
+            });
             if self.active {
                 mark.set_style(Modifier::REVERSED);
             }
@@ -1,11 +1,8 @@
-use std::{future::Future, ops::DerefMut, sync::Arc, time::Duration};
+use std::{future::Future, ops::DerefMut, path::Path, sync::Arc, time::Duration};
 
 use sqlx::{ConnectOptions, SqliteConnection, query, sqlite::SqliteConnectOptions};
 
-use color_eyre::{
-    Result,
-    eyre::{Context, OptionExt},
-};
+use color_eyre::{Result, eyre::Context};
 use tokio::{
     sync::Mutex,
     time::{MissedTickBehavior, interval},
@@ -13,9 +10,9 @@ use tokio::{
 use tracing::{Instrument, error, info, info_span, instrument};
 
 #[instrument]
-async fn open_db() -> Result<SqliteConnection> {
-    let mut db_path = dirs::cache_dir().ok_or_eyre("unable to detect cache dir")?;
-    db_path.push("jellyhaj.sqlite");
+async fn open_db(cache_dir: &Path) -> Result<SqliteConnection> {
+    std::fs::create_dir_all(cache_dir).context("creating cache dir")?;
+    let db_path = cache_dir.join("jellyhaj.sqlite");
     let create = async || {
         info!("opening sqlite db at {}", db_path.display());
         SqliteConnectOptions::new()
@@ -52,9 +49,10 @@ async fn cache_maintainance<Fut: Future<Output = Result<()>>>(
     }
 }
 
+/// `max_image_bytes` limits the size of cached images, 0 disables the limit
 #[instrument(skip_all)]
-pub async fn cache() -> Result<Arc<Mutex<SqliteConnection>>> {
-    let mut db = open_db().await?;
+pub async fn cache(cache_dir: &Path, max_image_bytes: u64) -> Result<Arc<Mutex<SqliteConnection>>> {
+    let mut db = open_db(cache_dir).await?;
     let migrate = info_span!("migrate");
     sqlx::migrate!("../migrations")
         .run(&mut db)
@@ -63,18 +61,42 @@ pub async fn cache() -> Result<Arc<Mutex<SqliteConnection>>> {
     migrate.in_scope(|| info!("migrations applied"));
     let maintainance = info_span!("cache_maintainance");
     let db = Arc::new(Mutex::new(db));
-    tokio::spawn(cache_maintainance(clean_images, db.clone()).instrument(maintainance.clone()));
+    tokio::spawn(
+        cache_maintainance(move |db| clean_images(db, max_image_bytes), db.clone())
+            .instrument(maintainance.clone()),
+    );
     Ok(db)
 }
 
 #[instrument]
-pub async fn clean_images(db: Arc<Mutex<SqliteConnection>>) -> Result<()> {
-    let res = query!("delete from image_cache where (added+7*24*60*60)<unixepoch()")
-        .execute(db.lock().await.deref_mut())
+pub async fn clean_images(db: Arc<Mutex<SqliteConnection>>, max_bytes: u64) -> Result<()> {
+    let mut db = db.lock().await;
+    let res = query!("delete from image_cache where (last_used+7*24*60*60)<unixepoch()")
+        .execute(db.deref_mut())
         .await
         .context("deleting old images from cache")?;
     if res.rows_affected() > 0 {
-        info!("removed {} images from cache", res.rows_affected());
+        info!("removed {} unused images from cache", res.rows_affected());
+    }
+    if max_bytes > 0 {
+        let max_bytes = i64::try_from(max_bytes).unwrap_or(i64::MAX);
+        let res = query!(
+            "delete from image_cache where rowid in
+                 (select rowid from
+                     (select rowid, sum(length(val)) over (order by last_used desc, rowid desc) as total
+                      from image_cache)
+                  where total > ?)",
+            max_bytes
+        )
+        .execute(db.deref_mut())
+        .await
+        .context("evicting least recently used images from cache")?;
+        if res.rows_affected() > 0 {
+            info!(
+                "removed {} least recently used images from cache",
+                res.rows_affected()
+            );
+        }
     }
     Ok(())
 }
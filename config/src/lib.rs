@@ -1,8 +1,18 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    num::{NonZeroU32, NonZeroU64, NonZeroUsize},
+    path::PathBuf,
+    str::FromStr,
+    time::Duration,
+};
 
-use color_eyre::eyre::{Context, OptionExt, Result};
-use jellyhaj_core::config::Config;
+use color_eyre::eyre::{Context, OptionExt, Result, eyre};
+use jellyhaj_core::config::{
+    AspectRatio, Config, DefaultItemAction, Icons, ImageLayout, MpvCacheOptions,
+    PlaybackErrorAction, QuitAction, ScrollStyle, SleepTimerAction, Theme,
+};
 use libmpv::MpvProfile;
+use ratatui::style::Color;
 use serde::Deserialize;
 use tracing::{info, instrument};
 
@@ -19,6 +29,232 @@ struct ParseConfig {
     pub mpv_profile: Option<String>,
     pub mpv_log_level: String,
     pub mpv_config_file: Option<PathBuf>,
+    #[serde(default)]
+    pub mpv_profile_config_files: HashMap<String, PathBuf>,
+    pub mpv_cache: Option<String>,
+    pub mpv_cache_secs: Option<u64>,
+    pub mpv_demuxer_max_bytes: Option<String>,
+    #[serde(default)]
+    pub remember_volume_speed: bool,
+    #[serde(default)]
+    pub focus_follows_mouse: bool,
+    #[serde(default)]
+    pub mouse_navigation: bool,
+    #[serde(default)]
+    pub single_click_play: bool,
+    #[serde(default = "default_subtitle_search_language")]
+    pub subtitle_search_language: String,
+    #[serde(default = "default_sleep_timer_minutes")]
+    pub sleep_timer_minutes: NonZeroU64,
+    pub sleep_timer_action: Option<String>,
+    pub on_playback_error: Option<String>,
+    pub quit_action: Option<String>,
+    pub default_item_action: Option<String>,
+    pub scroll_style: Option<String>,
+    #[serde(default = "default_prefetch_margin")]
+    pub prefetch_margin: usize,
+    #[serde(default = "default_home_row_limit")]
+    pub home_row_limit: NonZeroU32,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: NonZeroUsize,
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u64,
+    #[serde(default = "default_request_retries")]
+    pub request_retries: u32,
+    #[serde(default)]
+    pub prefetch_next_item: bool,
+    #[serde(default)]
+    pub now_playing_notification: bool,
+    #[serde(default)]
+    pub music_visualizer: bool,
+    #[serde(default = "default_inhibit_screensaver")]
+    pub inhibit_screensaver: bool,
+    pub preferred_audio_lang: Option<String>,
+    pub preferred_subtitle_lang: Option<String>,
+    #[serde(default = "default_image_fetch_timeout")]
+    pub image_fetch_timeout: NonZeroU64,
+    #[serde(default)]
+    pub resume_prompt: bool,
+    #[serde(default = "default_autoplay_next")]
+    pub autoplay_next: bool,
+    #[serde(default = "default_up_next_seconds")]
+    pub up_next_seconds: u64,
+    #[serde(default = "default_clear_images_on_play")]
+    pub clear_images_on_play: bool,
+    #[serde(default)]
+    pub library_stats: bool,
+    #[serde(default)]
+    pub key_sequence_timeout: u64,
+    pub cache_dir: Option<PathBuf>,
+    #[serde(default = "default_image_cache_size")]
+    pub image_cache_size: u64,
+    #[serde(default = "default_memory_image_cache_size")]
+    pub memory_image_cache_size: usize,
+    #[serde(default)]
+    pub image_decode_concurrency: usize,
+    #[serde(default)]
+    pub icons: ParseIcons,
+    #[serde(default)]
+    pub theme: ParseTheme,
+    #[serde(default)]
+    pub images: ParseImageLayout,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ParseIcons {
+    pub watched: Option<String>,
+    pub unwatched: Option<String>,
+    pub favorite: Option<String>,
+    pub arrow_up: Option<char>,
+    pub arrow_down: Option<char>,
+    pub checkbox_set: Option<char>,
+    pub checkbox_unset: Option<char>,
+}
+
+impl From<ParseIcons> for Icons {
+    fn from(value: ParseIcons) -> Self {
+        let default = Icons::default();
+        Icons {
+            watched: value.watched.unwrap_or(default.watched),
+            unwatched: value.unwatched.unwrap_or(default.unwatched),
+            favorite: value.favorite.unwrap_or(default.favorite),
+            arrow_up: value.arrow_up.unwrap_or(default.arrow_up),
+            arrow_down: value.arrow_down.unwrap_or(default.arrow_down),
+            checkbox_set: value.checkbox_set.unwrap_or(default.checkbox_set),
+            checkbox_unset: value.checkbox_unset.unwrap_or(default.checkbox_unset),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ParseTheme {
+    pub border: Option<String>,
+    pub active_border: Option<String>,
+    pub error: Option<String>,
+    pub accent: Option<String>,
+    pub watch_status: Option<String>,
+}
+
+impl TryFrom<ParseTheme> for Theme {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: ParseTheme) -> Result<Self> {
+        fn color(name: &str, value: Option<String>, default: Color) -> Result<Color> {
+            match value {
+                None => Ok(default),
+                Some(value) => Color::from_str(&value).map_err(|_| {
+                    eyre!(
+                        "invalid {name} color {value:?}, expected a name, an index or \"#rrggbb\""
+                    )
+                }),
+            }
+        }
+        let default = Theme::default();
+        Ok(Theme {
+            border: color("border", value.border, default.border)?,
+            active_border: color("active_border", value.active_border, default.active_border)?,
+            error: color("error", value.error, default.error)?,
+            accent: color("accent", value.accent, default.accent)?,
+            watch_status: color("watch_status", value.watch_status, default.watch_status)?,
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ParseImageLayout {
+    pub width: Option<u16>,
+    pub primary: Option<String>,
+    pub thumb: Option<String>,
+    pub backdrop: Option<String>,
+    pub other: Option<String>,
+}
+
+impl TryFrom<ParseImageLayout> for ImageLayout {
+    type Error = color_eyre::Report;
+
+    fn try_from(value: ParseImageLayout) -> Result<Self> {
+        fn ratio(name: &str, value: Option<String>, default: AspectRatio) -> Result<AspectRatio> {
+            let Some(value) = value else {
+                return Ok(default);
+            };
+            value
+                .split_once(':')
+                .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                .filter(|&(width, height)| width > 0 && height > 0)
+                .map(|(width, height)| AspectRatio { width, height })
+                .ok_or_else(|| {
+                    eyre!("invalid {name} ratio {value:?}, expected \"width:height\" like \"16:9\"")
+                })
+        }
+        let default = ImageLayout::default();
+        let width = value.width.unwrap_or(default.width);
+        if width == 0 {
+            return Err(eyre!("image width must be at least 1"));
+        }
+        Ok(ImageLayout {
+            width,
+            primary: ratio("primary", value.primary, default.primary)?,
+            thumb: ratio("thumb", value.thumb, default.thumb)?,
+            backdrop: ratio("backdrop", value.backdrop, default.backdrop)?,
+            other: ratio("other", value.other, default.other)?,
+        })
+    }
+}
+
+fn default_clear_images_on_play() -> bool {
+    true
+}
+
+fn default_inhibit_screensaver() -> bool {
+    true
+}
+
+fn default_autoplay_next() -> bool {
+    true
+}
+
+fn default_up_next_seconds() -> u64 {
+    5
+}
+
+fn default_image_cache_size() -> u64 {
+    500
+}
+
+fn default_memory_image_cache_size() -> usize {
+    200
+}
+
+fn default_image_fetch_timeout() -> NonZeroU64 {
+    NonZeroU64::new(30).expect("30 is not zero")
+}
+
+fn default_prefetch_margin() -> usize {
+    1
+}
+
+fn default_home_row_limit() -> NonZeroU32 {
+    NonZeroU32::new(16).expect("16 is not zero")
+}
+
+fn default_max_connections() -> NonZeroUsize {
+    NonZeroUsize::new(4).expect("4 is not zero")
+}
+
+fn default_request_timeout() -> u64 {
+    30
+}
+
+fn default_request_retries() -> u32 {
+    2
+}
+
+fn default_sleep_timer_minutes() -> NonZeroU64 {
+    NonZeroU64::new(30).expect("30 is not zero")
+}
+
+fn default_subtitle_search_language() -> String {
+    "eng".to_string()
 }
 
 #[instrument]
@@ -44,7 +280,7 @@ pub fn init_config(config_file: Option<PathBuf>, use_builtin: bool) -> Result<Co
         info!("loading built in config.")
     }
 
-    let config: ParseConfig = if !use_builtin && config_file.exists() {
+    let mut config: ParseConfig = if !use_builtin && config_file.exists() {
         toml::from_str(&std::fs::read_to_string(config_file).context("reading config file")?)
     } else {
         toml::from_str(include_str!("../config.toml"))
@@ -73,6 +309,75 @@ pub fn init_config(config_file: Option<PathBuf>, use_builtin: bool) -> Result<Co
         .unwrap_or(Ok(MpvProfile::default()))
         .context("parsing mpv_profile")?;
 
+    if let Some(cache) = config.mpv_cache.as_deref()
+        && !matches!(cache, "auto" | "yes" | "no")
+    {
+        return Err(eyre!(
+            "invalid mpv_cache {cache:?}, expected \"auto\", \"yes\" or \"no\""
+        ));
+    }
+
+    let sleep_timer_action = match config.sleep_timer_action.as_deref() {
+        None | Some("pause") => SleepTimerAction::Pause,
+        Some("quit") => SleepTimerAction::Quit,
+        Some(other) => {
+            return Err(eyre!(
+                "invalid sleep_timer_action {other:?}, expected \"pause\" or \"quit\""
+            ));
+        }
+    };
+
+    let on_playback_error = match config.on_playback_error.as_deref() {
+        None | Some("skip") => PlaybackErrorAction::Skip,
+        Some("stop") => PlaybackErrorAction::Stop,
+        Some(other) => {
+            return Err(eyre!(
+                "invalid on_playback_error {other:?}, expected \"skip\" or \"stop\""
+            ));
+        }
+    };
+
+    let quit_action = match config.quit_action.as_deref() {
+        None | Some("back") => QuitAction::Back,
+        Some("exit") => QuitAction::Exit,
+        Some(other) => {
+            return Err(eyre!(
+                "invalid quit_action {other:?}, expected \"back\" or \"exit\""
+            ));
+        }
+    };
+
+    let default_item_action = match config.default_item_action.as_deref() {
+        Some("open") => DefaultItemAction::Open,
+        None | Some("play") => DefaultItemAction::Play,
+        Some("details") => DefaultItemAction::Details,
+        Some(other) => {
+            return Err(eyre!(
+                "invalid default_item_action {other:?}, expected \"open\", \"play\" or \"details\""
+            ));
+        }
+    };
+
+    let scroll_style = match config.scroll_style.as_deref() {
+        None | Some("center") => ScrollStyle::Center,
+        Some("edge") => ScrollStyle::Edge,
+        Some(other) => {
+            return Err(eyre!(
+                "invalid scroll_style {other:?}, expected \"center\" or \"edge\""
+            ));
+        }
+    };
+
+    let cache_dir = if let Some(cache_dir) = config.cache_dir {
+        if cache_dir.is_absolute() {
+            cache_dir
+        } else {
+            config_dir.join(cache_dir)
+        }
+    } else {
+        dirs::cache_dir().ok_or_eyre("unable to detect cache dir")?
+    };
+
     let login_file = if let Some(login_file) = config.login_file {
         if login_file.is_absolute() {
             login_file
@@ -94,7 +399,56 @@ pub fn init_config(config_file: Option<PathBuf>, use_builtin: bool) -> Result<Co
         mpv_log_level: config.mpv_log_level,
         mpv_profile,
         help_prefixes,
+        mpv_profile_config_file: config.mpv_profile_config_files.remove(mpv_profile.to_str()),
         mpv_config_file: config.mpv_config_file,
+        mpv_cache: MpvCacheOptions {
+            cache: config.mpv_cache,
+            cache_secs: config.mpv_cache_secs,
+            demuxer_max_bytes: config.mpv_demuxer_max_bytes,
+            prefetch_playlist: config.prefetch_next_item,
+        },
+        remember_volume_speed: config.remember_volume_speed,
+        focus_follows_mouse: config.focus_follows_mouse,
+        mouse_navigation: config.mouse_navigation,
+        single_click_play: config.single_click_play,
+        subtitle_search_language: config.subtitle_search_language,
+        sleep_timer_minutes: config.sleep_timer_minutes,
+        sleep_timer_action,
+        on_playback_error,
+        quit_action,
+        default_item_action,
+        scroll_style,
+        prefetch_margin: config.prefetch_margin,
+        home_row_limit: config.home_row_limit,
+        max_connections: config.max_connections,
+        request_timeout: (config.request_timeout > 0)
+            .then(|| Duration::from_secs(config.request_timeout)),
+        request_retries: config.request_retries,
+        prefetch_next_item: config.prefetch_next_item,
+        now_playing_notification: config.now_playing_notification,
+        music_visualizer: config.music_visualizer,
+        inhibit_screensaver: config.inhibit_screensaver,
+        preferred_audio_lang: config.preferred_audio_lang,
+        preferred_subtitle_lang: config.preferred_subtitle_lang,
+        image_fetch_timeout: Duration::from_secs(config.image_fetch_timeout.get()),
+        resume_prompt: config.resume_prompt,
+        autoplay_next: config.autoplay_next,
+        up_next_seconds: config.up_next_seconds,
+        clear_images_on_play: config.clear_images_on_play,
+        library_stats: config.library_stats,
+        key_sequence_timeout: (config.key_sequence_timeout > 0)
+            .then(|| Duration::from_secs(config.key_sequence_timeout)),
+        cache_dir,
+        image_cache_size: config.image_cache_size.saturating_mul(1024 * 1024),
+        memory_image_cache_size: config.memory_image_cache_size,
+        image_decode_concurrency: if config.image_decode_concurrency > 0 {
+            config.image_decode_concurrency
+        } else {
+            std::thread::available_parallelism().map_or(4, NonZeroUsize::get)
+        },
+        icons: config.icons.into(),
+        theme: config.theme.try_into().context("parsing theme")?,
+        image_layout: config.images.try_into().context("parsing images")?,
     })
 }
 
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use jellyfin::JellyfinClient;
+use ratatui_image::picker::Picker;
+use sqlx::SqliteConnection;
+use stats_data::Stats;
+
+use crate::{
+    image::{available::ImagesAvailable, cache::ImageProtocolCache},
+    layout::ImageLayout,
+    theme::Theme,
+};
+
+/// Handles shared by the entries of one screen, every image keeps a reference to it instead of
+/// cloning each handle
+pub struct EntryContext {
+    pub jellyfin: JellyfinClient,
+    pub db: Arc<tokio::sync::Mutex<SqliteConnection>>,
+    pub cache: ImageProtocolCache,
+    /// notified when an image of the screen finished loading
+    pub available: ImagesAvailable,
+    pub picker: Arc<Picker>,
+    pub stats: Stats,
+    pub theme: Theme,
+    pub image_layout: ImageLayout,
+}
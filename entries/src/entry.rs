@@ -1,24 +1,26 @@
 use std::{borrow::Cow, fmt::Debug, sync::Arc};
 
 use jellyfin::{
-    JellyfinClient,
     image::select_images,
-    items::{ItemType, MediaItem},
+    items::{ExtraType, ImageType, ItemType, MediaItem, UserData, Video3DFormat},
     user_views::UserView,
 };
 use ratatui::{
     layout::Rect,
-    style::Color,
-    text::Span,
+    text::{Line, Span},
     widgets::{Block, BorderType, Paragraph, Widget},
 };
 use ratatui_fallible_widget::FallibleWidget;
-use ratatui_image::{FontSize, picker::Picker};
-use sqlx::SqliteConnection;
-use stats_data::Stats;
+use ratatui_image::FontSize;
 use tracing::instrument;
 
-use crate::image::{JellyfinImage, available::ImagesAvailable, cache::ImageProtocolCache};
+use crate::{
+    context::EntryContext,
+    icons::Icons,
+    image::{ImageSize, JellyfinImage},
+    layout::{AspectRatio, ImageLayout},
+    theme::Theme,
+};
 use color_eyre::Result;
 
 pub struct Entry {
@@ -27,6 +29,9 @@ pub struct Entry {
     subtitle: Option<String>,
     inner: EntryInner,
     watch_status: Option<Cow<'static, str>>,
+    badge: Option<&'static str>,
+    runtime: Option<String>,
+    theme: Theme,
     pub border_type: BorderType,
 }
 
@@ -36,23 +41,44 @@ impl Debug for Entry {
             .field("title", &self.title)
             .field("subtitle", &self.subtitle)
             .field("watch_status", &self.watch_status)
+            .field("badge", &self.badge)
+            .field("runtime", &self.runtime)
             .finish_non_exhaustive()
     }
 }
 
-pub const IMAGE_WIDTH: u16 = 32;
-pub fn image_height(font: FontSize) -> u16 {
-    let width = IMAGE_WIDTH * font.0;
-    let width: f64 = width.into();
-    let height = (width / 16.0) * 9.0;
+/// SxxEyy of an episode, the season is left out if it is unknown
+fn episode_number(item: &MediaItem) -> Option<String> {
+    let episode = item.episode_index?;
+    Some(match item.season_index {
+        Some(season) => format!("S{season:02}E{episode:02}"),
+        None => format!("E{episode:02}"),
+    })
+}
+
+/// height in cells of an image `width` cells wide
+pub fn image_height(font: FontSize, width: u16, ratio: AspectRatio) -> u16 {
+    let width = u32::from(width) * u32::from(font.0);
+    let height = ratio.height_of(width.into());
     let height = height / f64::from(font.1);
     height.ceil() as u16
 }
 
-pub const ENTRY_WIDTH: u16 = IMAGE_WIDTH + 2;
+pub fn entry_width(layout: ImageLayout) -> u16 {
+    layout.width + 2
+}
 
-pub fn entry_height(font: FontSize) -> u16 {
-    image_height(font) + 2
+pub fn entry_height(font: FontSize, layout: ImageLayout) -> u16 {
+    image_height(font, layout.width, layout.tallest()) + 2
+}
+
+/// pixel size of an image of `image_type` inside an entry, as used for cache keys
+pub fn entry_image_size(font: FontSize, layout: ImageLayout, image_type: ImageType) -> ImageSize {
+    let height = image_height(font, layout.width, layout.ratio(image_type));
+    ImageSize {
+        p_width: u32::from(layout.width) * u32::from(font.0),
+        p_height: u32::from(height) * u32::from(font.1),
+    }
 }
 
 impl FallibleWidget for Entry {
@@ -62,16 +88,25 @@ impl FallibleWidget for Entry {
         area: Rect,
         buf: &mut ratatui::prelude::Buffer,
     ) -> color_eyre::Result<()> {
+        let border = if self.border_type == BorderType::Double {
+            self.theme.active_border
+        } else {
+            self.theme.border
+        };
         let mut outer = Block::bordered()
             .border_type(self.border_type)
+            .border_style(border)
             .title_top(self.title.as_str());
         if let Some(subtitle) = &self.subtitle {
             outer = outer.title_bottom(subtitle.as_str());
         }
+        if let Some(runtime) = &self.runtime {
+            outer = outer.title_bottom(Line::from(runtime.as_str()).right_aligned());
+        }
         let inner = outer.inner(area);
         outer.render(area, buf);
         if let Some(watch_status) = self.watch_status.as_ref() {
-            Paragraph::new(Span::styled(watch_status.clone(), Color::LightBlue))
+            Paragraph::new(Span::styled(watch_status.clone(), self.theme.watch_status))
                 .right_aligned()
                 .render(
                     Rect {
@@ -83,6 +118,19 @@ impl FallibleWidget for Entry {
                     buf,
                 );
         }
+        if let Some(badge) = self.badge {
+            Paragraph::new(Span::styled(badge, self.theme.accent))
+                .right_aligned()
+                .render(
+                    Rect {
+                        x: area.x,
+                        y: area.bottom().saturating_sub(1),
+                        width: area.width,
+                        height: 1,
+                    },
+                    buf,
+                );
+        }
         if let Some(image) = &mut self.image {
             image.render_fallible(inner, buf)?;
         }
@@ -91,16 +139,37 @@ impl FallibleWidget for Entry {
 }
 
 impl Entry {
+    /// starts loading the image as if the entry was rendered in `area`
+    pub fn prefetch(&mut self, area: Rect) -> Result<()> {
+        if let Some(image) = &mut self.image {
+            image.prefetch(Block::bordered().inner(area))?;
+        }
+        Ok(())
+    }
+
     pub fn inner(&self) -> &EntryInner {
         &self.inner
     }
 
+    pub fn set_subtitle(&mut self, subtitle: String) {
+        self.subtitle = Some(subtitle);
+    }
+
+    /// replace the user data of the item after it was changed on the server
+    pub fn set_user_data(&mut self, user_data: UserData, icons: &Icons) {
+        if let EntryInner::Item(item) = &mut self.inner {
+            self.watch_status = watch_status(&user_data, icons);
+            item.user_data = Some(user_data);
+        }
+    }
+
     pub fn new(
         image: Option<JellyfinImage>,
         title: String,
         subtitle: Option<String>,
         inner: EntryInner,
         watch_status: Option<Cow<'static, str>>,
+        theme: Theme,
     ) -> Self {
         Self {
             image,
@@ -108,18 +177,17 @@ impl Entry {
             subtitle,
             inner,
             watch_status,
+            badge: None,
+            runtime: None,
+            theme,
             border_type: BorderType::Rounded,
         }
     }
 
     pub fn from_media_item(
         item: MediaItem,
-        jellyfin: &JellyfinClient,
-        db: &Arc<tokio::sync::Mutex<SqliteConnection>>,
-        cache: &ImageProtocolCache,
-        availabe: &ImagesAvailable,
-        picker: &Arc<Picker>,
-        stats: &Stats,
+        context: &Arc<EntryContext>,
+        icons: &Icons,
     ) -> Result<Option<Self>> {
         let (title, subtitle) = match &item.item_type {
             ItemType::Movie => (item.name.clone(), None),
@@ -128,14 +196,28 @@ impl Entry {
                 season_name: _,
                 series_id: _,
                 series_name,
-            } => (series_name.clone(), item.name.clone().into()),
+            } => (
+                series_name.clone(),
+                Some(match episode_number(&item) {
+                    Some(number) => format!("{number} {}", item.name),
+                    None => item.name.clone(),
+                }),
+            ),
+            ItemType::Season {
+                series_id: _,
+                series_name,
+            } if item.is_special() => (series_name.clone(), Some("Specials".to_string())),
             ItemType::Season {
                 series_id: _,
                 series_name,
             } => (series_name.clone(), item.name.clone().into()),
             ItemType::Series | ItemType::MusicAlbum => (item.name.clone(), None),
-            ItemType::Playlist | ItemType::Folder => (item.name.clone(), None),
-            ItemType::Music { album_id: _, album } => (album.clone(), item.name.clone().into()),
+            ItemType::Playlist | ItemType::Folder | ItemType::BoxSet => (item.name.clone(), None),
+            ItemType::Music {
+                album_id: _,
+                album,
+                album_artist: _,
+            } => (album.clone(), item.name.clone().into()),
             ItemType::Unknown => return Ok(None),
         };
         let image = select_images(&item)
@@ -144,44 +226,41 @@ impl Entry {
                     item.id.clone(),
                     tag.to_string(),
                     image_type,
-                    jellyfin.clone(),
-                    db.clone(),
-                    availabe.clone(),
-                    cache.clone(),
-                    picker.clone(),
-                    stats.clone(),
+                    item.blur_hash(image_type, tag).map(str::to_string),
+                    context.clone(),
                 )
             })
             .next();
-        let watch_status = if let Some(user_data) = item.user_data.as_ref() {
-            if let Some(num @ 1..) = user_data.unplayed_item_count {
-                Some(format!("{num}").into())
-            } else if user_data.played {
-                Some("✓".into())
-            } else {
-                None
-            }
-        } else {
-            None
+        let watch_status = item
+            .user_data
+            .as_ref()
+            .and_then(|user_data| watch_status(user_data, icons));
+        let badge = badge(&item);
+        // the badge is drawn over the bottom right corner, so it replaces the runtime
+        let runtime = match item.item_type {
+            ItemType::Movie
+            | ItemType::Episode {
+                season_id: _,
+                season_name: _,
+                series_id: _,
+                series_name: _,
+            } if badge.is_none() => item.run_time_ticks.map(format_runtime),
+            _ => None,
         };
-        Ok(Some(Self::new(
+        let mut entry = Self::new(
             image,
             title,
             subtitle,
             EntryInner::Item(item),
             watch_status,
-        )))
+            context.theme,
+        );
+        entry.badge = badge;
+        entry.runtime = runtime;
+        Ok(Some(entry))
     }
 
-    pub fn from_user_view(
-        item: UserView,
-        jellyfin: &JellyfinClient,
-        db: &Arc<tokio::sync::Mutex<SqliteConnection>>,
-        cache: &ImageProtocolCache,
-        availabe: &ImagesAvailable,
-        picker: &Arc<Picker>,
-        stats: &Stats,
-    ) -> Result<Self> {
+    pub fn from_user_view(item: UserView, context: &Arc<EntryContext>) -> Result<Self> {
         let title = item.name.clone();
         let image = item
             .image_tags
@@ -193,15 +272,18 @@ impl Entry {
                     item.id.clone(),
                     tag.clone(),
                     *image_type,
-                    jellyfin.clone(),
-                    db.clone(),
-                    availabe.clone(),
-                    cache.clone(),
-                    picker.clone(),
-                    stats.clone(),
+                    None,
+                    context.clone(),
                 )
             });
-        Ok(Self::new(image, title, None, EntryInner::View(item), None))
+        Ok(Self::new(
+            image,
+            title,
+            None,
+            EntryInner::View(item),
+            None,
+            context.theme,
+        ))
     }
 }
 
@@ -211,3 +293,48 @@ pub enum EntryInner {
     Item(MediaItem),
     View(UserView),
 }
+
+fn watch_status(user_data: &UserData, icons: &Icons) -> Option<Cow<'static, str>> {
+    let played = if let Some(num @ 1..) = user_data.unplayed_item_count {
+        num.to_string()
+    } else if user_data.played {
+        icons.watched.clone()
+    } else {
+        icons.unwatched.clone()
+    };
+    let status = if user_data.is_favorite && !icons.favorite.is_empty() {
+        if played.is_empty() {
+            icons.favorite.clone()
+        } else {
+            format!("{} {played}", icons.favorite)
+        }
+    } else {
+        played
+    };
+    (!status.is_empty()).then_some(status.into())
+}
+
+/// formats jellyfin ticks (100ns) as `1h 23m`
+pub fn format_runtime(ticks: u64) -> String {
+    let minutes = ticks / 10_000_000 / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+fn badge(item: &MediaItem) -> Option<&'static str> {
+    match (item.video_3d_format, item.extra_type) {
+        (Some(Video3DFormat::Mvc), _) => Some("3D MVC"),
+        (Some(_), _) => Some("3D"),
+        (None, Some(ExtraType::Trailer)) => Some("Trailer"),
+        (None, Some(ExtraType::BehindTheScenes)) => Some("Behind the scenes"),
+        (None, Some(ExtraType::DeletedScene)) => Some("Deleted scene"),
+        (None, Some(ExtraType::Interview)) => Some("Interview"),
+        (None, Some(ExtraType::Featurette)) => Some("Featurette"),
+        (None, Some(ExtraType::Short)) => Some("Short"),
+        (None, Some(_)) => Some("Extra"),
+        (None, None) => None,
+    }
+}
@@ -1,6 +1,9 @@
-use crate::entry::{ENTRY_WIDTH, Entry, entry_height};
+use crate::{
+    entry::{Entry, entry_height, entry_width},
+    layout::ImageLayout,
+};
 use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Position, Rect},
     widgets::{
         Block, BorderType, Padding, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget,
         Wrap,
@@ -11,12 +14,30 @@ use ratatui_image::picker::Picker;
 use std::{cmp::min, iter::repeat_n, sync::Arc};
 use tracing::{debug, instrument, trace};
 
+/// How the grid follows the selected row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollStyle {
+    /// keep the selected row in the middle
+    Center,
+    /// only scroll when the selected row leaves the visible rows
+    Edge,
+}
+
 pub struct EntryGrid {
     entries: Vec<Entry>,
     current: usize,
     width: usize,
     title: String,
     picker: Arc<Picker>,
+    image_layout: ImageLayout,
+    visible_areas: Vec<(usize, Rect)>,
+    scroll_style: ScrollStyle,
+    /// rows skipped in the last render, edge scrolling starts from here
+    skip_rows: usize,
+    /// rows visible in the last render, a page up or down moves by this many rows
+    page_rows: usize,
+    /// rows above and below the visible ones whose images are loaded
+    prefetch_margin: usize,
 }
 
 impl FallibleWidget for EntryGrid {
@@ -31,8 +52,10 @@ impl FallibleWidget for EntryGrid {
             .padding(Padding::uniform(1));
         let main = outer.inner(area);
         outer.render(area, buf);
-        self.width = ((main.width + 1) / (ENTRY_WIDTH + 1)).into();
-        let entry_height = entry_height(self.picker.font_size());
+        self.visible_areas.clear();
+        let entry_width = entry_width(self.image_layout);
+        self.width = ((main.width + 1) / (entry_width + 1)).into();
+        let entry_height = entry_height(self.picker.font_size(), self.image_layout);
         debug!("entry_height: {entry_height}");
         let height: usize = ((main.height + 1) / (entry_height + 1)).into();
         if height == 0 || self.width == 0 {
@@ -42,16 +65,29 @@ impl FallibleWidget for EntryGrid {
             return Ok(());
         }
         debug!("height: {height}");
+        self.page_rows = height;
         let rows = self.entries.len().div_ceil(self.width);
         debug!("rows: {rows}");
         let row_index = self.current / self.width;
         let mut skip_rows = 0usize;
         if height < rows {
-            let position = height / 2;
-            if row_index > position {
-                skip_rows = min(row_index - position, rows - height);
+            match self.scroll_style {
+                ScrollStyle::Center => {
+                    let position = height / 2;
+                    if row_index > position {
+                        skip_rows = min(row_index - position, rows - height);
+                    }
+                }
+                ScrollStyle::Edge => {
+                    skip_rows = min(
+                        self.skip_rows
+                            .clamp(row_index.saturating_sub(height - 1), row_index),
+                        rows - height,
+                    );
+                }
             }
         }
+        self.skip_rows = skip_rows;
         debug!("skip_rows: {skip_rows}");
         let rendered_rows = min(height, rows);
         debug!("rendered_rows: {rendered_rows}");
@@ -61,21 +97,42 @@ impl FallibleWidget for EntryGrid {
             .split(main);
         for row in skip_rows..skip_rows + rendered_rows {
             let area = row_areas[row - skip_rows];
-            let areas = Layout::horizontal(repeat_n(Constraint::Length(ENTRY_WIDTH), self.width))
+            let areas = Layout::horizontal(repeat_n(Constraint::Length(entry_width), self.width))
                 .spacing(1)
                 .flex(Flex::Start)
                 .split(area);
             let first_entry = row * self.width;
-            for entry in first_entry..first_entry + self.width {
-                let area = areas[entry - first_entry];
-                let border_type = if entry == self.current {
+            for index in first_entry..first_entry + self.width {
+                let area = areas[index - first_entry];
+                let border_type = if index == self.current {
                     BorderType::Double
                 } else {
                     BorderType::Rounded
                 };
-                if let Some(entry) = self.entries.get_mut(entry) {
+                if let Some(entry) = self.entries.get_mut(index) {
                     entry.border_type = border_type;
-                    entry.render_fallible(area, buf)?
+                    entry.render_fallible(area, buf)?;
+                    self.visible_areas.push((index, area));
+                }
+            }
+        }
+        if self.prefetch_margin > 0 {
+            let areas = Layout::horizontal(repeat_n(Constraint::Length(entry_width), self.width))
+                .spacing(1)
+                .flex(Flex::Start)
+                .split(row_areas[0]);
+            let end = skip_rows + rendered_rows;
+            let before = skip_rows.saturating_sub(self.prefetch_margin)..skip_rows;
+            let after = end..min(rows, end + self.prefetch_margin);
+            for row in before.chain(after) {
+                for (column, entry) in self
+                    .entries
+                    .iter_mut()
+                    .skip(row * self.width)
+                    .take(self.width)
+                    .enumerate()
+                {
+                    entry.prefetch(areas[column])?;
                 }
             }
         }
@@ -93,13 +150,26 @@ impl FallibleWidget for EntryGrid {
 }
 
 impl EntryGrid {
-    pub fn new(entries: Vec<Entry>, title: String, picker: Arc<Picker>) -> Self {
+    pub fn new(
+        entries: Vec<Entry>,
+        title: String,
+        picker: Arc<Picker>,
+        image_layout: ImageLayout,
+        scroll_style: ScrollStyle,
+        prefetch_margin: usize,
+    ) -> Self {
         Self {
             entries,
             current: 0,
             width: 1,
             title,
             picker,
+            image_layout,
+            visible_areas: Vec::new(),
+            scroll_style,
+            skip_rows: 0,
+            page_rows: 1,
+            prefetch_margin,
         }
     }
 
@@ -117,6 +187,35 @@ impl EntryGrid {
         }
         trace!("current: {}, length: {}", self.current, self.entries.len());
     }
+    #[instrument(skip_all)]
+    pub fn page_up(&mut self) {
+        self.current = self.current.saturating_sub(self.page_size());
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    #[instrument(skip_all)]
+    pub fn page_down(&mut self) {
+        self.current = page_down(self.current, self.entries.len(), self.page_size());
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    /// entries of the visible rows
+    fn page_size(&self) -> usize {
+        self.width * self.page_rows
+    }
+
+    #[instrument(skip_all)]
+    pub fn first(&mut self) {
+        self.current = 0;
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    #[instrument(skip_all)]
+    pub fn last(&mut self) {
+        self.current = self.entries.len().saturating_sub(1);
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
     #[instrument(skip_all)]
     pub fn left(&mut self) {
         self.current = self.current.saturating_sub(1);
@@ -132,6 +231,25 @@ impl EntryGrid {
         trace!("current: {}, length: {}", self.current, self.entries.len());
     }
 
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let position = Position::new(column, row);
+        let (index, _) = self
+            .visible_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))?;
+        self.current = *index;
+        Some(*index)
+    }
+
+    /// if the selection is within `rows` rows of the last row
+    pub fn near_end(&self, rows: usize) -> bool {
+        near_end(self.current, self.entries.len(), self.width, rows)
+    }
+
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = Entry>) {
+        self.entries.extend(entries);
+    }
+
     pub fn get(&self) -> Option<&Entry> {
         if self.entries.is_empty() {
             None
@@ -139,4 +257,48 @@ impl EntryGrid {
             Some(&self.entries[self.current])
         }
     }
+
+    pub fn get_mut(&mut self) -> Option<&mut Entry> {
+        self.entries.get_mut(self.current)
+    }
+}
+
+/// index `page` entries after `current`, clamped to the last of `len` entries
+fn page_down(current: usize, len: usize, page: usize) -> usize {
+    min(current + page, len.saturating_sub(1))
+}
+
+/// if `current` is within `rows` rows of the last of `len` entries arranged in rows of `width`
+fn near_end(current: usize, len: usize, width: usize, rows: usize) -> bool {
+    let width = width.max(1);
+    current / width + rows >= len.saturating_sub(1) / width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{near_end, page_down};
+
+    #[test]
+    fn page_down_clamps() {
+        assert_eq!(page_down(0, 100, 30), 30);
+        assert_eq!(page_down(65, 100, 30), 95);
+        assert_eq!(page_down(80, 100, 30), 99);
+        assert_eq!(page_down(0, 0, 30), 0);
+    }
+
+    #[test]
+    fn near_end_of_rows() {
+        // 100 entries in rows of 10, the last row is row 9
+        assert!(!near_end(0, 100, 10, 3));
+        assert!(!near_end(59, 100, 10, 3));
+        assert!(near_end(60, 100, 10, 3));
+        assert!(near_end(99, 100, 10, 3));
+    }
+
+    #[test]
+    fn near_end_of_short_grid() {
+        assert!(near_end(0, 0, 10, 0));
+        assert!(near_end(0, 5, 10, 0));
+        assert!(near_end(0, 5, 0, 4));
+    }
 }
@@ -0,0 +1,28 @@
+/// Symbols used to display the state of items and widgets.
+///
+/// Configurable since not every font contains the default glyphs.
+/// Empty strings are not displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icons {
+    pub watched: String,
+    pub unwatched: String,
+    pub favorite: String,
+    pub arrow_up: char,
+    pub arrow_down: char,
+    pub checkbox_set: char,
+    pub checkbox_unset: char,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self {
+            watched: "✓".to_string(),
+            unwatched: String::new(),
+            favorite: "♥".to_string(),
+            arrow_up: '⮙',
+            arrow_down: '⮛',
+            checkbox_set: 'X',
+            checkbox_unset: ' ',
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::f32::consts::PI;
+
+use image::{Rgb, RgbImage};
+
+const BASE83: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn decode83(s: &[u8]) -> Option<u32> {
+    s.iter().try_fold(0u32, |acc, c| {
+        let digit = BASE83.iter().position(|b| b == c)? as u32;
+        Some(acc * 83 + digit)
+    })
+}
+
+fn srgb_to_linear(value: u32) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.0031308 {
+        (v * 12.92 * 255.0 + 0.5) as u8
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u8
+    }
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Decodes a blurhash into an image of `width` x `height` pixels, `None` if the hash is invalid
+pub fn decode(hash: &str, width: u32, height: u32) -> Option<RgbImage> {
+    let hash = hash.as_bytes();
+    let size_flag = decode83(hash.get(..1)?)?;
+    let num_x = (size_flag % 9 + 1) as usize;
+    let num_y = (size_flag / 9 + 1) as usize;
+    if hash.len() != 4 + 2 * num_x * num_y {
+        return None;
+    }
+    let max_value = (decode83(&hash[1..2])? + 1) as f32 / 166.0;
+    let mut colors = Vec::with_capacity(num_x * num_y);
+    let dc = decode83(&hash[2..6])?;
+    colors.push([
+        srgb_to_linear(dc >> 16),
+        srgb_to_linear((dc >> 8) & 255),
+        srgb_to_linear(dc & 255),
+    ]);
+    for i in 1..num_x * num_y {
+        let ac = decode83(&hash[4 + i * 2..6 + i * 2])?;
+        let component = |quant: u32| sign_pow((quant as f32 - 9.0) / 9.0, 2.0) * max_value;
+        colors.push([
+            component(ac / (19 * 19)),
+            component((ac / 19) % 19),
+            component(ac % 19),
+        ]);
+    }
+    Some(RgbImage::from_fn(width, height, |x, y| {
+        let mut pixel = [0f32; 3];
+        for j in 0..num_y {
+            let basis_y = (PI * y as f32 * j as f32 / height as f32).cos();
+            for i in 0..num_x {
+                let basis = (PI * x as f32 * i as f32 / width as f32).cos() * basis_y;
+                for (p, c) in pixel.iter_mut().zip(colors[i + j * num_x]) {
+                    *p += c * basis;
+                }
+            }
+        }
+        Rgb(pixel.map(linear_to_srgb))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    fn assert_pixel(actual: [u8; 3], expected: [u8; 3]) {
+        assert!(
+            actual.iter().zip(expected).all(|(a, e)| a.abs_diff(e) <= 1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn decode_example_hash() {
+        let image = decode("LEHV6nWB2yk8pyo0adR*.7kCMdnj", 32, 32).unwrap();
+        assert_eq!(image.dimensions(), (32, 32));
+        assert_pixel(image.get_pixel(0, 0).0, [135, 164, 177]);
+        assert_pixel(image.get_pixel(31, 0).0, [137, 166, 181]);
+        assert_pixel(image.get_pixel(0, 31).0, [136, 144, 147]);
+        assert_pixel(image.get_pixel(31, 31).0, [133, 142, 147]);
+        assert_pixel(image.get_pixel(16, 16).0, [158, 125, 108]);
+    }
+
+    #[test]
+    fn reject_invalid_hash() {
+        assert!(decode("", 32, 32).is_none());
+        assert!(decode("LEHV6nWB2yk8pyo0adR*.7kCMdn", 32, 32).is_none());
+        assert!(decode("LEHV6nWB2yk8pyo0adR*.7kCMdn\"", 32, 32).is_none());
+    }
+}
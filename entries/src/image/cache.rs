@@ -1,9 +1,26 @@
-use std::{borrow::Borrow, collections::HashMap, fmt::Debug, hash::Hash, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    io::{Write, stdout},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
+use color_eyre::{Result, eyre::Context};
 use jellyfin::items::ImageType;
 use parking_lot::Mutex;
 use ratatui::layout::Rect;
-use ratatui_image::protocol::Protocol;
+use ratatui_image::{
+    picker::{Picker, ProtocolType},
+    protocol::Protocol,
+};
+use stats_data::Stats;
+use tokio::sync::Semaphore;
 use tracing::{instrument, trace};
 
 use crate::image::ImageSize;
@@ -87,31 +104,110 @@ impl<'s> Borrow<dyn AsKeyRef + 's> for ImageProtocolKey {
     }
 }
 
+/// Protocols with the time they were last stored, the oldest is evicted first
+#[derive(Default)]
+struct Protocols {
+    map: HashMap<ImageProtocolKey, (Protocol, Rect, u64)>,
+    clock: u64,
+}
+
 #[derive(Clone)]
 pub struct ImageProtocolCache {
-    protocols: Arc<Mutex<HashMap<ImageProtocolKey, (Protocol, Rect)>>>,
+    protocols: Arc<Mutex<Protocols>>,
+    generation: Arc<AtomicU64>,
+    fetch_timeout: Duration,
+    /// maximum number of stored protocols, 0 is unlimited
+    capacity: usize,
+    stats: Stats,
+    /// limits the number of images decoded at the same time
+    decode_permits: Arc<Semaphore>,
 }
 
 impl ImageProtocolCache {
     #[instrument(level = "trace", skip(self))]
     pub fn remove(&self, key: &dyn AsKeyRef) -> Option<(Protocol, Rect)> {
-        trace!("storing image protocol in cache");
-        self.protocols.lock().remove(key)
+        trace!("removing image protocol from cache");
+        let mut protocols = self.protocols.lock();
+        let removed = protocols.map.remove(key);
+        self.update_size(&protocols);
+        removed.map(|(protocol, size, _)| (protocol, size))
     }
     #[instrument(level = "trace", skip(self, protocol))]
     pub fn store(&self, protocol: Protocol, final_size: Rect, key: ImageProtocolKey) {
         trace!("storing image protocol in cache");
-        self.protocols.lock().insert(key, (protocol, final_size));
+        let mut protocols = self.protocols.lock();
+        protocols.clock += 1;
+        let clock = protocols.clock;
+        protocols.map.insert(key, (protocol, final_size, clock));
+        if self.capacity > 0 && protocols.map.len() > self.capacity {
+            let oldest = protocols
+                .map
+                .iter()
+                .min_by_key(|(_, (_, _, used))| *used)
+                .map(|(key, _)| {
+                    ImageProtocolKey::new(
+                        key.image_type,
+                        key.item_id.clone(),
+                        key.tag.clone(),
+                        key.size,
+                    )
+                });
+            if let Some(oldest) = oldest {
+                trace!(?oldest, "evicting least recently used image protocol");
+                protocols.map.remove(&oldest);
+            }
+        }
+        self.update_size(&protocols);
     }
-    pub fn new() -> Self {
-        Self {
-            protocols: Arc::new(Mutex::new(HashMap::new())),
+    fn update_size(&self, protocols: &Protocols) {
+        self.stats
+            .memory_image_cache_size
+            .store(protocols.map.len() as u64, Ordering::Relaxed);
+    }
+    /// Drops all image protocols, including the ones currently displayed.
+    ///
+    /// Images are transmitted to the terminal again on their next render.
+    /// Kitty keeps transmitted images around, so they are deleted from the terminal as well.
+    #[instrument(skip_all)]
+    pub fn invalidate(&self, picker: &Picker) -> Result<()> {
+        trace!("invalidating image protocols");
+        let mut protocols = self.protocols.lock();
+        protocols.map.clear();
+        self.update_size(&protocols);
+        drop(protocols);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        if picker.protocol_type() == ProtocolType::Kitty {
+            let mut stdout = stdout();
+            stdout
+                .write_all(b"\x1b_Ga=d,d=A\x1b\\")
+                .and_then(|()| stdout.flush())
+                .context("deleting kitty images")?;
         }
+        Ok(())
     }
-}
-
-impl Default for ImageProtocolCache {
-    fn default() -> Self {
-        Self::new()
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+    /// time after which fetching an image from the server is considered failed
+    pub fn fetch_timeout(&self) -> Duration {
+        self.fetch_timeout
+    }
+    pub(crate) fn decode_permits(&self) -> Arc<Semaphore> {
+        self.decode_permits.clone()
+    }
+    pub fn new(
+        fetch_timeout: Duration,
+        capacity: usize,
+        decode_concurrency: usize,
+        stats: Stats,
+    ) -> Self {
+        Self {
+            protocols: Arc::new(Mutex::new(Protocols::default())),
+            generation: Arc::new(AtomicU64::new(0)),
+            fetch_timeout,
+            capacity,
+            stats,
+            decode_permits: Arc::new(Semaphore::new(decode_concurrency.max(1))),
+        }
     }
 }
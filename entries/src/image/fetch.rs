@@ -4,18 +4,28 @@ use std::{
         Arc,
         atomic::Ordering::{Relaxed, SeqCst},
     },
+    time::Duration,
 };
 
 use crate::image::{ReadyImage, available::ImagesAvailable, cache::ImageProtocolKey};
 use bytes::Bytes;
-use color_eyre::{Result, eyre::Context};
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
 use image::{DynamicImage, ImageReader};
 use jellyfin::{JellyfinClient, image::GetImageQuery};
 use ratatui::layout::Rect;
 use sqlx::SqliteConnection;
 use stats_data::Stats;
 use std::ops::DerefMut;
-use tracing::{debug, instrument};
+use tokio::sync::{Semaphore, oneshot};
+use tracing::{debug, instrument, warn};
+
+/// format requested from the server
+const PREFERRED_FORMAT: &str = "Webp";
+/// format requested if the preferred format can not be decoded
+const FALLBACK_FORMAT: &str = "Jpg";
 
 #[instrument(skip_all)]
 pub async fn get_image(
@@ -26,60 +36,132 @@ pub async fn get_image(
     jellyfin: JellyfinClient,
     size: Rect,
     stats: Stats,
+    timeout: Duration,
+    decode_permits: Arc<Semaphore>,
 ) {
-    match {
-        let image_type = key.image_type.name();
-        let item_id = &key.item_id;
-        let tag = &key.tag;
-        sqlx::query_scalar!(
-            "select val from image_cache where
-             item_id = ? and
-             image_type = ? and
-             tag = ? and
-             size_x = ? and
-             size_y = ?",
-            item_id,
-            image_type,
-            tag,
-            key.size.p_width,
-            key.size.p_height
-        )
-        .fetch_optional(db.lock().await.deref_mut())
+    let image = load_image(key, db, jellyfin, &stats, timeout, &decode_permits).await;
+    *ready_image.image.lock() = Some(image.map(move |p| (p, size)));
+    debug!("Image ready");
+    ready_image.available.store(true, SeqCst);
+    available.inner.wake();
+}
+
+/// Loads the image from the cache or the server in the preferred format.
+///
+/// If it can not be decoded it is fetched again as jpeg, replacing the cached image.
+async fn load_image(
+    key: ImageProtocolKey,
+    db: Arc<tokio::sync::Mutex<SqliteConnection>>,
+    jellyfin: JellyfinClient,
+    stats: &Stats,
+    timeout: Duration,
+    decode_permits: &Arc<Semaphore>,
+) -> Result<DynamicImage> {
+    let val = match cached_image(&key, &db)
         .await
-    }
-    .context("Get image from cache")
-    .transpose()
+        .context("Get image from cache")?
     {
-        Some(Ok(val)) => {
-            stats.db_image_cache_hits.fetch_add(1, Relaxed);
-            rayon::spawn(move || parse_image(ready_image, available, &val, size));
-        }
-        Some(Err(e)) => {
-            *ready_image.image.lock() = Some(Err(e));
-            ready_image.available.store(true, SeqCst);
-            available.inner.wake();
+        Some(val) => {
+            stats.disk_image_cache_hits.fetch_add(1, Relaxed);
+            Bytes::from(val)
         }
-        None => {
-            stats.image_fetches.fetch_add(1, Relaxed);
-            match fetch_image(key, jellyfin, db).await {
-                Ok(image) => {
-                    rayon::spawn(move || parse_image(ready_image, available, &image, size))
-                }
-                Err(e) => {
-                    *ready_image.image.lock() = Some(Err(e));
-                    ready_image.available.store(true, SeqCst);
-                    available.inner.wake();
-                }
-            }
+        None => fetch_timeout(&key, &jellyfin, &db, stats, timeout, PREFERRED_FORMAT).await?,
+    };
+    match decode_image(val, decode_permits).await {
+        Ok(image) => Ok(image),
+        Err(e) => {
+            warn!("decoding image failed, requesting it as {FALLBACK_FORMAT}: {e:?}");
+            let val = fetch_timeout(&key, &jellyfin, &db, stats, timeout, FALLBACK_FORMAT).await?;
+            decode_image(val, decode_permits).await
         }
     }
 }
 
+async fn fetch_timeout(
+    key: &ImageProtocolKey,
+    jellyfin: &JellyfinClient,
+    db: &tokio::sync::Mutex<SqliteConnection>,
+    stats: &Stats,
+    timeout: Duration,
+    format: &str,
+) -> Result<Bytes> {
+    stats.image_fetches.fetch_add(1, Relaxed);
+    let fetched = match tokio::time::timeout(timeout, fetch_image(key, jellyfin, db, format)).await
+    {
+        Ok(fetched) => fetched,
+        Err(_) => Err(eyre!("fetching image timed out after {timeout:?}")),
+    };
+    if fetched.is_err() {
+        stats.failed_image_fetches.fetch_add(1, Relaxed);
+    }
+    fetched
+}
+
+/// Decodes the image on the rayon thread pool once a decode permit is available
+async fn decode_image(val: Bytes, decode_permits: &Arc<Semaphore>) -> Result<DynamicImage> {
+    let permit = decode_permits
+        .clone()
+        .acquire_owned()
+        .await
+        .context("image decoding is shut down")?;
+    let (send, recv) = oneshot::channel();
+    rayon::spawn(move || {
+        let _ = send.send(parse_image(&val));
+        drop(permit);
+    });
+    recv.await.context("image decoding was aborted")?
+}
+
+async fn cached_image(
+    key: &ImageProtocolKey,
+    db: &tokio::sync::Mutex<SqliteConnection>,
+) -> sqlx::Result<Option<Vec<u8>>> {
+    let image_type = key.image_type.name();
+    let item_id = &key.item_id;
+    let tag = &key.tag;
+    sqlx::query_scalar!(
+        "update image_cache set last_used = unixepoch() where
+             item_id = ? and
+             image_type = ? and
+             tag = ? and
+             size_x = ? and
+             size_y = ?
+         returning val",
+        item_id,
+        image_type,
+        tag,
+        key.size.p_width,
+        key.size.p_height
+    )
+    .fetch_optional(db.lock().await.deref_mut())
+    .await
+}
+
+/// Stores the image in the database cache without decoding it
 #[instrument(skip_all)]
-async fn fetch_image(
+pub async fn prefetch_image(
     key: ImageProtocolKey,
     jellyfin: JellyfinClient,
     db: Arc<tokio::sync::Mutex<SqliteConnection>>,
+    stats: Stats,
+) -> Result<()> {
+    if cached_image(&key, &db)
+        .await
+        .context("Get image from cache")?
+        .is_none()
+    {
+        stats.image_fetches.fetch_add(1, Relaxed);
+        fetch_image(&key, &jellyfin, &db, PREFERRED_FORMAT).await?;
+    }
+    Ok(())
+}
+
+#[instrument(skip_all)]
+async fn fetch_image(
+    key: &ImageProtocolKey,
+    jellyfin: &JellyfinClient,
+    db: &tokio::sync::Mutex<SqliteConnection>,
+    format: &str,
 ) -> Result<Bytes> {
     let image = jellyfin
         .get_image(
@@ -87,7 +169,7 @@ async fn fetch_image(
             key.image_type,
             &GetImageQuery {
                 tag: Some(&key.tag),
-                format: Some("Webp"),
+                format: Some(format),
                 max_width: Some(key.size.p_width),
                 max_height: Some(key.size.p_height),
             },
@@ -95,24 +177,16 @@ async fn fetch_image(
         .await?;
     let val: &[u8] = &image;
     let image_type = key.image_type.name();
-    sqlx::query!("insert into image_cache (item_id, image_type, tag, size_x, size_y, val) values (?,?,?,?,?,?)",
+    sqlx::query!("insert into image_cache (item_id, image_type, tag, size_x, size_y, val, last_used) values (?,?,?,?,?,?,unixepoch())",
         key.item_id,image_type, key.tag, key.size.p_width, key.size.p_height,val
     ).execute(db.lock().await.deref_mut()).await?;
     Ok(image)
 }
 
-fn parse_image_inner(val: &[u8]) -> Result<DynamicImage> {
+fn parse_image(val: &[u8]) -> Result<DynamicImage> {
     ImageReader::new(Cursor::new(val))
         .with_guessed_format()
         .context("detecting image type")?
         .decode()
         .context("parsing image")
 }
-
-#[instrument(skip_all)]
-fn parse_image(ready_image: Arc<ReadyImage>, available: ImagesAvailable, val: &[u8], size: Rect) {
-    *ready_image.image.lock() = Some(parse_image_inner(val).map(move |p| (p, size)));
-    debug!("Image ready");
-    ready_image.available.store(true, SeqCst);
-    available.inner.wake();
-}
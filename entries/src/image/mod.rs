@@ -4,34 +4,52 @@ use std::{
         Arc,
         atomic::{AtomicBool, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use color_eyre::{Result, eyre::Context};
-use image::DynamicImage;
-use jellyfin::{JellyfinClient, items::ImageType};
+use image::{DynamicImage, RgbImage};
+use jellyfin::items::ImageType;
 use parking_lot::Mutex;
 use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::text::Line;
 use ratatui::widgets::Widget;
 use ratatui_fallible_widget::FallibleWidget;
-use ratatui_image::{Image, Resize, picker::Picker, protocol::Protocol};
-use sqlx::SqliteConnection;
-use stats_data::Stats;
-use tracing::{debug, instrument, trace};
-
-use crate::image::{
-    available::ImagesAvailable,
-    cache::{ImageProtocolCache, ImageProtocolKey, ImageProtocolKeyRef},
+use ratatui_image::{Image, Resize, protocol::Protocol};
+use tokio::task::AbortHandle;
+use tracing::{debug, instrument, trace, warn};
+
+use crate::{
+    context::EntryContext,
+    entry::image_height,
+    image::cache::{ImageProtocolKey, ImageProtocolKeyRef},
 };
 
 pub mod available;
+mod blurhash;
 pub mod cache;
 mod fetch;
 
+pub use fetch::prefetch_image;
+
+/// minimum time between a failed image fetch and its retry
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
 struct ReadyImage {
     available: AtomicBool,
     image: Mutex<Option<Result<(DynamicImage, Rect)>>>,
 }
 
+impl ReadyImage {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            available: AtomicBool::new(false),
+            image: Mutex::new(None),
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ImageSize {
     pub p_width: u32,
@@ -42,22 +60,31 @@ pub struct JellyfinImage {
     item_id: String,
     tag: String,
     image_type: ImageType,
-    jellyfin: JellyfinClient,
-    db: Arc<tokio::sync::Mutex<SqliteConnection>>,
+    context: Arc<EntryContext>,
     image: Option<(Protocol, ImageProtocolKey, Rect)>,
     size: Option<Rect>,
-    available: ImagesAvailable,
     ready_image: Arc<ReadyImage>,
-    cache: ImageProtocolCache,
-    stats: Stats,
-    picker: Arc<Picker>,
     loading: bool,
+    /// pending fetch, aborted when the image is dropped or resized before it finishes
+    fetch: Option<AbortHandle>,
+    /// cache generation the current protocol was created in
+    generation: u64,
+    /// time the last fetch failed, it is retried on the next render after [RETRY_DELAY]
+    failed: Option<Instant>,
+    blur_hash: Option<String>,
+    /// blurhash decoded for the area it was rendered in, shown while the image loads
+    placeholder: Option<(Rect, RgbImage)>,
 }
 
 impl Drop for JellyfinImage {
     fn drop(&mut self) {
-        if let Some((protocol, key, area)) = self.image.take() {
-            self.cache.store(protocol, area, key);
+        if let Some(fetch) = self.fetch.take() {
+            fetch.abort();
+        }
+        if let Some((protocol, key, area)) = self.image.take()
+            && self.generation == self.context.cache.generation()
+        {
+            self.context.cache.store(protocol, area, key);
         }
     }
 }
@@ -66,14 +93,11 @@ impl FallibleWidget for JellyfinImage {
     #[instrument(skip_all, name = "render_image")]
     fn render_fallible(
         &mut self,
-        mut area: Rect,
+        area: Rect,
         buf: &mut ratatui::prelude::Buffer,
     ) -> color_eyre::Result<()> {
-        if let Some(old_area) = self.size.replace(area)
-            && (old_area.width != area.width || old_area.height != area.height)
-        {
-            self.image = None;
-        }
+        let mut area = self.image_area(area);
+        self.set_area(area);
         if let Some((image, size)) = self.get_image()? {
             trace!("received_image");
             trace!("area: {area:?}, size: {size:?}");
@@ -83,41 +107,119 @@ impl FallibleWidget for JellyfinImage {
             area.height = size.height;
             trace!("final area: {area:?}");
             Image::new(image).render(area, buf)
+        } else if self.failed.is_some() && area.height > 0 {
+            area.y += area.height / 2;
+            area.height = 1;
+            Line::from("⚠").centered().render(area, buf)
+        } else {
+            self.render_placeholder(area, buf);
         }
         Ok(())
     }
 }
 
 impl JellyfinImage {
-    #[allow(clippy::too_many_arguments)]
     pub fn new(
         item_id: String,
         tag: String,
         image_type: ImageType,
-        jellyfin: JellyfinClient,
-        db: Arc<tokio::sync::Mutex<SqliteConnection>>,
-        available: ImagesAvailable,
-        cache: ImageProtocolCache,
-        picker: Arc<Picker>,
-        stats: Stats,
+        blur_hash: Option<String>,
+        context: Arc<EntryContext>,
     ) -> Self {
         Self {
             item_id,
             tag,
             image_type,
-            jellyfin,
-            db,
             image: None,
             size: None,
-            available,
-            ready_image: Arc::new(ReadyImage {
-                available: AtomicBool::new(false),
-                image: Mutex::new(None),
-            }),
-            cache,
-            picker,
+            ready_image: ReadyImage::new(),
+            generation: context.cache.generation(),
+            context,
             loading: false,
-            stats,
+            fetch: None,
+            failed: None,
+            blur_hash,
+            placeholder: None,
+        }
+    }
+
+    /// draws the blurhash with half blocks, two pixels per cell
+    fn render_placeholder(&mut self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let Some(hash) = &self.blur_hash else {
+            return;
+        };
+        if area.is_empty() {
+            return;
+        }
+        if self
+            .placeholder
+            .as_ref()
+            .is_none_or(|(size, _)| size.width != area.width || size.height != area.height)
+        {
+            match blurhash::decode(hash, area.width.into(), u32::from(area.height) * 2) {
+                Some(placeholder) => self.placeholder = Some((area, placeholder)),
+                None => {
+                    warn!("invalid blurhash {hash:?}");
+                    self.blur_hash = None;
+                    return;
+                }
+            }
+        }
+        let Some((_, placeholder)) = &self.placeholder else {
+            return;
+        };
+        let color = |x: u16, y: u32| {
+            let [r, g, b] = placeholder.get_pixel(x.into(), y).0;
+            Color::Rgb(r, g, b)
+        };
+        for y in 0..area.height {
+            for x in 0..area.width {
+                buf[(area.x + x, area.y + y)]
+                    .set_char('▀')
+                    .set_fg(color(x, u32::from(y) * 2))
+                    .set_bg(color(x, u32::from(y) * 2 + 1));
+            }
+        }
+    }
+
+    fn set_area(&mut self, area: Rect) {
+        if let Some(old_area) = self.size.replace(area)
+            && (old_area.width != area.width || old_area.height != area.height)
+        {
+            self.image = None;
+            self.cancel_fetch();
+        }
+        if self.generation != self.context.cache.generation() {
+            self.generation = self.context.cache.generation();
+            self.image = None;
+        }
+    }
+
+    /// part of `area` with the aspect ratio configured for the image type, vertically centered
+    fn image_area(&self, mut area: Rect) -> Rect {
+        let ratio = self.context.image_layout.ratio(self.image_type);
+        let height = image_height(self.context.picker.font_size(), area.width, ratio);
+        let height = min(height, area.height);
+        area.y += (area.height - height) / 2;
+        area.height = height;
+        area
+    }
+
+    /// starts loading the image for `area` without rendering it
+    pub fn prefetch(&mut self, area: Rect) -> Result<()> {
+        self.set_area(self.image_area(area));
+        self.get_image()?;
+        Ok(())
+    }
+
+    /// aborts the pending fetch, a result it already delivered is discarded
+    fn cancel_fetch(&mut self) {
+        if let Some(fetch) = self.fetch.take() {
+            fetch.abort();
+        }
+        if self.loading {
+            self.loading = false;
+            self.ready_image = ReadyImage::new();
         }
     }
 
@@ -127,17 +229,27 @@ impl JellyfinImage {
         if self.image.is_some() {
             Ok(self.image.as_ref().map(|(p, _, s)| (p, *s)))
         } else if let Some(size) = self.size {
-            let p_height = (size.height as u32) * (self.picker.font_size().1 as u32);
-            let p_width = (size.width as u32) * (self.picker.font_size().0 as u32);
+            let p_height = (size.height as u32) * (self.context.picker.font_size().1 as u32);
+            let p_width = (size.width as u32) * (self.context.picker.font_size().0 as u32);
             if self.loading {
                 if self.ready_image.available.swap(false, Ordering::SeqCst) {
                     self.loading = false;
-                    let (image, new_size) = self
+                    self.fetch = None;
+                    let ready = self
                         .ready_image
                         .image
                         .lock()
                         .take()
-                        .expect("available is already set")?;
+                        .expect("available is already set");
+                    let (image, new_size) = match ready {
+                        Ok(ready) => ready,
+                        Err(e) => {
+                            warn!("failed to load image: {e:?}");
+                            self.failed = Some(Instant::now());
+                            return Ok(None);
+                        }
+                    };
+                    self.failed = None;
                     if size.width != new_size.width || size.height != new_size.height {
                         debug!("size mismatch, retrying");
                         self.loading = false;
@@ -145,11 +257,15 @@ impl JellyfinImage {
                     } else {
                         let width = min(
                             size.width as u32,
-                            image.width().div_ceil(self.picker.font_size().0 as u32),
+                            image
+                                .width()
+                                .div_ceil(self.context.picker.font_size().0 as u32),
                         ) as u16;
                         let height = min(
                             size.height as u32,
-                            image.height().div_ceil(self.picker.font_size().1 as u32),
+                            image
+                                .height()
+                                .div_ceil(self.context.picker.font_size().1 as u32),
                         ) as u16;
                         let image_size = Rect {
                             x: 0,
@@ -158,6 +274,7 @@ impl JellyfinImage {
                             height,
                         };
                         let image = self
+                            .context
                             .picker
                             .new_protocol(image, image_size, Resize::Fit(None))
                             .context("generating protocol")?;
@@ -176,15 +293,21 @@ impl JellyfinImage {
                 } else {
                     Ok(None)
                 }
+            } else if self
+                .failed
+                .is_some_and(|failed| failed.elapsed() < RETRY_DELAY)
+            {
+                Ok(None)
             } else {
-                let cached = self.cache.remove(&ImageProtocolKeyRef::new(
+                let cached = self.context.cache.remove(&ImageProtocolKeyRef::new(
                     self.image_type,
                     &self.item_id,
                     &self.tag,
                     ImageSize { p_width, p_height },
                 ));
                 if let Some((image, size)) = cached {
-                    self.stats
+                    self.context
+                        .stats
                         .memory_image_cache_hits
                         .fetch_add(1, Ordering::Relaxed);
                     let (image, _, _) = self.image.insert((
@@ -199,7 +322,7 @@ impl JellyfinImage {
                     ));
                     Ok(Some((image, size)))
                 } else {
-                    tokio::spawn(fetch::get_image(
+                    let fetch = tokio::spawn(fetch::get_image(
                         ImageProtocolKey {
                             image_type: self.image_type,
                             item_id: self.item_id.clone(),
@@ -207,12 +330,15 @@ impl JellyfinImage {
                             size: ImageSize { p_width, p_height },
                         },
                         self.ready_image.clone(),
-                        self.available.clone(),
-                        self.db.clone(),
-                        self.jellyfin.clone(),
+                        self.context.available.clone(),
+                        self.context.db.clone(),
+                        self.context.jellyfin.clone(),
                         size,
-                        self.stats.clone(),
+                        self.context.stats.clone(),
+                        self.context.cache.fetch_timeout(),
+                        self.context.cache.decode_permits(),
                     ));
+                    self.fetch = Some(fetch.abort_handle());
                     self.loading = true;
                     Ok(None)
                 }
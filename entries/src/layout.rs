@@ -0,0 +1,87 @@
+use jellyfin::items::ImageType;
+
+/// Width to height ratio of an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspectRatio {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl AspectRatio {
+    pub const WIDE: AspectRatio = AspectRatio {
+        width: 16,
+        height: 9,
+    };
+
+    /// height of an image `width` pixels wide
+    pub fn height_of(self, width: f64) -> f64 {
+        width / f64::from(self.width) * f64::from(self.height)
+    }
+}
+
+/// Size of the images shown in entries.
+///
+/// Configurable since posters are taller than the thumbnails the defaults are made for.
+/// Entries are as tall as the tallest ratio, so every image type fits in the same grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLayout {
+    /// width of the images in cells
+    pub width: u16,
+    pub primary: AspectRatio,
+    pub thumb: AspectRatio,
+    pub backdrop: AspectRatio,
+    /// every other image type
+    pub other: AspectRatio,
+}
+
+impl Default for ImageLayout {
+    fn default() -> Self {
+        Self {
+            width: 32,
+            primary: AspectRatio::WIDE,
+            thumb: AspectRatio::WIDE,
+            backdrop: AspectRatio::WIDE,
+            other: AspectRatio::WIDE,
+        }
+    }
+}
+
+impl ImageLayout {
+    pub fn ratio(&self, image_type: ImageType) -> AspectRatio {
+        match image_type {
+            ImageType::Primary => self.primary,
+            ImageType::Thumb => self.thumb,
+            ImageType::Backdrop => self.backdrop,
+            _ => self.other,
+        }
+    }
+
+    pub fn tallest(&self) -> AspectRatio {
+        [self.primary, self.thumb, self.backdrop, self.other]
+            .into_iter()
+            .max_by(|a, b| {
+                let a_height = u32::from(a.height) * u32::from(b.width);
+                a_height.cmp(&(u32::from(b.height) * u32::from(a.width)))
+            })
+            .expect("array is not empty")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AspectRatio, ImageLayout};
+
+    #[test]
+    fn tallest_ratio() {
+        assert_eq!(ImageLayout::default().tallest(), AspectRatio::WIDE);
+        let poster = AspectRatio {
+            width: 2,
+            height: 3,
+        };
+        let layout = ImageLayout {
+            primary: poster,
+            ..Default::default()
+        };
+        assert_eq!(layout.tallest(), poster);
+    }
+}
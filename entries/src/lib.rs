@@ -1,5 +1,10 @@
+pub mod context;
 pub mod entry;
 pub mod grid;
+pub mod icons;
 pub mod image;
+pub mod layout;
 pub mod list;
+pub mod mouse;
 pub mod screen;
+pub mod theme;
@@ -1,7 +1,7 @@
 use std::{cmp::min, iter::repeat_n};
 
 use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Position, Rect},
     widgets::{
         Block, BorderType, Padding, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget,
         Wrap,
@@ -11,13 +11,22 @@ use ratatui_fallible_widget::FallibleWidget;
 use ratatui_image::FontSize;
 use tracing::{instrument, trace};
 
-use crate::entry::{ENTRY_WIDTH, Entry, entry_height};
+use crate::{
+    entry::{Entry, entry_height, entry_width},
+    layout::ImageLayout,
+};
 
 #[derive(Debug)]
 pub struct EntryList {
     entries: Vec<Entry>,
     current: usize,
     title: String,
+    image_layout: ImageLayout,
+    visible_areas: Vec<(usize, Rect)>,
+    /// entries left and right of the visible ones whose images are loaded
+    prefetch_margin: usize,
+    /// entries visible in the last render, a page left or right moves by this many entries
+    page_size: usize,
     pub active: bool,
 }
 
@@ -33,7 +42,9 @@ impl FallibleWidget for EntryList {
             .padding(Padding::uniform(1));
         let main = outer.inner(area);
         outer.render(area, buf);
+        self.visible_areas.clear();
         let visible = self.visible(area.width);
+        self.page_size = visible.max(1);
         if visible == 0 && !self.entries.is_empty() {
             Paragraph::new("insufficient space")
                 .wrap(Wrap { trim: true })
@@ -42,15 +53,17 @@ impl FallibleWidget for EntryList {
         }
         let mut entries = self.entries.as_mut_slice();
         let mut current = self.current;
+        let mut offset = 0;
         if visible < entries.len() {
             let position_in_visible = visible / 2;
             if current > position_in_visible {
-                let offset = min(current - position_in_visible, entries.len() - visible);
+                offset = min(current - position_in_visible, entries.len() - visible);
                 current -= offset;
                 entries = &mut entries[offset..];
             }
         }
-        let areas = Layout::horizontal(repeat_n(Constraint::Length(ENTRY_WIDTH), visible))
+        let entry_width = entry_width(self.image_layout);
+        let areas = Layout::horizontal(repeat_n(Constraint::Length(entry_width), visible))
             .spacing(1)
             .flex(Flex::Start)
             .split(main);
@@ -62,7 +75,16 @@ impl FallibleWidget for EntryList {
             };
             let entry = &mut entries[i];
             entry.border_type = border_type;
-            entry.render_fallible(areas[i], buf)?
+            entry.render_fallible(areas[i], buf)?;
+            self.visible_areas.push((offset + i, areas[i]));
+        }
+        if visible > 0 {
+            let end = offset + visible;
+            let before = offset.saturating_sub(self.prefetch_margin)..offset;
+            let after = end..min(self.entries.len(), end + self.prefetch_margin);
+            for index in before.chain(after) {
+                self.entries[index].prefetch(areas[0])?;
+            }
         }
         if visible < self.entries.len() {
             Scrollbar::new(ratatui::widgets::ScrollbarOrientation::HorizontalBottom).render(
@@ -70,7 +92,7 @@ impl FallibleWidget for EntryList {
                 buf,
                 &mut ScrollbarState::new(self.entries.len())
                     .position(self.current)
-                    .viewport_content_length(ENTRY_WIDTH as usize + 1),
+                    .viewport_content_length(entry_width as usize + 1),
             );
         }
         Ok(())
@@ -78,17 +100,26 @@ impl FallibleWidget for EntryList {
 }
 
 impl EntryList {
-    pub fn new(entries: Vec<Entry>, title: String) -> Self {
+    pub fn new(
+        entries: Vec<Entry>,
+        title: String,
+        image_layout: ImageLayout,
+        prefetch_margin: usize,
+    ) -> Self {
         Self {
             entries,
             current: 0,
             title,
+            image_layout,
+            visible_areas: Vec::new(),
+            prefetch_margin,
+            page_size: 1,
             active: false,
         }
     }
 
     fn visible(&self, width: u16) -> usize {
-        let max_visible: u16 = (width - 5) / (ENTRY_WIDTH + 1);
+        let max_visible: u16 = (width - 5) / (entry_width(self.image_layout) + 1);
         min(max_visible.into(), self.entries.len())
     }
 
@@ -107,6 +138,41 @@ impl EntryList {
         trace!("current: {}, length: {}", self.current, self.entries.len());
     }
 
+    #[instrument(skip_all)]
+    pub fn page_left(&mut self) {
+        self.current = self.current.saturating_sub(self.page_size);
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    #[instrument(skip_all)]
+    pub fn page_right(&mut self) {
+        let new = self.current + self.page_size;
+        self.current = min(new, self.entries.len().saturating_sub(1));
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    #[instrument(skip_all)]
+    pub fn first(&mut self) {
+        self.current = 0;
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    #[instrument(skip_all)]
+    pub fn last(&mut self) {
+        self.current = self.entries.len().saturating_sub(1);
+        trace!("current: {}, length: {}", self.current, self.entries.len());
+    }
+
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        let position = Position::new(column, row);
+        let (index, _) = self
+            .visible_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))?;
+        self.current = *index;
+        Some(*index)
+    }
+
     pub fn get(&self) -> Option<&Entry> {
         if self.entries.is_empty() {
             None
@@ -114,8 +180,16 @@ impl EntryList {
             Some(&self.entries[self.current])
         }
     }
+
+    pub fn get_mut(&mut self) -> Option<&mut Entry> {
+        self.entries.get_mut(self.current)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Entry> {
+        self.entries.iter_mut()
+    }
 }
 
-pub fn entry_list_height(font: FontSize) -> u16 {
-    entry_height(font) + 4
+pub fn entry_list_height(font: FontSize, layout: ImageLayout) -> u16 {
+    entry_height(font, layout) + 4
 }
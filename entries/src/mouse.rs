@@ -0,0 +1,90 @@
+use keybinds::{ClickTracker, MouseButton, MouseEvent, MouseEventKind};
+
+use crate::{grid::EntryGrid, list::EntryList, screen::EntryScreen};
+
+/// Entry widgets that can be navigated with the mouse
+pub trait MouseNavigation {
+    /// identifies an entry for double click detection
+    type Key: PartialEq;
+
+    /// selects the entry at the position, `None` if there is none
+    fn select_at(&mut self, column: u16, row: u16) -> Option<Self::Key>;
+    fn scroll_up(&mut self);
+    fn scroll_down(&mut self);
+
+    /// moves the selection with the wheel and clicks, with `hover` the entry under the cursor is
+    /// selected
+    ///
+    /// returns true if the selected entry should be played
+    fn handle_mouse(
+        &mut self,
+        event: MouseEvent,
+        hover: bool,
+        clicks: &mut ClickTracker<Self::Key>,
+    ) -> bool {
+        match event.kind {
+            MouseEventKind::Moved if hover => {
+                self.select_at(event.column, event.row);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                return self
+                    .select_at(event.column, event.row)
+                    .is_some_and(|entry| clicks.click(entry));
+            }
+            MouseEventKind::ScrollUp => self.scroll_up(),
+            MouseEventKind::ScrollDown => self.scroll_down(),
+            _ => {}
+        }
+        false
+    }
+}
+
+impl MouseNavigation for EntryGrid {
+    type Key = usize;
+
+    fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        EntryGrid::select_at(self, column, row)
+    }
+
+    fn scroll_up(&mut self) {
+        self.up();
+    }
+
+    fn scroll_down(&mut self) {
+        self.down();
+    }
+}
+
+/// the list is horizontal, the wheel moves left and right
+impl MouseNavigation for EntryList {
+    type Key = usize;
+
+    fn select_at(&mut self, column: u16, row: u16) -> Option<usize> {
+        EntryList::select_at(self, column, row)
+    }
+
+    fn scroll_up(&mut self) {
+        self.left();
+    }
+
+    fn scroll_down(&mut self) {
+        self.right();
+    }
+}
+
+/// entries are identified by the row and their position in it
+impl MouseNavigation for EntryScreen {
+    type Key = (usize, usize);
+
+    fn select_at(&mut self, column: u16, row: u16) -> Option<(usize, usize)> {
+        EntryScreen::select_at(self, column, row)
+    }
+
+    fn scroll_up(&mut self) {
+        self.up();
+    }
+
+    fn scroll_down(&mut self) {
+        self.down();
+    }
+}
@@ -1,7 +1,7 @@
 use std::{cmp::min, iter::repeat_n, sync::Arc};
 
 use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Position, Rect},
     widgets::{Block, Padding, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget, Wrap},
 };
 use ratatui_fallible_widget::FallibleWidget;
@@ -9,7 +9,8 @@ use ratatui_image::picker::Picker;
 use tracing::{instrument, trace};
 
 use crate::{
-    entry::{ENTRY_WIDTH, Entry},
+    entry::{Entry, entry_width},
+    layout::ImageLayout,
     list::{EntryList, entry_list_height},
 };
 
@@ -19,6 +20,8 @@ pub struct EntryScreen {
     current: usize,
     title: String,
     picker: Arc<Picker>,
+    image_layout: ImageLayout,
+    visible_areas: Vec<(usize, Rect)>,
 }
 
 impl FallibleWidget for EntryScreen {
@@ -33,7 +36,8 @@ impl FallibleWidget for EntryScreen {
             .padding(Padding::uniform(1));
         let main = outer.inner(area);
         outer.render(area, buf);
-        let entry_height = entry_list_height(self.picker.font_size());
+        self.visible_areas.clear();
+        let entry_height = entry_list_height(self.picker.font_size(), self.image_layout);
         let visible = self.visible(area.height, entry_height);
         if visible == 0 && !self.entries.is_empty() {
             Paragraph::new("insufficient space")
@@ -43,10 +47,11 @@ impl FallibleWidget for EntryScreen {
         }
         let mut entries = self.entries.as_mut_slice();
         let mut current = self.current;
+        let mut offset = 0;
         if visible < entries.len() {
             let position_in_visible = visible / 2;
             if current > position_in_visible {
-                let offset = min(current - position_in_visible, entries.len() - visible);
+                offset = min(current - position_in_visible, entries.len() - visible);
                 current -= offset;
                 entries = &mut entries[offset..];
             }
@@ -59,7 +64,8 @@ impl FallibleWidget for EntryScreen {
         for i in 0..areas.len() {
             let entry = &mut entries[i];
             entry.active = i == current;
-            entry.render_fallible(areas[i], buf)?
+            entry.render_fallible(areas[i], buf)?;
+            self.visible_areas.push((offset + i, areas[i]));
         }
         if visible < self.entries.len() {
             Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight).render(
@@ -67,7 +73,7 @@ impl FallibleWidget for EntryScreen {
                 buf,
                 &mut ScrollbarState::new(self.entries.len())
                     .position(self.current)
-                    .viewport_content_length(ENTRY_WIDTH as usize + 1),
+                    .viewport_content_length(entry_width(self.image_layout) as usize + 1),
             );
         }
         Ok(())
@@ -75,12 +81,19 @@ impl FallibleWidget for EntryScreen {
 }
 
 impl EntryScreen {
-    pub fn new(entries: Vec<EntryList>, title: String, picker: Arc<Picker>) -> Self {
+    pub fn new(
+        entries: Vec<EntryList>,
+        title: String,
+        picker: Arc<Picker>,
+        image_layout: ImageLayout,
+    ) -> Self {
         Self {
             entries,
             current: 0,
             title,
             picker,
+            image_layout,
+            visible_areas: Vec::new(),
         }
     }
 
@@ -109,6 +122,37 @@ impl EntryScreen {
         self.entries[self.current].right();
     }
 
+    #[instrument(skip_all)]
+    pub fn page_left(&mut self) {
+        self.entries[self.current].page_left();
+    }
+
+    #[instrument(skip_all)]
+    pub fn page_right(&mut self) {
+        self.entries[self.current].page_right();
+    }
+
+    #[instrument(skip_all)]
+    pub fn first(&mut self) {
+        self.entries[self.current].first();
+    }
+
+    #[instrument(skip_all)]
+    pub fn last(&mut self) {
+        self.entries[self.current].last();
+    }
+
+    pub fn select_at(&mut self, column: u16, row: u16) -> Option<(usize, usize)> {
+        let position = Position::new(column, row);
+        let (index, _) = self
+            .visible_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))?;
+        self.current = *index;
+        let entry = self.entries[*index].select_at(column, row)?;
+        Some((*index, entry))
+    }
+
     pub fn get(&self) -> Option<&Entry> {
         if self.entries.is_empty() {
             None
@@ -117,6 +161,15 @@ impl EntryScreen {
         }
     }
 
+    pub fn get_mut(&mut self) -> Option<&mut Entry> {
+        self.entries.get_mut(self.current)?.get_mut()
+    }
+
+    /// all entries of all lists
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Entry> {
+        self.entries.iter_mut().flat_map(EntryList::iter_mut)
+    }
+
     fn visible(&self, height: u16, entry_height: u16) -> usize {
         min(((height - 5) / (entry_height)).into(), self.entries.len())
     }
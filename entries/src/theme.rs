@@ -0,0 +1,28 @@
+use ratatui::style::Color;
+
+/// Colors of the named roles used by the widgets.
+///
+/// Configurable since the defaults are hard to read on some terminal backgrounds.
+/// [Color::Reset] keeps the color of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub border: Color,
+    /// border of the selected entry or input
+    pub active_border: Color,
+    pub error: Color,
+    /// highlighted labels like the special and extra badges
+    pub accent: Color,
+    pub watch_status: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Color::Reset,
+            active_border: Color::Reset,
+            error: Color::Red,
+            accent: Color::Yellow,
+            watch_status: Color::LightBlue,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::pin::pin;
+use std::{pin::pin, time::Duration};
 
 use color_eyre::{
     Result,
@@ -8,16 +8,75 @@ use futures_util::StreamExt;
 use jellyfin::{
     JellyfinClient, JellyfinVec,
     items::{GetItemsQuery, MediaItem},
+    playlist::GetPlaylistItemsQuery,
 };
 use jellyhaj_core::{keybinds::LoadingCommand, state::Navigation};
 use keybinds::{BindingMap, KeybindEvent, KeybindEventStream, KeybindEvents};
 use ratatui::{
     DefaultTerminal,
-    widgets::{Block, Paragraph},
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    widgets::{Block, Gauge, Paragraph, Widget},
 };
 use ratatui_fallible_widget::TermExt;
+use tokio::{
+    sync::watch,
+    time::{MissedTickBehavior, interval},
+};
 use tracing::instrument;
 
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// State of a running task shown by [fetch_screen_progress]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub message: String,
+    /// completed fraction between 0 and 1, shown as a gauge below the message if known
+    pub ratio: Option<f64>,
+}
+
+impl Progress {
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            ratio: None,
+        }
+    }
+
+    pub fn ratio(message: impl Into<String>, ratio: f64) -> Self {
+        Self {
+            message: message.into(),
+            ratio: Some(ratio),
+        }
+    }
+}
+
+struct LoadingWidget {
+    progress: Progress,
+    frame: usize,
+}
+
+impl Widget for &LoadingWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered();
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [message, gauge] =
+            Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).areas(inner);
+        Paragraph::new(format!("{} {}", SPINNER[self.frame], self.progress.message))
+            .centered()
+            .render(message, buf);
+        if let Some(ratio) = self.progress.ratio {
+            let ratio = ratio.clamp(0.0, 1.0);
+            Gauge::default()
+                .ratio(ratio)
+                .label(format!("{:.0}%", ratio * 100.0))
+                .render(gauge, buf);
+        }
+    }
+}
+
 pub async fn fetch_screen(
     title: &str,
     fetch: impl Future<Output = Result<Navigation>>,
@@ -26,20 +85,57 @@ pub async fn fetch_screen(
     term: &mut DefaultTerminal,
     help_prefixes: &[String],
 ) -> Result<Navigation> {
-    let mut msg = Paragraph::new(title).centered().block(Block::bordered());
+    let (_progress, progress_receiver) = watch::channel(Progress::message(title));
+    fetch_screen_progress(
+        fetch,
+        progress_receiver,
+        events,
+        keybinds,
+        term,
+        help_prefixes,
+    )
+    .await
+}
+
+/// Like [fetch_screen], but the message and gauge are replaced with every value sent through
+/// `progress`
+pub async fn fetch_screen_progress(
+    fetch: impl Future<Output = Result<Navigation>>,
+    mut progress: watch::Receiver<Progress>,
+    events: &mut KeybindEvents,
+    keybinds: BindingMap<LoadingCommand>,
+    term: &mut DefaultTerminal,
+    help_prefixes: &[String],
+) -> Result<Navigation> {
+    let mut widget = LoadingWidget {
+        progress: progress.borrow_and_update().clone(),
+        frame: 0,
+    };
     let mut fetch = pin!(fetch);
-    let mut events = KeybindEventStream::new(events, &mut msg, keybinds, help_prefixes);
+    let mut spinner = interval(SPINNER_INTERVAL);
+    spinner.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut events = KeybindEventStream::new(events, &mut widget, keybinds, help_prefixes);
     loop {
         term.draw_fallible(&mut events)?;
         tokio::select! {
             data = &mut fetch => {
                 break data
             }
+            Ok(()) = progress.changed() => {
+                events.get_inner().progress = progress.borrow_and_update().clone();
+            }
+            _ = spinner.tick() => {
+                let widget = events.get_inner();
+                widget.frame = (widget.frame + 1) % SPINNER.len();
+            }
             term = events.next() => {
                 match term {
-                    Some(Ok(KeybindEvent::Command(LoadingCommand::Quit))) => break Ok(Navigation::PopContext),
+                    Some(Ok(KeybindEvent::Command(LoadingCommand::Quit))) => break Ok(Navigation::Quit),
+                    Some(Ok(KeybindEvent::Command(LoadingCommand::Back))) => break Ok(Navigation::PopContext),
                     Some(Ok(KeybindEvent::Render)) => continue,
                     Some(Ok(KeybindEvent::Text(_))) => unimplemented!(),
+                    Some(Ok(KeybindEvent::Mouse(_))) => continue,
+                    Some(Ok(KeybindEvent::Global(_))) => continue,
                     Some(Err(e)) => break Err(e).context("Error getting key events from terminal"),
                     None => break Ok(Navigation::Exit),
                 }
@@ -76,6 +172,8 @@ pub async fn fetch_all_children(jellyfin: &JellyfinClient, id: &str) -> Result<V
                 image_type_limit: 1.into(),
                 enable_user_data: true.into(),
                 fields: "Overview".into(),
+                sort_by: "ParentIndexNumber, IndexNumber, SortName".into(),
+                sort_order: "Ascending".into(),
                 ..Default::default()
             })
             .await
@@ -88,6 +186,33 @@ pub async fn fetch_all_children(jellyfin: &JellyfinClient, id: &str) -> Result<V
     Ok(items)
 }
 
+#[instrument(skip(jellyfin))]
+pub async fn fetch_playlist_items(jellyfin: &JellyfinClient, id: &str) -> Result<Vec<MediaItem>> {
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    let items = JellyfinVec::collect(async |start| {
+        jellyfin
+            .get_playlist_items(
+                id,
+                &GetPlaylistItemsQuery {
+                    user_id: user_id.into(),
+                    start_index: start.into(),
+                    limit: 100.into(),
+                    enable_images: true.into(),
+                    image_type_limit: 1.into(),
+                    enable_image_types: "Thumb, Backdrop, Primary".into(),
+                    enable_user_data: true.into(),
+                },
+            )
+            .await
+            .context("requesting playlist items")?
+            .deserialize()
+            .await
+            .context("deserializing playlist items")
+    })
+    .await?;
+    Ok(items)
+}
+
 #[instrument(skip(jellyfin))]
 pub async fn fetch_item(jellyfin: &JellyfinClient, id: &str) -> Result<MediaItem> {
     let user_id = jellyfin.get_auth().user.id.as_str();
@@ -0,0 +1,230 @@
+use std::{ops::DerefMut, pin::Pin};
+
+use color_eyre::eyre::{Context, Result};
+use entries::{
+    entry::Entry, grid::EntryGrid, image::available::ImagesAvailable, mouse::MouseNavigation,
+};
+use futures_util::StreamExt;
+use jellyfin::items::MediaItem;
+use jellyhaj_core::{
+    context::TuiContext,
+    entries::{EntryExt, UserDataChange, change_user_data},
+    keybinds::HistoryCommand,
+    state::{Navigation, NextScreen, global_screen},
+};
+use keybinds::{ClickTracker, KeybindEvent, KeybindEventStream};
+use ratatui_fallible_widget::TermExt;
+use sqlx::SqliteConnection;
+use tokio::sync::Mutex;
+use tracing::{debug, instrument, warn};
+
+/// number of items kept in the history
+const HISTORY_SIZE: i64 = 200;
+
+#[instrument(skip_all)]
+pub async fn record(db: &Mutex<SqliteConnection>, item: &MediaItem) -> Result<()> {
+    let json = serde_json::to_string(item).context("serializing item")?;
+    let mut db = db.lock().await;
+    sqlx::query!(
+        "insert or replace into history (item_id, item) values (?, ?)",
+        item.id,
+        json
+    )
+    .execute(db.deref_mut())
+    .await
+    .context("recording history")?;
+    sqlx::query!(
+        "delete from history where item_id not in
+             (select item_id from history order by opened desc limit ?)",
+        HISTORY_SIZE
+    )
+    .execute(db.deref_mut())
+    .await
+    .context("trimming history")?;
+    Ok(())
+}
+
+async fn load(db: &Mutex<SqliteConnection>) -> Result<Vec<MediaItem>> {
+    let rows = sqlx::query_scalar!("select item from history order by opened desc")
+        .fetch_all(db.lock().await.deref_mut())
+        .await
+        .context("loading history")?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|item| match serde_json::from_str(&item) {
+            Ok(item) => Some(item),
+            Err(e) => {
+                warn!("dropping unreadable history entry: {e:?}");
+                None
+            }
+        })
+        .collect())
+}
+
+async fn clear(db: &Mutex<SqliteConnection>) -> Result<()> {
+    sqlx::query!("delete from history")
+        .execute(db.lock().await.deref_mut())
+        .await
+        .context("clearing history")?;
+    Ok(())
+}
+
+pub async fn show_history(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
+    let items = load(&cx.cache).await?;
+    let images_available = ImagesAvailable::new();
+    let entry_cx = cx.entry_context(&images_available);
+    let mut grid = EntryGrid::new(
+        items
+            .into_iter()
+            .filter_map(|item| {
+                Entry::from_media_item(item, &entry_cx, &cx.config.icons).transpose()
+            })
+            .collect::<Result<Vec<_>>>()?,
+        "History".to_string(),
+        cx.image_picker.clone(),
+        cx.config.image_layout,
+        cx.config.scroll_style,
+        cx.config.prefetch_margin,
+    );
+    let cx = cx.project();
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut grid,
+        cx.config.keybinds.history.clone(),
+        &cx.config.help_prefixes,
+    );
+    events.set_mouse_input(cx.config.capture_mouse());
+    let mut clicks = ClickTracker::new(cx.config.single_click_play);
+    loop {
+        cx.term.draw_fallible(&mut events)?;
+        let cmd = tokio::select! {
+            _ = images_available.wait_available() => {continue}
+            term = events.next() => {
+                match term {
+                    Some(Ok(KeybindEvent::Command(cmd))) => cmd,
+                    Some(Ok(KeybindEvent::Render)) => continue,
+                    Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Mouse(event))) => {
+                        let hover = cx.config.focus_follows_mouse;
+                        if events.get_inner().handle_mouse(event, hover, &mut clicks) {
+                            HistoryCommand::Play
+                        } else {
+                            continue
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: NextScreen::History,
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
+                    Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+                    None => break Ok(Navigation::PopContext)
+                }
+            }
+        };
+        debug!("received command {cmd:?}");
+        match cmd {
+            HistoryCommand::Quit => {
+                break Ok(Navigation::Quit);
+            }
+            HistoryCommand::Back => {
+                break Ok(Navigation::PopContext);
+            }
+            HistoryCommand::Reload => {
+                break Ok(Navigation::Replace(NextScreen::History));
+            }
+            HistoryCommand::Clear => {
+                clear(cx.cache).await?;
+                break Ok(Navigation::Replace(NextScreen::History));
+            }
+            HistoryCommand::Prev => {
+                events.get_inner().left();
+            }
+            HistoryCommand::Next => {
+                events.get_inner().right();
+            }
+            HistoryCommand::Up => {
+                events.get_inner().up();
+            }
+            HistoryCommand::Down => {
+                events.get_inner().down();
+            }
+            HistoryCommand::Open => {
+                if let Some(entry) = events.get_inner().get() {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next: entry.open(),
+                    });
+                }
+            }
+            HistoryCommand::Play => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(next) = entry.play()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next,
+                    });
+                }
+            }
+            HistoryCommand::OpenEpisode => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(next) = entry.episode()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next,
+                    });
+                }
+            }
+            HistoryCommand::OpenSeason => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(next) = entry.season()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next,
+                    });
+                }
+            }
+            HistoryCommand::OpenSeries => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(next) = entry.series()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next,
+                    });
+                }
+            }
+            HistoryCommand::ToggleFavorite => {
+                if let Some(entry) = events.get_inner().get_mut()
+                    && let Err(e) = change_user_data(
+                        cx.jellyfin,
+                        entry,
+                        UserDataChange::ToggleFavorite,
+                        &cx.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
+            HistoryCommand::Activate => {
+                if let Some(entry) = events.get_inner().get() {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::History,
+                        next: entry.activate(cx.config.default_item_action),
+                    });
+                }
+            }
+        }
+    }
+}
@@ -70,6 +70,229 @@ in
       mpv_config_file = mkOption {
         type = types.nullOr types.path;
         default = null;
+        description = "mpv config file overriding all other mpv options";
+      };
+      mpv_profile_config_files = mkOption {
+        type = types.attrsOf types.path;
+        default = { };
+        description = "additional mpv config file for each profile, loaded before mpv_config_file";
+      };
+      mpv_cache = mkOption {
+        type = types.nullOr (
+          types.enum [
+            "auto"
+            "yes"
+            "no"
+          ]
+        );
+        default = null;
+        description = "network cache of mpv, null keeps the default of mpv";
+      };
+      mpv_cache_secs = mkOption {
+        type = types.nullOr types.ints.unsigned;
+        default = null;
+        description = "seconds of the stream mpv reads ahead";
+      };
+      mpv_demuxer_max_bytes = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        description = "maximum size of the read ahead of mpv, for example \"500MiB\"";
+      };
+      remember_volume_speed = mkOption {
+        type = types.bool;
+        default = false;
+        description = "restore volume and playback speed of the last session";
+      };
+      focus_follows_mouse = mkOption {
+        type = types.bool;
+        default = false;
+        description = "highlight the entry under the mouse cursor";
+      };
+      mouse_navigation = mkOption {
+        type = types.bool;
+        default = false;
+        description = "scroll with the mouse wheel and play entries by double clicking them";
+      };
+      single_click_play = mkOption {
+        type = types.bool;
+        default = false;
+        description = "play the clicked entry on a single click instead of a double click";
+      };
+      subtitle_search_language = mkOption {
+        type = types.str;
+        default = "eng";
+        description = "three letter language code used when searching subtitles";
+      };
+      sleep_timer_minutes = mkOption {
+        type = types.ints.positive;
+        default = 30;
+        description = "first duration of the sleep timer in minutes";
+      };
+      sleep_timer_action = mkOption {
+        type = types.enum [
+          "pause"
+          "quit"
+        ];
+        default = "pause";
+        description = "what happens when the sleep timer runs out";
+      };
+      on_playback_error = mkOption {
+        type = types.enum [
+          "skip"
+          "stop"
+        ];
+        default = "skip";
+        description = "what happens when an item fails to load";
+      };
+      quit_action = mkOption {
+        type = types.enum [
+          "back"
+          "exit"
+        ];
+        default = "back";
+        description = "what the quit keybind does, the back keybind always returns to the previous screen";
+      };
+      default_item_action = mkOption {
+        type = types.enum [
+          "open"
+          "play"
+          "details"
+        ];
+        default = "play";
+        description = "what the activate keybind does on an item";
+      };
+      scroll_style = mkOption {
+        type = types.enum [
+          "center"
+          "edge"
+        ];
+        default = "center";
+        description = "how library grids follow the selection";
+      };
+      prefetch_margin = mkOption {
+        type = types.ints.unsigned;
+        default = 1;
+        description = "rows of grids and entries of lists beyond the visible ones whose images are loaded ahead";
+      };
+      home_row_limit = mkOption {
+        type = types.ints.positive;
+        default = 16;
+        description = "number of items requested for each row of the home screen";
+      };
+      max_connections = mkOption {
+        type = types.ints.positive;
+        default = 4;
+        description = "maximum number of simultaneous connections to the server";
+      };
+      request_timeout = mkOption {
+        type = types.ints.unsigned;
+        default = 30;
+        description = "seconds after which a request to the server is given up, 0 waits forever";
+      };
+      request_retries = mkOption {
+        type = types.ints.unsigned;
+        default = 2;
+        description = "number of times a failed request loading data is repeated";
+      };
+      prefetch_next_item = mkOption {
+        type = types.bool;
+        default = false;
+        description = "load the image of the next playlist item and open the item in mpv while the current one plays";
+      };
+      now_playing_notification = mkOption {
+        type = types.bool;
+        default = false;
+        description = "show a desktop notification when a new item starts playing";
+      };
+      music_visualizer = mkOption {
+        type = types.bool;
+        default = false;
+        description = "animated bars below the track information while music plays";
+      };
+      inhibit_screensaver = mkOption {
+        type = types.bool;
+        default = true;
+        description = "keep the screensaver from activating while playing";
+      };
+      preferred_audio_lang = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        description = "comma separated language codes of the preferred audio track";
+      };
+      preferred_subtitle_lang = mkOption {
+        type = types.nullOr types.str;
+        default = null;
+        description = "comma separated language codes of the preferred subtitle track";
+      };
+      image_fetch_timeout = mkOption {
+        type = types.ints.positive;
+        default = 30;
+        description = "seconds after which fetching an image is given up";
+      };
+      resume_prompt = mkOption {
+        type = types.bool;
+        default = false;
+        description = "ask whether to resume or start over when playing a partially watched item";
+      };
+      autoplay_next = mkOption {
+        type = types.bool;
+        default = true;
+        description = "queue the following episodes of the series when playing an episode";
+      };
+      up_next_seconds = mkOption {
+        type = types.ints.unsigned;
+        default = 5;
+        description = "seconds the next episode waits after the previous one finished";
+      };
+      clear_images_on_play = mkOption {
+        type = types.bool;
+        default = true;
+        description = "remove images from the terminal while playing";
+      };
+      library_stats = mkOption {
+        type = types.bool;
+        default = false;
+        description = "show item count and runtime of each library on the home screen";
+      };
+      cache_dir = mkOption {
+        type = types.nullOr types.path;
+        default = null;
+        description = "directory of the cache database, defaults to the user cache directory";
+      };
+      image_cache_size = mkOption {
+        type = types.ints.unsigned;
+        default = 500;
+        description = "maximum size of cached images in MiB, 0 disables the limit";
+      };
+      memory_image_cache_size = mkOption {
+        type = types.ints.unsigned;
+        default = 200;
+        description = "number of images kept ready for display in memory, 0 disables the limit";
+      };
+      image_decode_concurrency = mkOption {
+        type = types.ints.unsigned;
+        default = 0;
+        description = "maximum number of images decoded at the same time, 0 uses the number of cpu cores";
+      };
+      key_sequence_timeout = mkOption {
+        type = types.ints.unsigned;
+        default = 0;
+        description = "seconds after which a partially entered key sequence is discarded, 0 disables the timeout";
+      };
+      icons = mkOption {
+        type = types.attrsOf types.str;
+        default = { };
+        description = "symbols used to display item state, for fonts lacking the default glyphs";
+      };
+      theme = mkOption {
+        type = types.attrsOf types.str;
+        default = { };
+        description = "colors of the interface roles border, active_border, error, accent and watch_status";
+      };
+      images = mkOption {
+        type = types.attrsOf (types.either types.int types.str);
+        default = { };
+        description = "width of entry images in cells and \"width:height\" ratios of the primary, thumb, backdrop and other images";
       };
     };
     keybinds = mkOption {
@@ -81,6 +304,11 @@ in
       type = lib.types.nullOr (
         lib.types.submodule {
           options = {
+            display_name = mkOption {
+              type = lib.types.nullOr lib.types.str;
+              default = null;
+              description = "friendly name shown instead of the server url";
+            };
             server_url = mkOption {
               type = lib.types.str;
             };
@@ -95,6 +323,11 @@ in
               type = lib.types.nullOr (lib.types.listOf lib.types.str);
               default = null;
             };
+            api_key = mkOption {
+              type = lib.types.nullOr lib.types.str;
+              default = null;
+              description = "API key used instead of the password";
+            };
           };
         }
       );
@@ -1,29 +1,41 @@
-use std::{collections::HashMap, pin::Pin};
+use std::{
+    collections::HashMap,
+    pin::{Pin, pin},
+    sync::Arc,
+};
 
 use color_eyre::eyre::{Context, Result};
 use entries::{
-    entry::Entry, image::available::ImagesAvailable, list::EntryList, screen::EntryScreen,
+    context::EntryContext,
+    entry::{Entry, EntryInner},
+    image::available::ImagesAvailable,
+    list::EntryList,
+    mouse::MouseNavigation,
+    screen::EntryScreen,
 };
 use futures_util::StreamExt;
 use jellyfin::{items::MediaItem, user_views::UserView};
 use jellyhaj_core::{
     context::TuiContext,
-    entries::EntryExt,
+    entries::{EntryExt, UserDataChange, change_user_data},
     keybinds::HomeScreenCommand,
-    state::{Navigation, NextScreen},
+    state::{Navigation, NextScreen, global_screen},
 };
 use ratatui_fallible_widget::TermExt;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
+
+use crate::library_stats::fetch_library_stats;
 
-use keybinds::{KeybindEvent, KeybindEventStream};
+use keybinds::{ClickTracker, KeybindEvent, KeybindEventStream};
 
+mod library_stats;
 pub mod load;
 
 fn create_from_media_item_vec(
     items: Vec<MediaItem>,
     title: &str,
     context: &TuiContext,
-    images_available: &ImagesAvailable,
+    entry_cx: &Arc<EntryContext>,
 ) -> Result<Option<EntryList>> {
     Ok(if items.is_empty() {
         None
@@ -32,19 +44,12 @@ fn create_from_media_item_vec(
             items
                 .into_iter()
                 .filter_map(|item| {
-                    Entry::from_media_item(
-                        item,
-                        &context.jellyfin,
-                        &context.cache,
-                        &context.image_cache,
-                        images_available,
-                        &context.image_picker,
-                        &context.stats,
-                    )
-                    .transpose()
+                    Entry::from_media_item(item, entry_cx, &context.config.icons).transpose()
                 })
                 .collect::<Result<Vec<_>>>()?,
             title.to_string(),
+            context.config.image_layout,
+            context.config.prefetch_margin,
         )
         .into()
     })
@@ -54,7 +59,7 @@ fn create_from_user_views_vec(
     items: Vec<UserView>,
     title: &str,
     context: &TuiContext,
-    images_available: &ImagesAvailable,
+    entry_cx: &Arc<EntryContext>,
 ) -> Result<Option<EntryList>> {
     Ok(if items.is_empty() {
         None
@@ -62,43 +67,40 @@ fn create_from_user_views_vec(
         EntryList::new(
             items
                 .into_iter()
-                .map(|item| {
-                    Entry::from_user_view(
-                        item,
-                        &context.jellyfin,
-                        &context.cache,
-                        &context.image_cache,
-                        images_available,
-                        &context.image_picker,
-                        &context.stats,
-                    )
-                })
+                .map(|item| Entry::from_user_view(item, entry_cx))
                 .collect::<Result<Vec<_>>>()?,
             title.to_string(),
+            context.config.image_layout,
+            context.config.prefetch_margin,
         )
         .into()
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn create_home_screen(
     resume: Vec<MediaItem>,
     next_up: Vec<MediaItem>,
+    recently_added: Vec<MediaItem>,
+    favorites: Vec<MediaItem>,
     views: Vec<UserView>,
     mut latest: HashMap<String, Vec<MediaItem>>,
     context: &TuiContext,
     images_available: &ImagesAvailable,
 ) -> Result<EntryScreen> {
+    let entry_cx = context.entry_context(images_available);
     let entries = [
-        create_from_media_item_vec(resume, "Continue Watching", context, images_available)
+        create_from_media_item_vec(resume, "Continue Watching", context, &entry_cx).transpose(),
+        create_from_media_item_vec(next_up, "Next Up", context, &entry_cx).transpose(),
+        create_from_media_item_vec(recently_added, "Recently Added", context, &entry_cx)
             .transpose(),
-        create_from_media_item_vec(next_up, "Next Up", context, images_available).transpose(),
-        create_from_user_views_vec(views.clone(), "Library", context, images_available).transpose(),
+        create_from_media_item_vec(favorites, "Favorites", context, &entry_cx).transpose(),
+        create_from_user_views_vec(views.clone(), "Library", context, &entry_cx).transpose(),
     ]
     .into_iter()
     .chain(views.iter().map(|view| {
         latest.remove(view.id.as_str()).and_then(|items| {
-            create_from_media_item_vec(items, view.name.as_str(), context, images_available)
-                .transpose()
+            create_from_media_item_vec(items, view.name.as_str(), context, &entry_cx).transpose()
         })
     }))
     .flatten()
@@ -107,6 +109,7 @@ fn create_home_screen(
         entries,
         "Home".to_string(),
         context.image_picker.clone(),
+        context.config.image_layout,
     ))
 }
 
@@ -114,11 +117,22 @@ pub fn handle_home_screen_data(
     context: Pin<&mut TuiContext>,
     resume: Vec<MediaItem>,
     next_up: Vec<MediaItem>,
+    recently_added: Vec<MediaItem>,
+    favorites: Vec<MediaItem>,
     views: Vec<UserView>,
     latest: HashMap<String, Vec<MediaItem>>,
 ) -> Result<Navigation> {
     let images_available = ImagesAvailable::new();
-    let screen = create_home_screen(resume, next_up, views, latest, &context, &images_available)?;
+    let screen = create_home_screen(
+        resume,
+        next_up,
+        recently_added,
+        favorites,
+        views,
+        latest,
+        &context,
+        &images_available,
+    )?;
     Ok(Navigation::Replace(NextScreen::HomeScreen(
         screen,
         images_available,
@@ -132,22 +146,69 @@ pub async fn display_home_screen(
     images_available: ImagesAvailable,
 ) -> Result<Navigation> {
     let context = context.project();
+    let mut stats_pending = context.config.library_stats;
+    let views = if stats_pending {
+        screen
+            .iter_mut()
+            .filter_map(|entry| match entry.inner() {
+                EntryInner::View(view) => Some(view.clone()),
+                EntryInner::Item(_) => None,
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let mut library_stats = pin!(fetch_library_stats(context.jellyfin, context.library_runtimes, views));
     let mut events = KeybindEventStream::new(
         context.events,
         &mut screen,
         context.config.keybinds.home_screen.clone(),
         &context.config.help_prefixes,
     );
+    events.set_mouse_input(context.config.capture_mouse());
+    let mut clicks = ClickTracker::new(context.config.single_click_play);
     loop {
         context.term.draw_fallible(&mut events)?;
         let cmd = tokio::select! {
             _ = images_available.wait_available() => {continue ;
             }
+            stats = library_stats.next(), if stats_pending => {
+                match stats {
+                    Some((id, Ok(Some(subtitle)))) => {
+                        if let Some(entry) = events.get_inner().iter_mut().find(|entry| {
+                            matches!(entry.inner(), EntryInner::View(view) if view.id == id)
+                        }) {
+                            entry.set_subtitle(subtitle);
+                        }
+                    }
+                    Some((_, Ok(None))) => {}
+                    Some((_, Err(e))) => warn!("unable to fetch library stats: {e:?}"),
+                    None => stats_pending = false,
+                }
+                continue
+            }
             term = events.next() => {
                 match term {
                     Some(Ok(KeybindEvent::Command(cmd))) => cmd,
                     Some(Ok(KeybindEvent::Text(_))) => unimplemented!(),
+                    Some(Ok(KeybindEvent::Mouse(event))) => {
+                        let hover = context.config.focus_follows_mouse;
+                        if events.get_inner().handle_mouse(event, hover, &mut clicks) {
+                            HomeScreenCommand::Play
+                        } else {
+                            continue
+                        }
+                    }
                     Some(Ok(KeybindEvent::Render)) => continue ,
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: NextScreen::LoadHomeScreen,
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
                     Some(Err(e)) => break  Err(e).context("getting key events from terminal"),
                     None => break  Ok(Navigation::Exit)
                 }
@@ -156,8 +217,10 @@ pub async fn display_home_screen(
         debug!("received command {cmd:?}");
         match cmd {
             HomeScreenCommand::Quit => {
-                break Ok(Navigation::PopContext);
+                break Ok(Navigation::Quit);
             }
+            // nothing to go back to from the home screen
+            HomeScreenCommand::Back => {}
             HomeScreenCommand::Reload => {
                 break Ok(Navigation::Replace(NextScreen::LoadHomeScreen));
             }
@@ -168,6 +231,20 @@ pub async fn display_home_screen(
             HomeScreenCommand::Right => {
                 events.get_inner().right();
             }
+            HomeScreenCommand::PageLeft => {
+                events.get_inner().page_left();
+                continue;
+            }
+            HomeScreenCommand::PageRight => {
+                events.get_inner().page_right();
+            }
+            HomeScreenCommand::First => {
+                events.get_inner().first();
+                continue;
+            }
+            HomeScreenCommand::Last => {
+                events.get_inner().last();
+            }
             HomeScreenCommand::Up => {
                 events.get_inner().up();
             }
@@ -223,6 +300,16 @@ pub async fn display_home_screen(
                     });
                 }
             }
+            HomeScreenCommand::ShufflePlay => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(next) = entry.shuffle()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next,
+                    });
+                }
+            }
             HomeScreenCommand::PlayOpen => {
                 if let Some(entry) = events.get_inner().get() {
                     let next = entry.play_open();
@@ -232,6 +319,15 @@ pub async fn display_home_screen(
                     });
                 }
             }
+            HomeScreenCommand::Activate => {
+                if let Some(entry) = events.get_inner().get() {
+                    let next = entry.activate(context.config.default_item_action);
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next,
+                    });
+                }
+            }
             HomeScreenCommand::RefreshItem => {
                 if let Some(entry) = events.get_inner().get()
                     && let Some(id) = entry.item_id()
@@ -242,18 +338,88 @@ pub async fn display_home_screen(
                     });
                 }
             }
+            HomeScreenCommand::AddToPlaylist => {
+                if let Some(entry) = events.get_inner().get()
+                    && let Some(id) = entry.item_id()
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next: NextScreen::LoadAddToPlaylist(id.to_string()),
+                    });
+                }
+            }
+            HomeScreenCommand::ResetProgress => {
+                if let Some(entry) = events.get_inner().get_mut()
+                    && let Err(e) = change_user_data(
+                        context.jellyfin,
+                        entry,
+                        UserDataChange::ResetProgress,
+                        &context.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
+            HomeScreenCommand::ToggleFavorite => {
+                if let Some(entry) = events.get_inner().get_mut()
+                    && let Err(e) = change_user_data(
+                        context.jellyfin,
+                        entry,
+                        UserDataChange::ToggleFavorite,
+                        &context.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
+            HomeScreenCommand::ToggleWatched => {
+                if let Some(entry) = events.get_inner().get_mut()
+                    && let Err(e) = change_user_data(
+                        context.jellyfin,
+                        entry,
+                        UserDataChange::ToggleWatched,
+                        &context.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::LoadHomeScreen,
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
             HomeScreenCommand::ShowStats => {
                 break Ok(Navigation::Push {
                     current: NextScreen::LoadHomeScreen,
                     next: NextScreen::Stats,
                 });
             }
+            HomeScreenCommand::ShowHistory => {
+                break Ok(Navigation::Push {
+                    current: NextScreen::LoadHomeScreen,
+                    next: NextScreen::History,
+                });
+            }
             HomeScreenCommand::ShowLogs => {
                 break Ok(Navigation::Push {
                     current: NextScreen::LoadHomeScreen,
                     next: NextScreen::Logs,
                 });
             }
+            HomeScreenCommand::Search => {
+                break Ok(Navigation::Push {
+                    current: NextScreen::LoadHomeScreen,
+                    next: NextScreen::Search,
+                });
+            }
         }
     }
 }
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use color_eyre::{Result, eyre::Context};
+use futures_util::{Stream, StreamExt, stream};
+use jellyfin::{
+    Auth, JellyfinClient, JellyfinVec,
+    items::GetItemsQuery,
+    user_views::{CollectionType, UserView},
+};
+use parking_lot::Mutex;
+
+/// number of libraries fetched at the same time
+const CONCURRENT_FETCHES: usize = 2;
+
+/// Subtitle for every library with a known collection type, completes once all are fetched.
+///
+/// The runtime needs every item of the library, so it is only counted once per library and kept
+/// in `runtimes`.
+pub fn fetch_library_stats(
+    jellyfin: &JellyfinClient<Auth>,
+    runtimes: &Mutex<HashMap<String, u64>>,
+    views: Vec<UserView>,
+) -> impl Stream<Item = (String, Result<Option<String>>)> {
+    stream::iter(views)
+        .map(move |view| async move {
+            let stats = library_stats(jellyfin, runtimes, &view)
+                .await
+                .with_context(|| format!("fetching stats of {}", view.name));
+            (view.id, stats)
+        })
+        .buffer_unordered(CONCURRENT_FETCHES)
+}
+
+async fn library_stats(
+    jellyfin: &JellyfinClient<Auth>,
+    runtimes: &Mutex<HashMap<String, u64>>,
+    view: &UserView,
+) -> Result<Option<String>> {
+    let (item_types, noun, runtime_types) = match view.collection_type {
+        CollectionType::Movies => ("Movie", "movies", Some("Movie")),
+        CollectionType::TvShows => ("Series", "series", Some("Episode")),
+        CollectionType::Music => ("MusicAlbum", "albums", None),
+        CollectionType::HomeVideos => ("Video", "videos", Some("Video")),
+        CollectionType::Books => ("Book", "books", None),
+        CollectionType::BoxSets => ("BoxSet", "collections", None),
+        CollectionType::Playlists | CollectionType::MixedContent | CollectionType::Unknown => {
+            return Ok(None);
+        }
+    };
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    let count = jellyfin
+        .get_items(&GetItemsQuery {
+            user_id: user_id.into(),
+            limit: 0.into(),
+            parent_id: view.id.as_str().into(),
+            include_item_types: item_types.into(),
+            recursive: true.into(),
+            enable_images: false.into(),
+            enable_user_data: false.into(),
+            ..Default::default()
+        })
+        .await
+        .context("requesting item count")?
+        .deserialize()
+        .await
+        .context("deserializing item count")?
+        .total_record_count
+        .unwrap_or_default();
+    let Some(runtime_types) = runtime_types else {
+        return Ok(Some(format!("{count} {noun}")));
+    };
+    if let Some(hours) = runtimes.lock().get(&view.id) {
+        return Ok(Some(format!("{count} {noun}, {hours}h")));
+    }
+    let items = JellyfinVec::collect(async |start| {
+        jellyfin
+            .get_items(&GetItemsQuery {
+                user_id: user_id.into(),
+                start_index: start.into(),
+                limit: 500.into(),
+                parent_id: view.id.as_str().into(),
+                include_item_types: runtime_types.into(),
+                recursive: true.into(),
+                enable_images: false.into(),
+                enable_user_data: false.into(),
+                ..Default::default()
+            })
+            .await
+            .context("requesting items")?
+            .deserialize()
+            .await
+            .context("deserializing items")
+    })
+    .await?;
+    let ticks: u64 = items.iter().filter_map(|item| item.run_time_ticks).sum();
+    let hours = ticks / 10_000_000 / 3600;
+    runtimes.lock().insert(view.id.clone(), hours);
+    Ok(Some(format!("{count} {noun}, {hours}h")))
+}
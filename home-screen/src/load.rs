@@ -4,10 +4,11 @@ use color_eyre::{Result, eyre::Context};
 use futures_util::{StreamExt, TryStreamExt, stream};
 use jellyfin::{
     JellyfinClient,
-    items::{GetNextUpQuery, GetResumeQuery, MediaItem},
+    items::{GetItemsQuery, GetNextUpQuery, GetResumeQuery, MediaItem},
     user_library::GetLatestQuery,
     user_views::{CollectionType, GetUserViewsQuery, UserView, UserViewType},
 };
+use tokio::sync::watch;
 use tracing::{debug, instrument, trace};
 
 use jellyhaj_core::{
@@ -15,19 +16,57 @@ use jellyhaj_core::{
     state::{Navigation, NextScreen},
 };
 
-use fetch::fetch_screen;
+use fetch::{Progress, fetch_screen_progress};
 
 #[derive(Debug)]
 pub struct HomeScreenData {
     pub resume: Vec<MediaItem>,
     pub next_up: Vec<MediaItem>,
+    /// latest items across all libraries
+    pub recently_added: Vec<MediaItem>,
+    pub favorites: Vec<MediaItem>,
     pub views: Vec<UserView>,
     pub latest: HashMap<String, Vec<MediaItem>>,
 }
 
+async fn get_favorites(
+    client: &JellyfinClient,
+    user_id: &str,
+    limit: u32,
+) -> Result<Vec<MediaItem>> {
+    Ok(client
+        .get_items(&GetItemsQuery {
+            user_id: user_id.into(),
+            limit: limit.into(),
+            recursive: true.into(),
+            filters: "IsFavorite".into(),
+            include_item_types: "Movie, Series, Season, Episode, MusicAlbum, Playlist".into(),
+            enable_images: true.into(),
+            enable_image_types: "Thumb, Backdrop, Primary".into(),
+            image_type_limit: 1.into(),
+            enable_user_data: true.into(),
+            fields: "Overview".into(),
+            sort_by: "SortName".into(),
+            sort_order: "Ascending".into(),
+            ..Default::default()
+        })
+        .await
+        .context("fetching favorites")?
+        .deserialize()
+        .await
+        .context("deserializing favorites")?
+        .items)
+}
+
 #[instrument(skip_all)]
-async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenData> {
+async fn load_data(
+    client: &JellyfinClient,
+    user_id: &str,
+    limit: u32,
+    progress: &watch::Sender<Progress>,
+) -> Result<HomeScreenData> {
     debug!("collecting main screen information");
+    progress.send_replace(Progress::message("Loading libraries…"));
     let user_views = client
         .get_user_views(&GetUserViewsQuery {
             user_id: Some(user_id),
@@ -41,10 +80,11 @@ async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenD
         .await
         .context("deserializing user views")?;
     trace!("user_views: {user_views:#?}");
+    progress.send_replace(Progress::message("Loading continue watching…"));
     let resume = client
         .get_user_items_resume(&GetResumeQuery {
             user_id: user_id.into(),
-            limit: 16.into(),
+            limit: limit.into(),
             enable_user_data: true.into(),
             image_type_limit: 1.into(),
             enable_image_types: "Thumb, Backdrop, Primary".into(),
@@ -62,10 +102,11 @@ async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenD
         .await
         .context("deserializing resumes")?;
     trace!("resume: {resume:#?}");
+    progress.send_replace(Progress::message("Loading next up…"));
     let next_up = client
         .get_shows_next_up(&GetNextUpQuery {
             user_id: Some(user_id),
-            limit: Some(16),
+            limit: Some(limit),
             enable_user_data: Some(true),
             enable_images: Some(true),
             fields: "Overview".into(),
@@ -83,15 +124,41 @@ async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenD
         .await
         .context("deserializing next up")?;
     trace!("next up: {next_up:#?}");
+    progress.send_replace(Progress::message("Loading recently added…"));
+    let recently_added = client
+        .get_user_library_latest_media(&GetLatestQuery {
+            user_id: Some(user_id),
+            limit: Some(limit),
+            enable_user_data: Some(true),
+            enable_images: Some(true),
+            image_type_limit: Some(1),
+            fields: "Overview".into(),
+            enable_image_types: Some("Thumb, Backdrop, Primary"),
+            group_items: Some(true),
+            ..Default::default()
+        })
+        .await
+        .context("fetching recently added")?
+        .deserialize()
+        .await
+        .context("deserializing recently added")?;
+    trace!("recently added: {recently_added:#?}");
+    progress.send_replace(Progress::message("Loading favorites…"));
+    let favorites = get_favorites(client, user_id, limit).await?;
+    trace!("favorites: {favorites:#?}");
+    progress.send_replace(Progress::message("Loading latest media…"));
     let latest: HashMap<_, _> = stream::iter(user_views.items.iter())
         .filter_map(async |view| {
             if view.view_type == UserViewType::CollectionFolder
-                && view.collection_type != CollectionType::Unknown
+                && !matches!(
+                    view.collection_type,
+                    CollectionType::Books | CollectionType::Unknown
+                )
             {
                 match client
                     .get_user_library_latest_media(&GetLatestQuery {
                         user_id: Some(user_id),
-                        limit: Some(16),
+                        limit: Some(limit),
                         enable_user_data: Some(true),
                         enable_images: Some(true),
                         image_type_limit: Some(1),
@@ -122,6 +189,8 @@ async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenD
     Ok(HomeScreenData {
         resume: resume.items,
         next_up: next_up.items,
+        recently_added,
+        favorites,
         views: user_views.items,
         latest,
     })
@@ -131,10 +200,11 @@ async fn load_data(client: &JellyfinClient, user_id: &str) -> Result<HomeScreenD
 pub async fn load_home_screen(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
     let cx = cx.project();
     let jellyfin = cx.jellyfin;
-    fetch_screen(
-        "Loading home screen",
+    let limit = cx.config.home_row_limit.get();
+    let (progress, progress_receiver) = watch::channel(Progress::message("Loading home screen"));
+    fetch_screen_progress(
         async {
-            match load_data(jellyfin, &jellyfin.get_auth().user.id)
+            match load_data(jellyfin, &jellyfin.get_auth().user.id, limit, &progress)
                 .await
                 .context("Loading home screen data")
             {
@@ -145,11 +215,14 @@ pub async fn load_home_screen(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
                 Ok(data) => Ok(Navigation::Replace(NextScreen::HomeScreenData {
                     resume: data.resume,
                     next_up: data.next_up,
+                    recently_added: data.recently_added,
+                    favorites: data.favorites,
                     views: data.views,
                     latest: data.latest,
                 })),
             }
         },
+        progress_receiver,
         cx.events,
         cx.config.keybinds.fetch.clone(),
         cx.term,
@@ -1,25 +1,96 @@
-use std::{cmp::min, pin::Pin};
+use std::{borrow::Cow, cmp::min, pin::Pin, pin::pin};
 
 use color_eyre::{Result, eyre::Context};
 use entries::{
-    entry::{ENTRY_WIDTH, Entry, entry_height},
+    entry::{Entry, entry_height, entry_width, format_runtime},
     image::available::ImagesAvailable,
 };
 use fetch::{fetch_child_of_type, fetch_screen};
 use futures_util::StreamExt;
-use jellyfin::items::MediaItem;
+use jellyfin::{Auth, JellyfinClient, items::MediaItem};
 use jellyhaj_core::{
     context::TuiContext,
     keybinds::ItemDetailsCommand,
-    state::{Navigation, NextScreen, ToNavigation},
+    state::{
+        ItemLink, ItemSort, LinkKind, Navigation, NextScreen, ToNavigation, UserViewFilter,
+        global_screen,
+    },
 };
-use keybinds::{KeybindEvent, KeybindEventStream};
+use keybinds::{KeybindEvent, KeybindEventStream, retain_commands};
 use ratatui::{
     layout::{Constraint, Layout, Margin},
-    text::Text,
-    widgets::{Block, Padding, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget},
+    style::Stylize,
+    text::{Line, Span, Text},
+    widgets::{Block, Padding, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget, Wrap},
 };
 use ratatui_fallible_widget::{FallibleWidget, TermExt};
+use tracing::warn;
+
+/// number of people shown as links on the details screen
+const MAX_PEOPLE: usize = 5;
+
+const LINK_KINDS: [(LinkKind, &str); 3] = [
+    (LinkKind::Genre, "Genres"),
+    (LinkKind::Studio, "Studios"),
+    (LinkKind::Person, "People"),
+];
+
+fn item_links(item: &MediaItem) -> Vec<ItemLink> {
+    let genres = item.genre_items.iter().flatten().map(|genre| ItemLink {
+        kind: LinkKind::Genre,
+        id: genre.id.clone(),
+        name: genre.name.clone(),
+        parent: None,
+    });
+    let studios = item.studios.iter().flatten().map(|studio| ItemLink {
+        kind: LinkKind::Studio,
+        id: studio.id.clone(),
+        name: studio.name.clone(),
+        parent: None,
+    });
+    let people = item
+        .people
+        .iter()
+        .flatten()
+        .take(MAX_PEOPLE)
+        .map(|person| ItemLink {
+            kind: LinkKind::Person,
+            id: person.id.clone(),
+            name: person.name.clone(),
+            parent: None,
+        });
+    genres.chain(studios).chain(people).collect()
+}
+
+/// items in lists only contain the requested fields, the single item endpoint returns all of them
+async fn fetch_links(jellyfin: &JellyfinClient<Auth>, id: &str) -> Result<Vec<ItemLink>> {
+    let item = jellyfin
+        .get_item(id, Some(&jellyfin.get_auth().user.id))
+        .await
+        .context("requesting item")?
+        .deserialize()
+        .await
+        .context("deserializing item")?;
+    Ok(item_links(&item))
+}
+
+/// errors keep the download subtitles command available, the search then shows the error
+async fn has_subtitle_providers(jellyfin: &JellyfinClient<Auth>) -> bool {
+    let options = async {
+        jellyfin
+            .get_available_library_options()
+            .await?
+            .deserialize()
+            .await
+    };
+    match options.await {
+        Ok(options) => !options.subtitle_fetchers.is_empty(),
+        Err(e) => {
+            warn!("unable to check for subtitle providers: {e:?}");
+            true
+        }
+    }
+}
 
 pub async fn display_fetch_item(cx: Pin<&mut TuiContext>, parent: &str) -> Result<Navigation> {
     let cx = cx.project();
@@ -44,11 +115,71 @@ pub async fn display_fetch_item(cx: Pin<&mut TuiContext>, parent: &str) -> Resul
 struct ItemDisplay<'s> {
     entry: &'s mut Entry,
     height: u16,
+    entry_width: u16,
     width: Option<u16>,
+    /// overview wrapped to `width`
+    overview: Vec<String>,
     scrollbar_state: ScrollbarState,
     scrollbar_pos: u16,
     scrollbar_len: u16,
+    /// visible lines of the overview in the last render
+    page: u16,
     item: &'s MediaItem,
+    links: Vec<ItemLink>,
+    selected_link: usize,
+}
+
+impl ItemDisplay<'_> {
+    /// scroll position at which the last line of the overview is at the bottom
+    fn max_scroll(&self) -> u16 {
+        self.scrollbar_len.saturating_sub(self.page)
+    }
+
+    fn scroll_down(&mut self, lines: u16) {
+        self.scrollbar_pos = min(self.scrollbar_pos + lines, self.max_scroll());
+    }
+
+    /// production year, rating and runtime followed by the browsable links
+    fn details_paragraph(&self) -> Paragraph<'_> {
+        let facts: Vec<_> = [
+            self.item.production_year.map(|year| year.to_string()),
+            self.item
+                .community_rating
+                .map(|rating| format!("★ {rating:.1}")),
+            self.item.run_time_ticks.map(format_runtime),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let links = LINK_KINDS.iter().filter_map(|(kind, label)| {
+            let mut spans = vec![Span::from(format!("{label}: ")).bold()];
+            for (index, link) in self
+                .links
+                .iter()
+                .enumerate()
+                .filter(|(_, link)| link.kind == *kind)
+            {
+                if spans.len() > 1 {
+                    spans.push(Span::from(", "));
+                }
+                let span = Span::from(link.name.as_str());
+                spans.push(if index == self.selected_link {
+                    span.reversed()
+                } else {
+                    span
+                });
+            }
+            (spans.len() > 1).then(|| Line::from(spans))
+        });
+        let lines: Vec<_> = (!facts.is_empty())
+            .then(|| Line::from(facts.join(" · ")))
+            .into_iter()
+            .chain(links)
+            .collect();
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .block(Block::bordered().title("Details"))
+    }
 }
 
 impl FallibleWidget for ItemDisplay<'_> {
@@ -61,38 +192,51 @@ impl FallibleWidget for ItemDisplay<'_> {
             .title(self.item.name.as_str())
             .padding(ratatui::widgets::Padding::uniform(1));
         let main = block.inner(area);
-        let [entry_area, descripton_area] =
+        let [top_area, descripton_area] =
             Layout::vertical([Constraint::Length(self.height), Constraint::Min(1)])
                 .spacing(1)
                 .areas(main);
-        let [entry_area] = Layout::horizontal([Constraint::Length(ENTRY_WIDTH)]).areas(entry_area);
+        let [entry_area, details_area] =
+            Layout::horizontal([Constraint::Length(self.entry_width), Constraint::Min(1)])
+                .spacing(1)
+                .areas(top_area);
         self.entry.render_fallible(entry_area, buf)?;
+        self.details_paragraph().render(details_area, buf);
+        let Some(overview) = &self.item.overview else {
+            return Ok(());
+        };
         let w = descripton_area.width.saturating_sub(4);
         if self.width != Some(w) {
             self.width = Some(w);
-            if let Some(d) = &self.item.overview {
-                let lines = textwrap::wrap(d, w as usize);
-                self.scrollbar_state = self.scrollbar_state.content_length(lines.len());
-                self.scrollbar_len = lines.len() as u16;
-                self.scrollbar_pos = min(self.scrollbar_pos, self.scrollbar_len - 1);
-                Paragraph::new(Text::from_iter(lines))
-                    .block(
-                        Block::bordered()
-                            .title("Overview")
-                            .padding(Padding::uniform(1)),
-                    )
-                    .scroll((self.scrollbar_pos, 0))
-                    .render(descripton_area, buf);
-                Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight).render(
-                    descripton_area.inner(Margin {
-                        horizontal: 0,
-                        vertical: 2,
-                    }),
-                    buf,
-                    &mut self.scrollbar_state,
-                );
-            }
+            self.overview = textwrap::wrap(overview, w as usize)
+                .into_iter()
+                .map(Cow::into_owned)
+                .collect();
+            self.scrollbar_len = self.overview.len() as u16;
         }
+        self.page = descripton_area.height.saturating_sub(4);
+        self.scrollbar_pos = min(self.scrollbar_pos, self.max_scroll());
+        self.scrollbar_state = self
+            .scrollbar_state
+            .content_length(self.max_scroll() as usize + 1)
+            .viewport_content_length(self.page as usize)
+            .position(self.scrollbar_pos as usize);
+        Paragraph::new(Text::from_iter(self.overview.iter().map(String::as_str)))
+            .block(
+                Block::bordered()
+                    .title("Overview")
+                    .padding(Padding::uniform(1)),
+            )
+            .scroll((self.scrollbar_pos, 0))
+            .render(descripton_area, buf);
+        Scrollbar::new(ratatui::widgets::ScrollbarOrientation::VerticalRight).render(
+            descripton_area.inner(Margin {
+                horizontal: 0,
+                vertical: 2,
+            }),
+            buf,
+            &mut self.scrollbar_state,
+        );
         Ok(())
     }
 }
@@ -100,15 +244,8 @@ impl FallibleWidget for ItemDisplay<'_> {
 //also works with movies
 pub async fn display_item(cx: Pin<&mut TuiContext>, item: MediaItem) -> Result<Navigation> {
     let images_available = ImagesAvailable::new();
-    let entry = Entry::from_media_item(
-        item.clone(),
-        &cx.jellyfin,
-        &cx.cache,
-        &cx.image_cache,
-        &images_available,
-        &cx.image_picker,
-        &cx.stats,
-    )?;
+    let entry_cx = cx.entry_context(&images_available);
+    let entry = Entry::from_media_item(item.clone(), &entry_cx, &cx.config.icons)?;
     let mut entry = if let Some(entry) = entry {
         entry
     } else {
@@ -116,45 +253,94 @@ pub async fn display_item(cx: Pin<&mut TuiContext>, item: MediaItem) -> Result<N
     };
     let mut widget = ItemDisplay {
         entry: &mut entry,
-        height: entry_height(cx.image_picker.font_size()),
+        height: entry_height(cx.image_picker.font_size(), cx.config.image_layout),
+        entry_width: entry_width(cx.config.image_layout),
         width: None,
+        overview: Vec::new(),
         scrollbar_state: ScrollbarState::new(0),
         scrollbar_pos: 0,
         scrollbar_len: 0,
+        page: 0,
         item: &item,
+        links: item_links(&item),
+        selected_link: 0,
     };
     let cx = cx.project();
-    let mut events = KeybindEventStream::new(
-        cx.events,
-        &mut widget,
-        cx.config.keybinds.item_details.clone(),
-        &cx.config.help_prefixes,
+    let mut links_pending = widget.links.is_empty();
+    let item_id = item.id.clone();
+    let mut fetch_links = pin!(fetch_links(cx.jellyfin, &item_id));
+    let jellyfin = cx.jellyfin;
+    let mut providers_pending = cx.subtitle_providers.get().is_none();
+    let mut fetch_providers = pin!(
+        cx.subtitle_providers
+            .get_or_init(|| has_subtitle_providers(jellyfin))
     );
+    let keybinds = &cx.config.keybinds.item_details;
+    // the command is hidden until the server is known to have a subtitle provider
+    let map = if cx.subtitle_providers.get() == Some(&true) {
+        keybinds.clone()
+    } else {
+        retain_commands(keybinds, &|c| {
+            !matches!(c, ItemDetailsCommand::DownloadSubtitles)
+        })
+    };
+    let mut events = KeybindEventStream::new(cx.events, &mut widget, map, &cx.config.help_prefixes);
     loop {
         cx.term.draw_fallible(&mut events)?;
         let cmd = tokio::select! {
             _ = images_available.wait_available() => {continue          }
+            links = &mut fetch_links, if links_pending => {
+                links_pending = false;
+                match links {
+                    Ok(links) => events.get_inner().links = links,
+                    Err(e) => warn!("unable to fetch genres, studios and people: {e:?}"),
+                }
+                continue
+            }
+            providers = &mut fetch_providers, if providers_pending => {
+                providers_pending = false;
+                if *providers {
+                    events.set_map(keybinds.clone());
+                }
+                continue
+            }
             term = events.next() => {
                 match term {
                     Some(Ok(KeybindEvent::Command(cmd))) => cmd,
                     Some(Ok(KeybindEvent::Render)) => continue ,
                     Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: NextScreen::ItemDetails(item),
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
                     Some(Err(e)) => break  Err(e).context("getting key events from terminal"),
                     None => break  Ok(Navigation::PopContext)
                 }
             }
         };
         match cmd {
-            ItemDetailsCommand::Quit => break Ok(Navigation::PopContext),
+            ItemDetailsCommand::Quit => break Ok(Navigation::Quit),
+            ItemDetailsCommand::Back => break Ok(Navigation::PopContext),
             ItemDetailsCommand::Up => {
-                events.get_inner().scrollbar_pos = min(
-                    events.get_inner().scrollbar_pos + 1,
-                    events.get_inner().scrollbar_len - 1,
-                );
+                let widget = events.get_inner();
+                widget.scrollbar_pos = widget.scrollbar_pos.saturating_sub(1);
             }
             ItemDetailsCommand::Down => {
-                events.get_inner().scrollbar_pos =
-                    events.get_inner().scrollbar_pos.saturating_sub(1);
+                events.get_inner().scroll_down(1);
+            }
+            ItemDetailsCommand::PageUp => {
+                let widget = events.get_inner();
+                widget.scrollbar_pos = widget.scrollbar_pos.saturating_sub(widget.page.max(1));
+            }
+            ItemDetailsCommand::PageDown => {
+                let widget = events.get_inner();
+                widget.scroll_down(widget.page.max(1));
             }
             ItemDetailsCommand::Reload => {
                 break Ok(Navigation::Replace(NextScreen::FetchItemDetails(item.id)));
@@ -173,6 +359,43 @@ pub async fn display_item(cx: Pin<&mut TuiContext>, item: MediaItem) -> Result<N
                     next: NextScreen::RefreshItem(id),
                 });
             }
+            ItemDetailsCommand::PrevLink => {
+                let widget = events.get_inner();
+                widget.selected_link = widget
+                    .selected_link
+                    .checked_sub(1)
+                    .unwrap_or(widget.links.len().saturating_sub(1));
+            }
+            ItemDetailsCommand::NextLink => {
+                let widget = events.get_inner();
+                widget.selected_link = (widget.selected_link + 1)
+                    .checked_rem(widget.links.len())
+                    .unwrap_or(0);
+            }
+            ItemDetailsCommand::OpenLink => {
+                let widget = events.get_inner();
+                if let Some(link) = widget.links.get(widget.selected_link) {
+                    let link = link.clone();
+                    break Ok(Navigation::Push {
+                        current: NextScreen::ItemDetails(item),
+                        next: NextScreen::LoadLinkedItems {
+                            link,
+                            filter: UserViewFilter {
+                                sort: ItemSort::Name,
+                                descending: false,
+                                ..Default::default()
+                            },
+                        },
+                    });
+                }
+            }
+            ItemDetailsCommand::DownloadSubtitles => {
+                let id = item.id.clone();
+                break Ok(Navigation::Push {
+                    current: NextScreen::ItemDetails(item),
+                    next: NextScreen::FetchRemoteSubtitles(id),
+                });
+            }
         }
     }
 }
@@ -2,20 +2,23 @@ use std::{cmp::min, pin::Pin};
 
 use color_eyre::{Result, eyre::Context};
 use entries::{
-    entry::Entry,
+    entry::{Entry, EntryInner},
     image::available::ImagesAvailable,
     list::{EntryList, entry_list_height},
+    mouse::MouseNavigation,
+};
+use fetch::{
+    fetch_all_children, fetch_child_of_type, fetch_item, fetch_playlist_items, fetch_screen,
 };
-use fetch::{fetch_all_children, fetch_child_of_type, fetch_item, fetch_screen};
 use futures_util::{StreamExt, future::try_join};
-use jellyfin::items::MediaItem;
+use jellyfin::items::{ItemType, MediaItem};
 use jellyhaj_core::{
     context::TuiContext,
     entries::EntryExt,
     keybinds::ItemListDetailsCommand,
-    state::{Navigation, NextScreen, ToNavigation},
+    state::{LoadPlay, Navigation, NextScreen, ToNavigation, global_screen},
 };
-use keybinds::{KeybindEvent, KeybindEventStream};
+use keybinds::{ClickTracker, KeybindEvent, KeybindEventStream};
 use ratatui::{
     layout::{Constraint, Layout, Margin},
     text::Text,
@@ -32,8 +35,12 @@ pub async fn display_fetch_item_list(
     fetch_screen(
         &format!("Loading {}", &item.name),
         async move {
-            Ok(fetch_all_children(jellyfin, &item.id)
-                .await
+            let data = if let ItemType::Playlist = item.item_type {
+                fetch_playlist_items(jellyfin, &item.id).await
+            } else {
+                fetch_all_children(jellyfin, &item.id).await
+            };
+            Ok(data
                 .map(move |data| Navigation::Replace(NextScreen::ItemListDetailsData(item, data)))
                 .to_nav())
         },
@@ -89,32 +96,64 @@ pub async fn display_fetch_season(cx: Pin<&mut TuiContext>, series: &str) -> Res
     .await
 }
 
+pub async fn mark_played(cx: Pin<&mut TuiContext>, item: MediaItem) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    fetch_screen(
+        &format!("Marking {} as watched", item.name),
+        async move {
+            // the server marks all children of the item as well
+            Ok(jellyfin
+                .mark_played(&item.id)
+                .await
+                .context("marking item as played")
+                .map(|_| Navigation::Replace(NextScreen::FetchItemListDetails(item)))
+                .to_nav())
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
 pub fn handle_item_list_details_data(
     cx: Pin<&mut TuiContext>,
     item: MediaItem,
-    childs: Vec<MediaItem>,
+    mut childs: Vec<MediaItem>,
 ) -> Result<Navigation> {
+    if let ItemType::Series = item.item_type {
+        // the specials season sorts first, show it after the regular seasons
+        childs.sort_by_key(MediaItem::is_special);
+    }
+    if let ItemType::Season { .. } = item.item_type {
+        // episodes without a number are shown after the numbered ones
+        childs.sort_by_key(|episode| (episode.episode_index.is_none(), episode.episode_index));
+    }
+    if let ItemType::MusicAlbum = item.item_type {
+        // disc and track number
+        childs.sort_by_key(|track| (track.season_index, track.episode_index));
+    }
+    if let ItemType::BoxSet = item.item_type {
+        // release order, the server only sorts by name
+        childs.sort_by_key(|movie| (movie.production_year.is_none(), movie.production_year));
+    }
     let name = item.name.clone();
     let images_available = ImagesAvailable::new();
+    let entry_cx = cx.entry_context(&images_available);
     Ok(Navigation::Replace(NextScreen::ItemListDetails(
         item,
         EntryList::new(
             childs
                 .iter()
                 .flat_map(|item| {
-                    Entry::from_media_item(
-                        item.clone(),
-                        &cx.jellyfin,
-                        &cx.cache,
-                        &cx.image_cache,
-                        &images_available,
-                        &cx.image_picker,
-                        &cx.stats,
-                    )
-                    .transpose()
+                    Entry::from_media_item(item.clone(), &entry_cx, &cx.config.icons).transpose()
                 })
                 .collect::<Result<Vec<_>>>()?,
             name,
+            cx.config.image_layout,
+            cx.config.prefetch_margin,
         ),
         images_available,
     )))
@@ -182,7 +221,7 @@ pub async fn display_item_list_details(
     let cx = cx.project();
     entries.active = true;
     let mut details = ItemListDetails {
-        height: entry_list_height(cx.image_picker.font_size()),
+        height: entry_list_height(cx.image_picker.font_size(), cx.config.image_layout),
         width: None,
         scrollbar_state: ScrollbarState::new(0),
         scrollbar_pos: 0,
@@ -197,6 +236,8 @@ pub async fn display_item_list_details(
         cx.config.keybinds.item_list_details.clone(),
         &cx.config.help_prefixes,
     );
+    events.set_mouse_input(cx.config.capture_mouse());
+    let mut clicks = ClickTracker::new(cx.config.single_click_play);
     loop {
         cx.term.draw_fallible(&mut events)?;
         let cmd = tokio::select! {
@@ -206,13 +247,31 @@ pub async fn display_item_list_details(
                     Some(Ok(KeybindEvent::Command(cmd))) => cmd,
                     Some(Ok(KeybindEvent::Render)) => continue ,
                     Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Mouse(event))) => {
+                        let hover = cx.config.focus_follows_mouse;
+                        if events.get_inner().entries.handle_mouse(event, hover, &mut clicks) {
+                            ItemListDetailsCommand::Play
+                        } else {
+                            continue
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: NextScreen::ItemListDetails(item, entries, images_available),
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
                     Some(Err(e)) => break  Err(e).context("getting key events from terminal"),
                     None => break  Ok(Navigation::Exit)
                 }
             }
         };
         match cmd {
-            ItemListDetailsCommand::Quit => break Ok(Navigation::PopContext),
+            ItemListDetailsCommand::Quit => break Ok(Navigation::Quit),
+            ItemListDetailsCommand::Back => break Ok(Navigation::PopContext),
             ItemListDetailsCommand::Up => {
                 events.get_inner().scrollbar_pos = min(
                     events.get_inner().scrollbar_pos + 1,
@@ -233,6 +292,23 @@ pub async fn display_item_list_details(
                 break Ok(Navigation::Replace(NextScreen::FetchItemListDetails(item)));
             }
             ItemListDetailsCommand::Play => {
+                if let ItemType::Playlist = item.item_type
+                    && let Some(entry) = events.get_inner().entries.get()
+                    && let EntryInner::Item(MediaItem {
+                        playlist_item_id: Some(entry_id),
+                        ..
+                    }) = entry.inner()
+                {
+                    // queue the rest of the playlist instead of the album or series of the item
+                    let next = NextScreen::LoadPlayItem(LoadPlay::Playlist {
+                        id: item.id.clone(),
+                        entry_id: Some(entry_id.clone()),
+                    });
+                    break Ok(Navigation::Push {
+                        current: NextScreen::ItemListDetails(item, entries, images_available),
+                        next,
+                    });
+                }
                 if let Some(entry) = events.get_inner().entries.get()
                     && let Some(next) = entry.play()
                 {
@@ -292,6 +368,41 @@ pub async fn display_item_list_details(
                     });
                 }
             }
+            ItemListDetailsCommand::AddToPlaylist => {
+                if let Some(entry) = events.get_inner().entries.get()
+                    && let Some(id) = entry.item_id()
+                {
+                    let id = id.to_string();
+                    break Ok(Navigation::Push {
+                        current: NextScreen::ItemListDetails(item, entries, images_available),
+                        next: NextScreen::LoadAddToPlaylist(id),
+                    });
+                }
+            }
+            ItemListDetailsCommand::RemoveFromPlaylist => {
+                if let ItemType::Playlist = item.item_type
+                    && let Some(entry) = events.get_inner().entries.get()
+                    && let EntryInner::Item(MediaItem {
+                        playlist_item_id: Some(entry_id),
+                        ..
+                    }) = entry.inner()
+                {
+                    let next = NextScreen::SendRemoveFromPlaylist {
+                        playlist: item.id.clone(),
+                        entry: entry_id.clone(),
+                    };
+                    // reloaded afterwards, so the entry only goes once the server removed it
+                    break Ok(Navigation::Push {
+                        current: NextScreen::FetchItemListDetails(item),
+                        next,
+                    });
+                }
+            }
+            ItemListDetailsCommand::MarkParentWatched => {
+                if let ItemType::Series | ItemType::Season { .. } = item.item_type {
+                    break Ok(Navigation::Replace(NextScreen::MarkPlayed(item)));
+                }
+            }
             ItemListDetailsCommand::RefreshParentItem => {
                 let id = item.id.clone();
                 break Ok(Navigation::Push {
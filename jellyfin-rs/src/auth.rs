@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use aws_lc_rs::digest;
 use http::{HeaderValue, header::AUTHORIZATION};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use base64::{Engine, engine::general_purpose::URL_SAFE};
+use color_eyre::eyre::eyre;
 use tracing::{instrument, trace};
 
 use crate::{
@@ -21,6 +22,30 @@ struct AuthUserNameReq<'a> {
     username: &'a str,
     pw: &'a str,
 }
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct AuthQuickConnectReq<'a> {
+    secret: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuickConnectQuery<'a> {
+    secret: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuickConnectState {
+    pub authenticated: bool,
+    pub secret: String,
+    pub code: String,
+}
+
+/// Quick Connect happens before the user is known, so its device id is derived without a user name.
+/// Tokens obtained this way have to be restored with `auth_key(token, QUICK_CONNECT_USER)`.
+pub const QUICK_CONNECT_USER: &str = "";
+
 impl JellyfinClient<NoAuth> {
     pub fn auth_key(self, key: String, user_name: impl AsRef<str>) -> JellyfinClient<KeyAuth> {
         let key = key.to_string();
@@ -94,6 +119,88 @@ impl JellyfinClient<NoAuth> {
         };
         Ok(make_auth_or_return(self, auth))
     }
+
+    fn quick_connect_header(&self) -> HeaderValue {
+        let device_id = make_user_client_id(
+            QUICK_CONNECT_USER,
+            &self.inner.client_info,
+            &self.inner.device_name,
+        );
+        make_auth_handshake_header(&self.inner.client_info, &self.inner.device_name, &device_id)
+    }
+
+    #[instrument(skip_all)]
+    pub async fn quick_connect_enabled(&self) -> crate::Result<bool> {
+        self.send_request_json(self.get("/QuickConnect/Enabled", NoQuery)?.empty_body()?)
+            .await?
+            .deserialize()
+            .await
+    }
+
+    /// Starts a Quick Connect request, the returned code has to be approved by a logged in user
+    #[instrument(skip_all)]
+    pub async fn quick_connect_initiate(&self) -> crate::Result<QuickConnectState> {
+        self.send_request_json(
+            self.post("/QuickConnect/Initiate", NoQuery)?
+                .header(AUTHORIZATION, self.quick_connect_header())
+                .empty_body()?,
+        )
+        .await?
+        .deserialize()
+        .await
+    }
+
+    #[instrument(skip_all)]
+    pub async fn quick_connect_state(&self, secret: &str) -> crate::Result<QuickConnectState> {
+        self.send_request_json(
+            self.get("/QuickConnect/Connect", &QuickConnectQuery { secret })?
+                .header(AUTHORIZATION, self.quick_connect_header())
+                .empty_body()?,
+        )
+        .await?
+        .deserialize()
+        .await
+    }
+
+    #[instrument(skip_all)]
+    pub async fn auth_quick_connect(
+        self,
+        secret: &str,
+    ) -> StdResult<JellyfinClient<Auth>, (Self, color_eyre::Report)> {
+        let device_id = make_user_client_id(
+            QUICK_CONNECT_USER,
+            &self.inner.client_info,
+            &self.inner.device_name,
+        );
+        let auth: StdResult<UserAuth, color_eyre::Report> = async {
+            self.send_request_json(
+                self.post("/Users/AuthenticateWithQuickConnect", NoQuery)?
+                    .header(AUTHORIZATION, self.quick_connect_header())
+                    .json_body(&AuthQuickConnectReq { secret })?,
+            )
+            .await?
+            .deserialize()
+            .await
+        }
+        .await;
+        let auth = match auth {
+            Ok(v) => v,
+            Err(e) => return Err((self, e)),
+        };
+        let auth_header = make_auth_header(
+            &auth.access_token,
+            &self.inner.client_info,
+            &self.inner.device_name,
+            &device_id,
+        );
+        let auth = Auth {
+            user: auth.user,
+            access_token: auth.access_token,
+            header: auth_header,
+            device_id,
+        };
+        Ok(make_auth_or_return(self, auth))
+    }
 }
 
 fn make_auth_or_return<Auth1: AuthStatus, Auth2: AuthStatus>(
@@ -135,14 +242,34 @@ impl JellyfinClient<KeyAuth> {
             Ok(v) => v,
             Err(e) => return Err((self, e)),
         };
+        Ok(self.with_user(user))
+    }
+
+    /// API keys are not bound to a user, so the user is looked up by name
+    pub async fn get_user_by_name(
+        self,
+        name: impl AsRef<str>,
+    ) -> StdResult<JellyfinClient<Auth>, (Self, color_eyre::Report)> {
+        let name = name.as_ref();
+        let users = async { self.get_users(false, false).await?.deserialize().await };
+        let users: Vec<User> = match users.await {
+            Ok(v) => v,
+            Err(e) => return Err((self, e)),
+        };
+        match users.into_iter().find(|user| user.name == name) {
+            Some(user) => Ok(self.with_user(user)),
+            None => Err((self, eyre!("no user named {name} found"))),
+        }
+    }
 
+    fn with_user(self, user: User) -> JellyfinClient<Auth> {
         let auth = Auth {
             user,
             access_token: self.inner.auth.access_key.clone(),
             header: self.inner.auth.header.clone(),
             device_id: self.inner.auth.device_id.clone(),
         };
-        Ok(make_auth_or_return(self, auth))
+        make_auth_or_return(self, auth)
     }
 }
 
@@ -1,19 +1,24 @@
 use std::{
-    fmt::Debug,
+    fmt::{Debug, Display},
     future::Future,
     marker::PhantomData,
     net::IpAddr,
-    ops::DerefMut,
+    num::NonZeroUsize,
+    ops::{Deref, DerefMut},
     pin::pin,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering::Relaxed},
+    },
     task::{Poll, ready},
+    time::Duration,
 };
 
 use bytes::{Bytes, BytesMut};
 use color_eyre::{Section, SectionExt, eyre::eyre};
 use futures_util::{FutureExt, future::poll_fn};
 use http::{
-    Request, Response,
+    Method, Request, Response, StatusCode,
     header::{CONTENT_LENGTH, CONTENT_TYPE},
     response::Parts,
     uri::Authority,
@@ -24,7 +29,8 @@ use serde::de::DeserializeOwned;
 use tokio::{
     io::{AsyncRead, AsyncWrite},
     net::TcpStream,
-    sync::Mutex,
+    sync::{Mutex, MutexGuard, Semaphore, SemaphorePermit},
+    time::{sleep, timeout},
 };
 use tokio_rustls::{
     TlsConnector,
@@ -35,14 +41,79 @@ use tracing::{Instrument, error, error_span, instrument, warn};
 
 use crate::Result;
 
+/// wait before the first retry of a failed request, doubled for every further retry
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// The server answered with an error status
+#[derive(Debug)]
+pub struct StatusError(pub StatusCode);
+
+impl Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP Error encountered: {}", self.0)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// Counters of the requests sent over a [Connection], every retry counts as a request
+#[derive(Debug, Default)]
+pub struct RequestStats {
+    pub requests: AtomicU64,
+    pub in_flight: AtomicU64,
+    pub failed: AtomicU64,
+    last_error: std::sync::Mutex<Option<String>>,
+}
+
+impl RequestStats {
+    /// message of the most recent failed request
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn record_error(&self, error: &color_eyre::Report) {
+        self.failed.fetch_add(1, Relaxed);
+        *self.last_error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error.to_string());
+    }
+}
+
+/// counts a request as in flight until it finishes or is dropped
+struct InFlight<'s>(&'s RequestStats);
+
+impl<'s> InFlight<'s> {
+    fn new(stats: &'s RequestStats) -> Self {
+        stats.requests.fetch_add(1, Relaxed);
+        stats.in_flight.fetch_add(1, Relaxed);
+        Self(stats)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Relaxed);
+    }
+}
+
 pub struct Connection {
     authority: Authority,
     host: ServerName<'static>,
     port: u16,
     tls: bool,
-    inner: Mutex<ConnectionInner>,
+    /// connections are only opened once all previous ones are busy
+    pool: Box<[Mutex<ConnectionInner>]>,
+    /// one permit per connection in `pool`, requests wait here until any connection is idle
+    idle: Semaphore,
     general_config: TlsConnector,
     http1_config: TlsConnector,
+    /// requests taking longer are given up, `None` waits forever
+    timeout: Option<Duration>,
+    /// additional attempts of GET requests failing with a connection or server error
+    retries: u32,
+    /// shared with connections created by [Connection::clone_new]
+    stats: Arc<RequestStats>,
 }
 
 impl Debug for Connection {
@@ -52,6 +123,9 @@ impl Debug for Connection {
             .field("host", &self.host)
             .field("port", &self.port)
             .field("tls", &self.tls)
+            .field("max_connections", &self.pool.len())
+            .field("timeout", &self.timeout)
+            .field("retries", &self.retries)
             .finish()
     }
 }
@@ -62,6 +136,42 @@ enum ConnectionInner {
     H1(hyper::client::conn::http1::SendRequest<String>),
 }
 
+fn new_pool(size: NonZeroUsize) -> Box<[Mutex<ConnectionInner>]> {
+    (0..size.get())
+        .map(|_| Mutex::new(ConnectionInner::Disconnected))
+        .collect()
+}
+
+/// a connection of the pool, held until the request is sent
+struct PoolSlot<'c> {
+    connection: MutexGuard<'c, ConnectionInner>,
+    /// the connection is dropped when the slot is released, unless the request finished
+    evict: bool,
+    _permit: SemaphorePermit<'c>,
+}
+
+impl Drop for PoolSlot<'_> {
+    fn drop(&mut self) {
+        if self.evict {
+            *self.connection = ConnectionInner::Disconnected;
+        }
+    }
+}
+
+impl Deref for PoolSlot<'_> {
+    type Target = ConnectionInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.connection
+    }
+}
+
+impl DerefMut for PoolSlot<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.connection
+    }
+}
+
 impl Connection {
     pub fn clone_new(&self) -> Self {
         Self {
@@ -69,9 +179,13 @@ impl Connection {
             host: self.host.clone(),
             port: self.port,
             tls: self.tls,
-            inner: Mutex::new(ConnectionInner::Disconnected),
+            pool: new_pool(self.max_connections()),
+            idle: Semaphore::new(self.pool.len()),
             general_config: self.general_config.clone(),
             http1_config: self.http1_config.clone(),
+            timeout: self.timeout,
+            retries: self.retries,
+            stats: self.stats.clone(),
         }
     }
 
@@ -87,8 +201,25 @@ impl Connection {
     pub fn tls(&self) -> bool {
         self.tls
     }
+    pub fn max_connections(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.pool.len()).expect("pool is never empty")
+    }
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+    pub fn request_stats(&self) -> &Arc<RequestStats> {
+        &self.stats
+    }
+    /// gives up requests after `timeout` and retries failed GET requests `retries` times
+    pub fn set_timeout(&mut self, timeout: Option<Duration>, retries: u32) {
+        self.timeout = timeout;
+        self.retries = retries;
+    }
 
-    pub fn new(authority: Authority, tls: bool) -> Result<Self> {
+    pub fn new(authority: Authority, tls: bool, max_connections: NonZeroUsize) -> Result<Self> {
         let host = ServerName::try_from(authority.host())?.to_owned();
         let port = authority.port_u16().unwrap_or(if tls { 443 } else { 80 });
         let mut cert_store = RootCertStore::empty();
@@ -113,9 +244,13 @@ impl Connection {
             host,
             port,
             tls,
-            inner: Mutex::new(ConnectionInner::Disconnected),
+            pool: new_pool(max_connections),
+            idle: Semaphore::new(max_connections.get()),
             general_config: Arc::new(general_config).into(),
             http1_config: Arc::new(http1_config).into(),
+            timeout: None,
+            retries: 0,
+            stats: Arc::default(),
         })
     }
 
@@ -145,10 +280,72 @@ impl Connection {
         }
     }
 
+    /// first idle connection, waits until any connection is idle if all are busy
+    async fn acquire(&self) -> PoolSlot<'_> {
+        let permit = self
+            .idle
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        let connection = self
+            .pool
+            .iter()
+            .find_map(|con| con.try_lock().ok())
+            .expect("a permit is only available while a connection is idle");
+        PoolSlot {
+            connection,
+            evict: true,
+            _permit: permit,
+        }
+    }
+
+    /// sends `req`, failed GET requests are retried with exponential backoff
+    /// unless the server rejected them
     #[instrument(skip_all)]
     pub async fn send_request(&self, req: Request<String>) -> Result<(BytesMut, Parts)> {
+        let idempotent = req.method() == Method::GET;
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 0;
         loop {
-            let mut state = self.inner.lock().await;
+            let in_flight = InFlight::new(&self.stats);
+            let res = match self.timeout {
+                Some(limit) => timeout(limit, self.send_request_once(&req))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(eyre!(
+                            "request to {} timed out after {}s",
+                            req.uri().path(),
+                            limit.as_secs()
+                        ))
+                    }),
+                None => self.send_request_once(&req).await,
+            };
+            drop(in_flight);
+            if let Err(e) = &res {
+                self.stats.record_error(e);
+            }
+            match res {
+                Err(e)
+                    if idempotent
+                        && attempt < self.retries
+                        && e.downcast_ref::<StatusError>()
+                            .is_none_or(|status| status.0.is_server_error()) =>
+                {
+                    attempt += 1;
+                    warn!("request failed, retry {attempt} in {backoff:?}: {e:?}");
+                    sleep(backoff).await;
+                    backoff *= 2;
+                }
+                res => break res,
+            }
+        }
+    }
+
+    /// http/1 connections are held until the response is read and dropped if the request is
+    /// abandoned or fails, so a timed out request does not leave them to the next one
+    async fn send_request_once(&self, req: &Request<String>) -> Result<(BytesMut, Parts)> {
+        loop {
+            let mut state = self.acquire().await;
             let resp = loop {
                 let inner = match state.deref_mut() {
                     ConnectionInner::Disconnected => {
@@ -202,13 +399,28 @@ impl Connection {
                 };
                 *state = inner;
             };
-            drop(state);
-            match resp.await {
-                Ok(resp) => return recv_response(check_status(resp)?).await,
+            let mut state = if let ConnectionInner::H2(_) = *state {
+                state.evict = false;
+                None
+            } else {
+                Some(state)
+            };
+            let response = match resp.await {
+                Ok(resp) => recv_response(check_status(resp)?).await.map(Some),
                 Err(e) => {
                     warn!("received connection error: {e:?}");
                     warn!("retrying request");
+                    Ok(None)
                 }
+            };
+            if let Some(state) = &mut state
+                && let Ok(Some(_)) = response
+            {
+                state.evict = false;
+            }
+            drop(state);
+            if let Some(response) = response? {
+                return Ok(response);
             }
         }
     }
@@ -238,7 +450,7 @@ async fn get_stream(host: &ServerName<'static>, port: u16) -> Result<TcpStream>
 fn check_status<T>(response: Response<T>) -> Result<Response<T>> {
     let status = response.status();
     if status.is_client_error() || status.is_server_error() {
-        Err(eyre!("HTTP Error encountered: {status}"))
+        Err(StatusError(status).into())
     } else {
         Ok(response)
     }
@@ -0,0 +1,30 @@
+use serde::Serialize;
+
+use crate::{
+    Authed, JellyfinClient, JellyfinVec, Result, connect::JsonResponse, items::NameIdPair,
+    request::RequestBuilderExt,
+};
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGenresQuery<'s> {
+    pub user_id: Option<&'s str>,
+    pub parent_id: Option<&'s str>,
+    pub start_index: Option<u32>,
+    pub limit: Option<u32>,
+    pub sort_by: Option<&'s str>,
+    pub sort_order: Option<&'s str>,
+    pub enable_images: Option<bool>,
+    pub enable_total_record_count: Option<bool>,
+}
+
+impl<Auth: Authed> JellyfinClient<Auth> {
+    /// genres of the items below `parent_id`, or of all items if it is `None`
+    pub async fn get_genres(
+        &self,
+        query: &GetGenresQuery<'_>,
+    ) -> Result<JsonResponse<JellyfinVec<NameIdPair>>> {
+        self.send_request_json(self.get("/Genres", query)?.empty_body()?)
+            .await
+    }
+}
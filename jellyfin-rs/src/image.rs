@@ -63,19 +63,15 @@ impl<Auth: AuthStatus> JellyfinClient<Auth> {
         image_type: ImageType,
         query: &GetImageQuery<'_>,
     ) -> Result<Uri> {
-        Uri::builder()
-            .scheme(if self.tls() { "https" } else { "http" })
-            .authority(self.authority().to_owned())
-            .path_and_query(self.build_uri(
-                |prefix: &mut String| {
-                    prefix.push_str("/Items/");
-                    prefix.push_str(item_id);
-                    prefix.push_str("/Images/");
-                    prefix.push_str(image_type.name());
-                },
-                query,
-            )?)
-            .build()
-            .context("assembling image uri")
+        self.build_absolute_uri(
+            |prefix: &mut String| {
+                prefix.push_str("/Items/");
+                prefix.push_str(item_id);
+                prefix.push_str("/Images/");
+                prefix.push_str(image_type.name());
+            },
+            query,
+        )
+        .context("assembling image uri")
     }
 }
@@ -10,6 +10,14 @@ use serde::Deserialize;
 use serde::Serialize;
 use tracing::instrument;
 
+#[derive(Debug, Clone, Serialize)]
+struct StreamQuery<'s> {
+    /// stream the file as is instead of transcoding it
+    #[serde(rename = "static")]
+    is_static: bool,
+    api_key: &'s str,
+}
+
 #[derive(Debug, Default, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UserIdQuery<'a> {
@@ -62,6 +70,11 @@ pub struct GetItemsQuery<'a> {
     pub image_type_limit: Option<u32>,
     pub enable_user_data: Option<bool>,
     pub fields: Option<&'a str>,
+    pub filters: Option<&'a str>,
+    pub genre_ids: Option<&'a str>,
+    pub studio_ids: Option<&'a str>,
+    pub person_ids: Option<&'a str>,
+    pub search_term: Option<&'a str>,
     pub sort_by: Option<&'a str>,
     pub recursive: Option<bool>,
     pub sort_order: Option<&'a str>,
@@ -179,14 +192,49 @@ pub enum ItemType {
     Series,
     Playlist,
     Folder,
+    BoxSet,
+    #[serde(rename = "Audio", rename_all = "PascalCase")]
     Music {
+        /// empty for tracks outside of an album
+        #[serde(default)]
         album_id: String,
+        #[serde(default)]
         album: String,
+        album_artist: Option<String>,
     },
     #[serde(other)]
     Unknown,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Video3DFormat {
+    HalfSideBySide,
+    FullSideBySide,
+    FullTopAndBottom,
+    HalfTopAndBottom,
+    #[serde(rename = "MVC")]
+    Mvc,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExtraType {
+    Clip,
+    Trailer,
+    BehindTheScenes,
+    DeletedScene,
+    Interview,
+    Scene,
+    Sample,
+    ThemeSong,
+    ThemeVideo,
+    Featurette,
+    Short,
+    #[serde(other)]
+    Unknown,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct UserData {
@@ -206,11 +254,30 @@ pub struct SetUserData {
     pub played: Option<bool>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct NameIdPair {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Person {
+    pub name: String,
+    pub id: String,
+    pub role: Option<String>,
+    #[serde(rename = "Type")]
+    pub person_type: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct MediaItem {
     pub id: String,
     pub image_tags: Option<HashMap<ImageType, String>>,
+    /// blurhash of each image by type and tag
+    pub image_blur_hashes: Option<HashMap<ImageType, HashMap<String, String>>>,
     pub media_type: MediaType,
     pub name: String,
     pub sort_name: Option<String>,
@@ -224,6 +291,34 @@ pub struct MediaItem {
     #[serde(rename = "ParentIndexNumber")]
     pub season_index: Option<u64>,
     pub run_time_ticks: Option<u64>,
+    pub playlist_item_id: Option<String>,
+    #[serde(rename = "Video3DFormat")]
+    pub video_3d_format: Option<Video3DFormat>,
+    pub extra_type: Option<ExtraType>,
+    pub genre_items: Option<Vec<NameIdPair>>,
+    pub studios: Option<Vec<NameIdPair>>,
+    pub people: Option<Vec<Person>>,
+    pub community_rating: Option<f64>,
+    pub production_year: Option<u32>,
+}
+
+impl MediaItem {
+    pub fn blur_hash(&self, image_type: ImageType, tag: &str) -> Option<&str> {
+        self.image_blur_hashes
+            .as_ref()?
+            .get(&image_type)?
+            .get(tag)
+            .map(String::as_str)
+    }
+
+    /// season 0 and its episodes, jellyfin stores specials there
+    pub fn is_special(&self) -> bool {
+        match self.item_type {
+            ItemType::Episode { .. } => self.season_index == Some(0),
+            ItemType::Season { .. } => self.episode_index == Some(0),
+            _ => false,
+        }
+    }
 }
 
 impl<Auth: Authed> JellyfinClient<Auth> {
@@ -304,53 +399,87 @@ impl<Auth: Authed> JellyfinClient<Auth> {
     }
 
     pub fn get_video_uri(&self, item: &MediaItem) -> Result<Uri> {
-        Uri::builder()
-            .scheme(if self.tls() { "https" } else { "http" })
-            .authority(self.authority().to_owned())
-            .path_and_query(self.build_uri(
-                |prefix: &mut String| {
-                    prefix.push_str("/Items/");
-                    prefix.push_str(&item.id);
-                    prefix.push_str("/Download");
-                },
-                NoQuery,
-            )?)
-            .build()
-            .context("assembling video uri")
+        self.build_absolute_uri(
+            |prefix: &mut String| {
+                prefix.push_str("/Items/");
+                prefix.push_str(&item.id);
+                prefix.push_str("/Download");
+            },
+            NoQuery,
+        )
+        .context("assembling video uri")
+    }
+
+    /// direct stream of the item that includes the access token, for use in external players
+    pub fn get_stream_uri(&self, item: &MediaItem) -> Result<Uri> {
+        let path = |prefix: &mut String| {
+            prefix.push_str(match item.media_type {
+                MediaType::Audio => "/Audio/",
+                _ => "/Videos/",
+            });
+            prefix.push_str(&item.id);
+            prefix.push_str("/stream");
+        };
+        self.build_absolute_uri(
+            path,
+            &StreamQuery {
+                is_static: true,
+                api_key: self.get_auth().token(),
+            },
+        )
+        .context("assembling stream uri")
     }
 }
 
 impl JellyfinClient {
-    pub async fn set_unplayed(&self, item: &str) -> Result<()> {
-        self.send_request(
-            self.delete(
+    /// marks the item and all its children as played, returns the new user data of the item
+    pub async fn mark_played(&self, item: &str) -> Result<JsonResponse<UserData>> {
+        self.send_request_json(
+            self.post(
                 |prefix: &mut String| {
-                    prefix.push_str("/Users/");
-                    prefix.push_str(&self.get_auth().user.id);
-                    prefix.push_str("/PlayedItems/");
+                    prefix.push_str("/UserPlayedItems/");
                     prefix.push_str(item);
                 },
-                NoQuery,
+                &UserIdQuery {
+                    user_id: Some(self.get_auth().user.id.as_str()),
+                },
             )?
             .empty_body()?,
         )
-        .await?;
-        Ok(())
+        .await
     }
-    pub async fn set_played(&self, item: &str) -> Result<()> {
-        self.send_request(
-            self.post(
+    /// marks the item and all its children as unplayed, also resets the playback position
+    pub async fn mark_unplayed(&self, item: &str) -> Result<JsonResponse<UserData>> {
+        self.send_request_json(
+            self.delete(
                 |prefix: &mut String| {
-                    prefix.push_str("/Users/");
-                    prefix.push_str(&self.get_auth().user.id);
-                    prefix.push_str("/PlayedItems/");
+                    prefix.push_str("/UserPlayedItems/");
                     prefix.push_str(item);
                 },
-                NoQuery,
+                &UserIdQuery {
+                    user_id: Some(self.get_auth().user.id.as_str()),
+                },
             )?
             .empty_body()?,
         )
-        .await?;
-        Ok(())
+        .await
+    }
+    /// adds or removes the item from the favorites, returns the new user data of the item
+    pub async fn set_favorite(&self, item: &str, favorite: bool) -> Result<JsonResponse<UserData>> {
+        let path = |prefix: &mut String| {
+            prefix.push_str("/Users/");
+            prefix.push_str(&self.get_auth().user.id);
+            prefix.push_str("/FavoriteItems/");
+            prefix.push_str(item);
+        };
+        self.send_request_json(
+            if favorite {
+                self.post(path, NoQuery)?
+            } else {
+                self.delete(path, NoQuery)?
+            }
+            .empty_body()?,
+        )
+        .await
     }
 }
@@ -1,4 +1,7 @@
-use std::{borrow::Cow, fmt::Debug, future::Future, ops::Deref, sync::Arc};
+use std::{
+    borrow::Cow, fmt::Debug, future::Future, num::NonZeroUsize, ops::Deref, sync::Arc,
+    time::Duration,
+};
 
 use color_eyre::eyre::{OptionExt, eyre};
 use connect::Connection;
@@ -13,14 +16,18 @@ pub mod activity;
 pub mod auth;
 pub mod connect;
 pub mod err;
+pub mod genres;
 pub mod image;
 pub mod items;
 pub mod playback_status;
 pub mod playlist;
 pub mod request;
+pub mod scheduled_tasks;
 pub mod session;
 pub mod shows;
 pub mod socket;
+pub mod subtitles;
+pub mod trickplay;
 pub mod user;
 pub mod user_library;
 pub mod user_views;
@@ -123,10 +130,12 @@ pub struct ClientInfo {
 impl<AuthS: AuthStatus> JellyfinClient<AuthS> {
     /// Creates a new `JellyfinConnection`
     /// * `url` The base jellyfin server url, without a trailing "/"
+    /// * `max_connections` The maximum number of simultaneous connections to the server
     pub fn new(
         uri: impl AsRef<str>,
         client_info: ClientInfo,
         device_name: impl Into<Cow<'static, str>>,
+        max_connections: NonZeroUsize,
     ) -> err::Result<JellyfinClient<NoAuth>> {
         let uri = Uri::try_from(uri.as_ref())?.into_parts();
         let tls = match uri.scheme.as_ref().map(|s| s.as_str()) {
@@ -145,7 +154,7 @@ impl<AuthS: AuthStatus> JellyfinClient<AuthS> {
             inner: Arc::new(ClientInner {
                 uri_base,
                 host_header,
-                connection: Connection::new(authority, tls)?,
+                connection: Connection::new(authority, tls, max_connections)?,
                 auth: NoAuth,
                 client_info,
                 device_name: device_name.into(),
@@ -161,10 +170,11 @@ impl<AuthS: AuthStatus> JellyfinClient<AuthS> {
         url: impl AsRef<str>,
         client_info: ClientInfo,
         device_name: impl Into<Cow<'static, str>>,
+        max_connections: NonZeroUsize,
         username: impl AsRef<str>,
         password: impl AsRef<str>,
     ) -> err::Result<JellyfinClient<Auth>> {
-        Self::new(url, client_info, device_name)?
+        Self::new(url, client_info, device_name, max_connections)?
             .auth_user_name(username, password)
             .await
             .map_err(|(_, e)| e)
@@ -174,10 +184,11 @@ impl<AuthS: AuthStatus> JellyfinClient<AuthS> {
         url: impl AsRef<str>,
         client_info: ClientInfo,
         device_name: impl Into<Cow<'static, str>>,
+        max_connections: NonZeroUsize,
         key: String,
         username: impl AsRef<str>,
     ) -> Result<JellyfinClient<KeyAuth>> {
-        Ok(Self::new(url, client_info, device_name)?.auth_key(key, username))
+        Ok(Self::new(url, client_info, device_name, max_connections)?.auth_key(key, username))
     }
 
     pub fn get_auth(&self) -> &AuthS {
@@ -227,6 +238,23 @@ impl<Auth: Authed> JellyfinClient<Auth> {
     }
 }
 
+impl JellyfinClient<NoAuth> {
+    /// Gives up requests taking longer than `timeout`, failed GET requests are retried
+    /// `retries` times with exponential backoff
+    pub fn with_timeout(self, timeout: Option<Duration>, retries: u32) -> Self {
+        // a shared client gets its own connection so the others are unaffected
+        let mut this = match Arc::strong_count(&self.inner) {
+            1 => self,
+            _ => client_with_auth(self, NoAuth),
+        };
+        Arc::get_mut(&mut this.inner)
+            .expect("client is not shared")
+            .connection
+            .set_timeout(timeout, retries);
+        this
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct JellyfinVec<T> {
@@ -1,8 +1,10 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    Authed, JellyfinClient, JellyfinVec, connect::JsonResponse, items::MediaItem,
-    request::RequestBuilderExt,
+    Authed, JellyfinClient, JellyfinVec,
+    connect::JsonResponse,
+    items::MediaItem,
+    request::{NoQuery, RequestBuilderExt},
 };
 
 #[derive(Debug, Default, Clone, Serialize)]
@@ -16,6 +18,35 @@ pub struct GetPlaylistItemsQuery<'s> {
     pub enable_user_data: Option<bool>,
 }
 
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreatePlaylist<'s> {
+    pub name: &'s str,
+    pub ids: &'s [&'s str],
+    pub user_id: Option<&'s str>,
+    pub media_type: Option<&'s str>,
+    pub is_public: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlaylistCreated {
+    pub id: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddToPlaylistQuery<'s> {
+    pub ids: &'s str,
+    pub user_id: Option<&'s str>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveFromPlaylistQuery<'s> {
+    pub entry_ids: &'s str,
+}
+
 impl<Auth: Authed> JellyfinClient<Auth> {
     pub async fn get_playlist_items(
         &self,
@@ -35,4 +66,52 @@ impl<Auth: Authed> JellyfinClient<Auth> {
         )
         .await
     }
+
+    pub async fn create_playlist(
+        &self,
+        playlist: &CreatePlaylist<'_>,
+    ) -> crate::Result<JsonResponse<PlaylistCreated>> {
+        self.send_request_json(self.post("/Playlists", NoQuery)?.json_body(playlist)?)
+            .await
+    }
+
+    pub async fn add_to_playlist(
+        &self,
+        playlist_id: &str,
+        query: &AddToPlaylistQuery<'_>,
+    ) -> crate::Result<()> {
+        self.send_request(
+            self.post(
+                |prefix: &mut String| {
+                    prefix.push_str("/Playlists/");
+                    prefix.push_str(playlist_id);
+                    prefix.push_str("/Items");
+                },
+                query,
+            )?
+            .empty_body()?,
+        )
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove_from_playlist(
+        &self,
+        playlist_id: &str,
+        query: &RemoveFromPlaylistQuery<'_>,
+    ) -> crate::Result<()> {
+        self.send_request(
+            self.delete(
+                |prefix: &mut String| {
+                    prefix.push_str("/Playlists/");
+                    prefix.push_str(playlist_id);
+                    prefix.push_str("/Items");
+                },
+                query,
+            )?
+            .empty_body()?,
+        )
+        .await?;
+        Ok(())
+    }
 }
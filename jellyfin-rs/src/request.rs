@@ -1,6 +1,7 @@
 use crate::{AuthStatus, JellyfinClient, Result, request::sealed::QuerySealed};
+use color_eyre::eyre::Context;
 use http::{
-    Method,
+    Method, Uri,
     header::{CONTENT_LENGTH, CONTENT_TYPE, HOST},
 };
 use serde::Serialize;
@@ -13,6 +14,15 @@ impl<Auth: AuthStatus> JellyfinClient<Auth> {
         query.append(&mut path)?;
         Ok(path)
     }
+    /// uri including scheme and authority, for use outside of this client
+    pub fn build_absolute_uri(&self, uri: impl PathBuilder, query: impl Query) -> Result<Uri> {
+        Uri::builder()
+            .scheme(if self.tls() { "https" } else { "http" })
+            .authority(self.authority().to_owned())
+            .path_and_query(self.build_uri(uri, query)?)
+            .build()
+            .context("assembling uri")
+    }
     pub fn request(
         &self,
         uri: impl PathBuilder,
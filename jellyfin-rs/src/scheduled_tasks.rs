@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Authed, JellyfinClient, Result,
+    connect::JsonResponse,
+    request::{NoQuery, RequestBuilderExt},
+};
+
+/// keys of the scheduled tasks built into the server
+pub mod known_keys {
+    /// scans all libraries for new, changed and removed files
+    pub const REFRESH_LIBRARY: &str = "RefreshLibrary";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TaskState {
+    Idle,
+    Cancelling,
+    Running,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ScheduledTask {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+    pub state: TaskState,
+    /// only set while the task is running
+    pub current_progress_percentage: Option<f64>,
+    /// missing if the task never ran
+    pub last_execution_result: Option<TaskResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskResult {
+    pub start_time_utc: String,
+    pub end_time_utc: String,
+    pub status: String,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetScheduledTasksQuery {
+    pub is_hidden: Option<bool>,
+    pub is_enabled: Option<bool>,
+}
+
+impl<Auth: Authed> JellyfinClient<Auth> {
+    pub async fn get_scheduled_tasks(
+        &self,
+        query: &GetScheduledTasksQuery,
+    ) -> Result<JsonResponse<Vec<ScheduledTask>>> {
+        self.send_request_json(self.get("/ScheduledTasks", query)?.empty_body()?)
+            .await
+    }
+
+    pub async fn get_scheduled_task(&self, task_id: &str) -> Result<JsonResponse<ScheduledTask>> {
+        self.send_request_json(
+            self.get(
+                |prefix: &mut String| {
+                    prefix.push_str("/ScheduledTasks/");
+                    prefix.push_str(task_id);
+                },
+                NoQuery,
+            )?
+            .empty_body()?,
+        )
+        .await
+    }
+
+    /// starts the task, requires an administrator
+    pub async fn run_scheduled_task(&self, task_id: &str) -> Result<()> {
+        self.send_request(
+            self.post(
+                |prefix: &mut String| {
+                    prefix.push_str("/ScheduledTasks/Running/");
+                    prefix.push_str(task_id);
+                },
+                NoQuery,
+            )?
+            .empty_body()?,
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use color_eyre::eyre::Context;
+use http::Uri;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Authed, JellyfinClient, Result,
+    connect::JsonResponse,
+    request::{NoQuery, RequestBuilderExt},
+};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoteSubtitleInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub provider_name: Option<String>,
+    pub format: Option<String>,
+    #[serde(rename = "ThreeLetterISOLanguageName")]
+    pub language: Option<String>,
+    pub download_count: Option<u64>,
+    pub is_hash_match: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchRemoteSubtitlesQuery {
+    pub is_perfect_match: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LibraryOptionInfo {
+    pub name: String,
+}
+
+/// plugins the server offers for libraries
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AvailableLibraryOptions {
+    /// remote subtitle providers, subtitles can't be searched without one
+    #[serde(default)]
+    pub subtitle_fetchers: Vec<LibraryOptionInfo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum MediaStreamType {
+    Audio,
+    Video,
+    Subtitle,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MediaStream {
+    #[serde(rename = "Type")]
+    pub stream_type: MediaStreamType,
+    pub index: u32,
+    /// stored next to the media file, downloaded subtitles end up here
+    #[serde(default)]
+    pub is_external: bool,
+    pub codec: Option<String>,
+    pub display_title: Option<String>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ItemMediaStreams {
+    #[serde(default)]
+    pub media_streams: Vec<MediaStream>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaStreamsQuery<'s> {
+    user_id: &'s str,
+    fields: &'s str,
+}
+
+impl<Auth: Authed> JellyfinClient<Auth> {
+    pub async fn search_remote_subtitles(
+        &self,
+        item: &str,
+        language: &str,
+        query: &SearchRemoteSubtitlesQuery,
+    ) -> Result<JsonResponse<Vec<RemoteSubtitleInfo>>> {
+        self.send_request_json(
+            self.get(
+                |prefix: &mut String| {
+                    prefix.push_str("/Items/");
+                    prefix.push_str(item);
+                    prefix.push_str("/RemoteSearch/Subtitles/");
+                    prefix.push_str(language);
+                },
+                query,
+            )?
+            .empty_body()?,
+        )
+        .await
+    }
+
+    pub async fn get_available_library_options(
+        &self,
+    ) -> Result<JsonResponse<AvailableLibraryOptions>> {
+        self.send_request_json(
+            self.get(
+                |prefix: &mut String| {
+                    prefix.push_str("/Libraries/AvailableOptions");
+                },
+                NoQuery,
+            )?
+            .empty_body()?,
+        )
+        .await
+    }
+
+    pub async fn download_remote_subtitles(&self, item: &str, subtitle_id: &str) -> Result<()> {
+        self.send_request(
+            self.post(
+                |prefix: &mut String| {
+                    prefix.push_str("/Items/");
+                    prefix.push_str(item);
+                    prefix.push_str("/RemoteSearch/Subtitles/");
+                    prefix.push_str(subtitle_id);
+                },
+                NoQuery,
+            )?
+            .empty_body()?,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+impl JellyfinClient {
+    pub async fn get_media_streams(&self, item: &str) -> Result<JsonResponse<ItemMediaStreams>> {
+        self.send_request_json(
+            self.get(
+                |prefix: &mut String| {
+                    prefix.push_str("/Items/");
+                    prefix.push_str(item);
+                },
+                &MediaStreamsQuery {
+                    user_id: &self.get_auth().user.id,
+                    fields: "MediaStreams",
+                },
+            )?
+            .empty_body()?,
+        )
+        .await
+    }
+
+    /// uri of an external subtitle stream, text subtitles other than ass are converted to srt
+    pub fn get_subtitle_uri(&self, item: &str, stream: &MediaStream) -> Result<Uri> {
+        let format = match stream.codec.as_deref() {
+            Some(codec @ ("ass" | "ssa")) => codec,
+            _ => "srt",
+        };
+        self.build_absolute_uri(
+            |prefix: &mut String| {
+                prefix.push_str("/Videos/");
+                prefix.push_str(item);
+                prefix.push('/');
+                prefix.push_str(item);
+                prefix.push_str("/Subtitles/");
+                prefix.push_str(&stream.index.to_string());
+                prefix.push_str("/Stream.");
+                prefix.push_str(format);
+            },
+            NoQuery,
+        )
+        .context("assembling subtitle uri")
+    }
+}
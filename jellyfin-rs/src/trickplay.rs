@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use serde::Deserialize;
+
+use crate::{
+    Authed, JellyfinClient, Result,
+    connect::JsonResponse,
+    items::UserIdQuery,
+    request::{NoQuery, RequestBuilderExt},
+};
+
+/// Layout of the trickplay tiles generated for one thumbnail width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TrickplayInfo {
+    pub width: u32,
+    pub height: u32,
+    /// thumbnails per row of a tile
+    pub tile_width: u32,
+    /// thumbnails per column of a tile
+    pub tile_height: u32,
+    pub thumbnail_count: u32,
+    /// milliseconds between two thumbnails
+    pub interval: u32,
+    pub bandwidth: u32,
+}
+
+/// Position of the thumbnail for a timestamp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrickplayThumbnail {
+    /// index of the tile image
+    pub tile: u32,
+    /// pixel offset of the thumbnail within the tile
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TrickplayInfo {
+    /// thumbnail closest before `seconds`
+    pub fn thumbnail(&self, seconds: f64) -> TrickplayThumbnail {
+        let index = (seconds.max(0.0) * 1000.0 / self.interval.max(1) as f64) as u32;
+        let index = index.min(self.thumbnail_count.saturating_sub(1));
+        let per_tile = (self.tile_width * self.tile_height).max(1);
+        let offset = index % per_tile;
+        TrickplayThumbnail {
+            tile: index / per_tile,
+            x: offset % self.tile_width.max(1) * self.width,
+            y: offset / self.tile_width.max(1) * self.height,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ItemTrickplay {
+    /// media source id to the available widths
+    pub trickplay: Option<HashMap<String, HashMap<u32, TrickplayInfo>>>,
+}
+
+impl ItemTrickplay {
+    /// smallest generated width of the first media source
+    pub fn smallest(&self) -> Option<TrickplayInfo> {
+        self.trickplay
+            .as_ref()?
+            .values()
+            .next()?
+            .values()
+            .min_by_key(|info| info.width)
+            .copied()
+    }
+}
+
+impl<Auth: Authed> JellyfinClient<Auth> {
+    /// trickplay layouts of an item, they are only included in the single item endpoint
+    pub async fn get_trickplay_info(
+        &self,
+        item_id: &str,
+        user_id: Option<&str>,
+    ) -> Result<JsonResponse<ItemTrickplay>> {
+        self.send_request_json(
+            self.get(
+                |prefix: &mut String| {
+                    prefix.push_str("/Items/");
+                    prefix.push_str(item_id);
+                },
+                &UserIdQuery { user_id },
+            )?
+            .empty_body()?,
+        )
+        .await
+    }
+
+    /// jpeg with the thumbnails of tile `index` for the trickplay `width`
+    pub async fn get_trickplay_tile(&self, item_id: &str, width: u32, index: u32) -> Result<Bytes> {
+        Ok(self
+            .send_request(
+                self.get(
+                    |prefix: &mut String| {
+                        prefix.push_str("/Videos/");
+                        prefix.push_str(item_id);
+                        prefix.push_str("/Trickplay/");
+                        prefix.push_str(&width.to_string());
+                        prefix.push('/');
+                        prefix.push_str(&index.to_string());
+                        prefix.push_str(".jpg");
+                    },
+                    NoQuery,
+                )?
+                .empty_body()?,
+            )
+            .await?
+            .0
+            .into())
+    }
+}
@@ -37,6 +37,7 @@ pub struct UserView {
     pub view_type: UserViewType,
     pub image_tags: Option<HashMap<ImageType, String>>,
     pub sort_name: String,
+    #[serde(default)]
     pub collection_type: CollectionType,
 }
 
@@ -46,13 +47,19 @@ pub enum UserViewType {
     UserView,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum CollectionType {
     Playlists,
     Movies,
     TvShows,
     Music,
+    Books,
+    HomeVideos,
+    BoxSets,
+    /// libraries with mixed content have no collection type
+    #[default]
+    MixedContent,
     #[serde(other)]
     Unknown,
 }
@@ -1,6 +1,17 @@
-use std::path::PathBuf;
+use std::{
+    num::{NonZeroU32, NonZeroU64, NonZeroUsize},
+    path::PathBuf,
+    time::Duration,
+};
 
+pub use entries::{
+    grid::ScrollStyle,
+    icons::Icons,
+    layout::{AspectRatio, ImageLayout},
+    theme::Theme,
+};
 use libmpv::MpvProfile;
+pub use player_core::MpvCacheOptions;
 
 use crate::keybinds::Keybinds;
 
@@ -12,5 +23,93 @@ pub struct Config {
     pub mpv_log_level: String,
     pub mpv_profile: MpvProfile,
     pub help_prefixes: Vec<String>,
+    /// loaded after the built-in options of the profile and before [Config::mpv_config_file]
+    pub mpv_profile_config_file: Option<PathBuf>,
     pub mpv_config_file: Option<PathBuf>,
+    pub mpv_cache: MpvCacheOptions,
+    pub remember_volume_speed: bool,
+    pub focus_follows_mouse: bool,
+    /// scroll with the mouse wheel and play entries by clicking them
+    pub mouse_navigation: bool,
+    /// play on a single click instead of a double click
+    pub single_click_play: bool,
+    pub subtitle_search_language: String,
+    pub sleep_timer_minutes: NonZeroU64,
+    pub sleep_timer_action: SleepTimerAction,
+    pub on_playback_error: PlaybackErrorAction,
+    pub quit_action: QuitAction,
+    pub default_item_action: DefaultItemAction,
+    pub scroll_style: ScrollStyle,
+    /// rows of grids and entries of lists beyond the visible ones whose images are loaded
+    pub prefetch_margin: usize,
+    /// number of items requested for each row of the home screen
+    pub home_row_limit: NonZeroU32,
+    pub max_connections: NonZeroUsize,
+    /// requests taking longer are given up, `None` waits forever
+    pub request_timeout: Option<Duration>,
+    /// additional attempts of failed GET requests
+    pub request_retries: u32,
+    pub prefetch_next_item: bool,
+    /// show a desktop notification when a new item starts playing, requires the mpris feature
+    pub now_playing_notification: bool,
+    /// animated bars in the player while music plays
+    pub music_visualizer: bool,
+    /// keep the screensaver off during playback, requires the inhibit feature
+    pub inhibit_screensaver: bool,
+    pub preferred_audio_lang: Option<String>,
+    pub preferred_subtitle_lang: Option<String>,
+    pub image_fetch_timeout: Duration,
+    /// ask whether to resume or start over when playing a partially watched item
+    pub resume_prompt: bool,
+    /// queue the following episodes of the series when playing an episode
+    pub autoplay_next: bool,
+    pub up_next_seconds: u64,
+    pub clear_images_on_play: bool,
+    pub library_stats: bool,
+    pub key_sequence_timeout: Option<Duration>,
+    pub cache_dir: PathBuf,
+    /// maximum size of cached images in bytes, 0 is unlimited
+    pub image_cache_size: u64,
+    /// maximum number of images kept ready for display in memory, 0 is unlimited
+    pub memory_image_cache_size: usize,
+    /// maximum number of images decoded at the same time
+    pub image_decode_concurrency: usize,
+    pub icons: Icons,
+    pub theme: Theme,
+    pub image_layout: ImageLayout,
+}
+
+impl Config {
+    /// whether any mouse feature is enabled and the terminal has to report mouse events
+    pub fn capture_mouse(&self) -> bool {
+        self.focus_follows_mouse || self.mouse_navigation
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepTimerAction {
+    Pause,
+    Quit,
+}
+
+/// What happens when an item in the playlist fails to load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackErrorAction {
+    Skip,
+    Stop,
+}
+
+/// What the quit keybind does, the back keybind always returns to the previous screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuitAction {
+    Back,
+    Exit,
+}
+
+/// What the activate keybind does on an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultItemAction {
+    Open,
+    Play,
+    Details,
 }
@@ -1,27 +1,35 @@
-use std::{pin::Pin, sync::Arc};
+use std::{collections::HashMap, pin::Pin, sync::Arc};
 
 use crate::config::Config;
 use ::keybinds::KeybindEvents;
-use entries::image::cache::ImageProtocolCache;
+use entries::{
+    context::EntryContext,
+    image::{available::ImagesAvailable, cache::ImageProtocolCache},
+};
 use jellyfin::{Auth, JellyfinClient, socket::JellyfinWebSocket};
 use player_core::{OwnedPlayerHandle, PlayerHandle};
 use ratatui::DefaultTerminal;
 use ratatui_image::picker::Picker;
 use sqlx::SqliteConnection;
 use stats_data::Stats;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell};
 
-pub struct TuiContext {
+/// State of the screens connected to one server, created anew when switching servers
+pub struct TuiContext<'a> {
     pub jellyfin: JellyfinClient<Auth>,
     pub jellyfin_socket: JellyfinWebSocket,
-    pub term: DefaultTerminal,
-    pub config: Config,
-    pub events: KeybindEvents,
+    pub term: &'a mut DefaultTerminal,
+    pub config: &'a Config,
+    pub events: &'a mut KeybindEvents,
     pub image_picker: Arc<Picker>,
     pub cache: Arc<Mutex<SqliteConnection>>,
     pub image_cache: ImageProtocolCache,
     pub mpv_handle: OwnedPlayerHandle,
     pub stats: Stats,
+    /// whether the server has a remote subtitle provider, checked once when first needed
+    pub subtitle_providers: OnceCell<bool>,
+    /// total runtime in hours of each library, counted on the first visit of the home screen
+    pub library_runtimes: parking_lot::Mutex<HashMap<String, u64>>,
 }
 
 pub struct TuiContextProj<'p> {
@@ -35,9 +43,11 @@ pub struct TuiContextProj<'p> {
     pub image_cache: &'p mut ImageProtocolCache,
     pub mpv_handle: &'p PlayerHandle,
     pub stats: &'p Stats,
+    pub subtitle_providers: &'p OnceCell<bool>,
+    pub library_runtimes: &'p parking_lot::Mutex<HashMap<String, u64>>,
 }
 
-impl TuiContext {
+impl TuiContext<'_> {
     #[doc(hidden)]
     #[inline]
     pub fn project<'__pin>(self: Pin<&'__pin mut Self>) -> TuiContextProj<'__pin> {
@@ -53,19 +63,55 @@ impl TuiContext {
                 image_cache,
                 mpv_handle,
                 stats,
+                subtitle_providers,
+                library_runtimes,
             } = self.get_unchecked_mut();
             TuiContextProj {
                 jellyfin,
                 jellyfin_socket: Pin::new_unchecked(jellyfin_socket),
-                term,
-                config,
-                events,
+                term: &mut **term,
+                config: &**config,
+                events: &mut **events,
                 image_picker,
                 cache,
                 image_cache,
                 mpv_handle,
                 stats,
+                subtitle_providers,
+                library_runtimes,
             }
         }
     }
 }
+
+impl TuiContext<'_> {
+    /// Handles for the entries of a screen, their images notify `available` once loaded
+    pub fn entry_context(&self, available: &ImagesAvailable) -> Arc<EntryContext> {
+        Arc::new(EntryContext {
+            jellyfin: self.jellyfin.clone(),
+            db: self.cache.clone(),
+            cache: self.image_cache.clone(),
+            available: available.clone(),
+            picker: self.image_picker.clone(),
+            stats: self.stats.clone(),
+            theme: self.config.theme,
+            image_layout: self.config.image_layout,
+        })
+    }
+}
+
+impl TuiContextProj<'_> {
+    /// Handles for the entries of a screen, their images notify `available` once loaded
+    pub fn entry_context(&self, available: &ImagesAvailable) -> Arc<EntryContext> {
+        Arc::new(EntryContext {
+            jellyfin: self.jellyfin.clone(),
+            db: self.cache.clone(),
+            cache: self.image_cache.clone(),
+            available: available.clone(),
+            picker: self.image_picker.clone(),
+            stats: self.stats.clone(),
+            theme: self.config.theme,
+            image_layout: self.config.image_layout,
+        })
+    }
+}
@@ -1,16 +1,76 @@
-use entries::entry::{Entry, EntryInner};
-use jellyfin::items::{ItemType, MediaItem};
+use color_eyre::{Result, eyre::Context};
+use entries::{
+    entry::{Entry, EntryInner},
+    icons::Icons,
+};
+use jellyfin::{
+    JellyfinClient,
+    items::{ItemType, MediaItem},
+};
 
-use crate::state::{LoadPlay, NextScreen};
+use crate::{
+    config::DefaultItemAction,
+    state::{LoadPlay, NextScreen, UserViewFilter},
+};
+
+/// Change of the user data of an entry, see [change_user_data]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserDataChange {
+    ToggleWatched,
+    ToggleFavorite,
+    ResetProgress,
+}
+
+/// Sends `change` of the item of `entry` to the server and shows the new state.
+///
+/// Entries without item or user data are left alone. Screens show errors on top of themselves
+/// with [NextScreen::Error], so a failed request does not leave them.
+pub async fn change_user_data(
+    jellyfin: &JellyfinClient,
+    entry: &mut Entry,
+    change: UserDataChange,
+    icons: &Icons,
+) -> Result<()> {
+    let Some(id) = entry.item_id() else {
+        return Ok(());
+    };
+    let response = match change {
+        UserDataChange::ToggleWatched => match entry.played() {
+            Some(true) => jellyfin.mark_unplayed(id).await,
+            Some(false) => jellyfin.mark_played(id).await,
+            None => return Ok(()),
+        }
+        .context("changing played state")?,
+        UserDataChange::ToggleFavorite => match entry.favorite() {
+            Some(favorite) => jellyfin.set_favorite(id, !favorite).await,
+            None => return Ok(()),
+        }
+        .context("changing favorite state")?,
+        UserDataChange::ResetProgress => jellyfin
+            .mark_unplayed(id)
+            .await
+            .context("resetting playback progress")?,
+    };
+    let user_data = response
+        .deserialize()
+        .await
+        .context("deserializing user data")?;
+    entry.set_user_data(user_data, icons);
+    Ok(())
+}
 
 pub trait EntryExt {
     fn item_id(&self) -> Option<&str>;
+    fn played(&self) -> Option<bool>;
+    fn favorite(&self) -> Option<bool>;
     fn play(&self) -> Option<NextScreen>;
     fn open(&self) -> NextScreen;
     fn play_open(&self) -> NextScreen;
+    fn activate(&self, action: DefaultItemAction) -> NextScreen;
     fn episode(&self) -> Option<NextScreen>;
     fn season(&self) -> Option<NextScreen>;
     fn series(&self) -> Option<NextScreen>;
+    fn shuffle(&self) -> Option<NextScreen>;
 }
 
 impl EntryExt for Entry {
@@ -20,6 +80,21 @@ impl EntryExt for Entry {
             EntryInner::View(_) => None,
         }
     }
+    fn played(&self) -> Option<bool> {
+        match self.inner() {
+            EntryInner::Item(item) => item.user_data.as_ref().map(|user_data| user_data.played),
+            EntryInner::View(_) => None,
+        }
+    }
+    fn favorite(&self) -> Option<bool> {
+        match self.inner() {
+            EntryInner::Item(item) => item
+                .user_data
+                .as_ref()
+                .map(|user_data| user_data.is_favorite),
+            EntryInner::View(_) => None,
+        }
+    }
     fn play(&self) -> Option<NextScreen> {
         match self.inner() {
             EntryInner::View(_) => None,
@@ -28,16 +103,33 @@ impl EntryExt for Entry {
     }
     fn open(&self) -> NextScreen {
         match self.inner() {
-            EntryInner::View(view) => NextScreen::LoadUserView(view.clone()),
+            EntryInner::View(view) => NextScreen::LoadUserView {
+                view: view.clone(),
+                filter: UserViewFilter::default(),
+            },
             EntryInner::Item(item) => open(item),
         }
     }
     fn play_open(&self) -> NextScreen {
         match self.inner() {
-            EntryInner::View(view) => NextScreen::LoadUserView(view.clone()),
+            EntryInner::View(view) => NextScreen::LoadUserView {
+                view: view.clone(),
+                filter: UserViewFilter::default(),
+            },
             EntryInner::Item(item) => play(item),
         }
     }
+    fn activate(&self, action: DefaultItemAction) -> NextScreen {
+        match (self.inner(), action) {
+            (EntryInner::View(view), _) => NextScreen::LoadUserView {
+                view: view.clone(),
+                filter: UserViewFilter::default(),
+            },
+            (EntryInner::Item(item), DefaultItemAction::Open) => open(item),
+            (EntryInner::Item(item), DefaultItemAction::Play) => play(item),
+            (EntryInner::Item(item), DefaultItemAction::Details) => details(item),
+        }
+    }
     fn episode(&self) -> Option<NextScreen> {
         match self.inner() {
             EntryInner::Item(i) => Some(episode(i)),
@@ -56,12 +148,52 @@ impl EntryExt for Entry {
             _ => None,
         }
     }
+    fn shuffle(&self) -> Option<NextScreen> {
+        let id = match self.inner() {
+            EntryInner::View(view) => &view.id,
+            EntryInner::Item(MediaItem {
+                id,
+                image_tags: _,
+                image_blur_hashes: _,
+                media_type: _,
+                name: _,
+                sort_name: _,
+                overview: _,
+                item_type:
+                    ItemType::Folder
+                    | ItemType::BoxSet
+                    | ItemType::Series
+                    | ItemType::MusicAlbum
+                    | ItemType::Season {
+                        series_id: _,
+                        series_name: _,
+                    },
+                user_data: _,
+                episode_index: _,
+                season_index: _,
+                run_time_ticks: _,
+                playlist_item_id: _,
+                video_3d_format: _,
+                extra_type: _,
+                genre_items: _,
+                studios: _,
+                people: _,
+                community_rating: _,
+                production_year: _,
+            }) => id,
+            EntryInner::Item(_) => return None,
+        };
+        Some(NextScreen::LoadPlayItem(LoadPlay::Shuffle {
+            id: id.clone(),
+        }))
+    }
 }
 pub fn play(item: &MediaItem) -> NextScreen {
     NextScreen::LoadPlayItem(match item {
         v @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -71,10 +203,19 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::Movie(v.clone()),
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -84,10 +225,22 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
-        } => LoadPlay::Playlist { id: id.clone() },
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
+        } => LoadPlay::Playlist {
+            id: id.clone(),
+            entry_id: None,
+        },
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -97,10 +250,19 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::Series { id: id.clone() },
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -114,6 +276,14 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::Season {
             series_id: series_id.clone(),
             id: id.clone(),
@@ -121,6 +291,7 @@ pub fn play(item: &MediaItem) -> NextScreen {
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -136,6 +307,14 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::Episode {
             series_id: series_id.clone(),
             id: id.clone(),
@@ -143,15 +322,29 @@ pub fn play(item: &MediaItem) -> NextScreen {
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
             overview: _,
-            item_type: ItemType::Music { album_id, album: _ },
+            item_type:
+                ItemType::Music {
+                    album_id,
+                    album: _,
+                    album_artist: _,
+                },
             user_data: _,
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::Music {
             id: id.clone(),
             album_id: album_id.clone(),
@@ -159,6 +352,7 @@ pub fn play(item: &MediaItem) -> NextScreen {
         MediaItem {
             id,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -168,10 +362,41 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => LoadPlay::MusicAlbum { id: id.clone() },
+        MediaItem {
+            id,
+            image_tags: _,
+            image_blur_hashes: _,
+            media_type: _,
+            name: _,
+            sort_name: _,
+            overview: _,
+            item_type: ItemType::BoxSet,
+            user_data: _,
+            episode_index: _,
+            season_index: _,
+            run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
+        } => LoadPlay::BoxSet { id: id.clone() },
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -181,6 +406,14 @@ pub fn play(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => return NextScreen::UnsupportedItem,
     })
 }
@@ -190,6 +423,7 @@ fn open(item: &MediaItem) -> NextScreen {
         v @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -199,6 +433,7 @@ fn open(item: &MediaItem) -> NextScreen {
                 | ItemType::Music {
                     album_id: _,
                     album: _,
+                    album_artist: _,
                 }
                 | ItemType::Episode {
                     season_id: _,
@@ -210,10 +445,19 @@ fn open(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::ItemDetails(v.clone()),
         v @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -221,6 +465,7 @@ fn open(item: &MediaItem) -> NextScreen {
             item_type:
                 ItemType::Playlist
                 | ItemType::Folder
+                | ItemType::BoxSet
                 | ItemType::Series
                 | ItemType::MusicAlbum
                 | ItemType::Season {
@@ -231,10 +476,19 @@ fn open(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::FetchItemListDetails(v.clone()),
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -244,14 +498,51 @@ fn open(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::UnsupportedItem,
     }
 }
+/// fetches the full details of playable items, containers are opened with their details and children
+fn details(item: &MediaItem) -> NextScreen {
+    match item.item_type {
+        ItemType::Movie
+        | ItemType::Music {
+            album_id: _,
+            album: _,
+            album_artist: _,
+        }
+        | ItemType::Episode {
+            season_id: _,
+            season_name: _,
+            series_id: _,
+            series_name: _,
+        } => NextScreen::FetchItemDetails(item.id.clone()),
+        ItemType::Playlist
+        | ItemType::Folder
+        | ItemType::BoxSet
+        | ItemType::Series
+        | ItemType::MusicAlbum
+        | ItemType::Season {
+            series_id: _,
+            series_name: _,
+        }
+        | ItemType::Unknown => open(item),
+    }
+}
+
 fn episode(item: &MediaItem) -> NextScreen {
     match item {
         v @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -261,6 +552,7 @@ fn episode(item: &MediaItem) -> NextScreen {
                 | ItemType::Music {
                     album_id: _,
                     album: _,
+                    album_artist: _,
                 }
                 | ItemType::Episode {
                     season_id: _,
@@ -272,10 +564,19 @@ fn episode(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::ItemDetails(v.clone()),
         i @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -283,6 +584,7 @@ fn episode(item: &MediaItem) -> NextScreen {
             item_type:
                 ItemType::Playlist
                 | ItemType::Folder
+                | ItemType::BoxSet
                 | ItemType::MusicAlbum
                 | ItemType::Series
                 | ItemType::Season {
@@ -293,10 +595,19 @@ fn episode(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::ItemDetails(i.clone()),
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -306,6 +617,14 @@ fn episode(item: &MediaItem) -> NextScreen {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => NextScreen::UnsupportedItem,
     }
 }
@@ -315,6 +634,7 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -330,10 +650,19 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetailsRef(id.clone())),
         i @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -347,10 +676,19 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetails(i.clone())),
         i @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -360,23 +698,46 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetails(i.clone())),
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
             overview: _,
-            item_type: ItemType::Music { album_id, album: _ },
+            item_type:
+                ItemType::Music {
+                    album_id,
+                    album: _,
+                    album_artist: _,
+                },
             user_data: _,
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetailsRef(album_id.clone())),
         i @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -386,10 +747,19 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetails(i.clone())),
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -399,6 +769,14 @@ pub fn season(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::UnsupportedItem),
         _ => None,
     }
@@ -409,6 +787,7 @@ fn series(item: &MediaItem) -> Option<NextScreen> {
         MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -428,10 +807,19 @@ fn series(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetailsRef(series_id.clone())),
         i @ MediaItem {
             id: _,
             image_tags: _,
+            image_blur_hashes: _,
             media_type: _,
             name: _,
             sort_name: _,
@@ -441,6 +829,14 @@ fn series(item: &MediaItem) -> Option<NextScreen> {
             episode_index: _,
             season_index: _,
             run_time_ticks: _,
+            playlist_item_id: _,
+            video_3d_format: _,
+            extra_type: _,
+            genre_items: _,
+            studios: _,
+            people: _,
+            community_rating: _,
+            production_year: _,
         } => Some(NextScreen::FetchItemListDetails(i.clone())),
         _ => None,
     }
@@ -7,14 +7,33 @@ pub struct Keybinds {
     pub logger: BindingMap<LoggerCommand>,
     pub fetch: BindingMap<LoadingCommand>,
     pub play_mpv: BindingMap<MpvCommand>,
+    pub play_mpv_tracks: BindingMap<MpvCommand>,
+    pub play_mpv_queue: BindingMap<MpvCommand>,
+    pub play_mpv_chapters: BindingMap<MpvCommand>,
     pub user_view: BindingMap<UserViewCommand>,
+    pub user_view_sort: BindingMap<UserViewCommand>,
+    pub genres: BindingMap<GenresCommand>,
     pub home_screen: BindingMap<HomeScreenCommand>,
     pub login_info: BindingMap<LoginInfoCommand>,
+    pub server_selection: BindingMap<ServerSelectionCommand>,
     pub error: BindingMap<ErrorCommand>,
     pub item_details: BindingMap<ItemDetailsCommand>,
     pub item_list_details: BindingMap<ItemListDetailsCommand>,
     pub refresh_item: BindingMap<RefreshItemCommand>,
+    pub add_to_playlist: BindingMap<AddToPlaylistCommand>,
+    pub remote_subtitles: BindingMap<RemoteSubtitlesCommand>,
+    pub resume: BindingMap<ResumeCommand>,
+    pub history: BindingMap<HistoryCommand>,
+    pub search: BindingMap<SearchCommand>,
     pub unsupported_item: BindingMap<UnsupportedItemCommand>,
+    pub global: BindingMap<GlobalCommand>,
+}
+
+/// Commands available on every screen
+#[derive(Debug, Clone, Copy, Command)]
+pub enum GlobalCommand {
+    GoToNowPlaying,
+    SwitchServer,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
@@ -32,32 +51,92 @@ pub enum LoggerCommand {
     MessagesDown,
     Escape,
     Quit,
+    Back,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum StatsCommand {
     Quit,
+    Back,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum UnsupportedItemCommand {
     Quit,
+    Back,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum LoadingCommand {
     Quit,
+    Back,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum MpvCommand {
     Quit,
+    Back,
     Pause,
+    SleepTimer,
+    CancelSleepTimer,
+    CancelUpNext,
+    PlayUpNext,
+    Next,
+    Previous,
+    SeekForward,
+    SeekBackward,
+    VolumeUp,
+    VolumeDown,
+    SpeedUp,
+    SpeedDown,
+    SpeedReset,
+    NextChapter,
+    PrevChapter,
+    Subtitles,
+    AudioTracks,
+    ShowQueue,
+    ShowChapters,
+    Up,
+    Down,
+    Select,
+    Remove,
+    MoveUp,
+    MoveDown,
+    CopyStreamUrl,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum RefreshItemCommand {
     Quit,
+    Back,
+    Up,
+    Down,
+    Select,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum AddToPlaylistCommand {
+    Quit,
+    Back,
+    Up,
+    Down,
+    Select,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum RemoteSubtitlesCommand {
+    Quit,
+    Back,
+    Up,
+    Down,
+    Select,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum ResumeCommand {
+    Quit,
+    Back,
     Up,
     Down,
     Select,
@@ -66,36 +145,110 @@ pub enum RefreshItemCommand {
 #[derive(Debug, Clone, Copy, Command)]
 pub enum UserViewCommand {
     Quit,
+    Back,
     Reload,
     Prev,
     Next,
     Up,
     Down,
+    PageUp,
+    PageDown,
+    First,
+    Last,
     Open,
     Play,
     OpenEpisode,
     OpenSeason,
     OpenSeries,
     RefreshItem,
+    AddToPlaylist,
+    Activate,
+    ToggleUnplayed,
+    ToggleWatched,
+    ShufflePlay,
+    ToggleFavorite,
+    ResetProgress,
+    Sort,
+    Select,
+    Genres,
+    ScanLibrary,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum GenresCommand {
+    Quit,
+    Back,
+    Up,
+    Down,
+    Open,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum HomeScreenCommand {
     Quit,
+    Back,
     Reload,
     Left,
     Right,
+    PageLeft,
+    PageRight,
+    First,
+    Last,
     Up,
     Down,
     Open,
     Play,
     PlayOpen,
+    ShufflePlay,
     OpenEpisode,
     OpenSeason,
     OpenSeries,
     RefreshItem,
+    AddToPlaylist,
+    ToggleWatched,
+    ToggleFavorite,
+    ResetProgress,
+    Activate,
     ShowStats,
     ShowLogs,
+    ShowHistory,
+    Search,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum SearchCommand {
+    Quit,
+    Back,
+    Delete,
+    Up,
+    Down,
+    Left,
+    Right,
+    Open,
+    Play,
+    Activate,
+    ToggleFavorite,
+    NextKind,
+    PrevKind,
+}
+
+#[derive(Debug, Clone, Copy, Command)]
+pub enum HistoryCommand {
+    Quit,
+    Back,
+    Reload,
+    Clear,
+    Prev,
+    Next,
+    Up,
+    Down,
+    Open,
+    Play,
+    OpenEpisode,
+    OpenSeason,
+    OpenSeries,
+    Activate,
+    ToggleFavorite,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
@@ -107,30 +260,48 @@ pub enum LoginInfoCommand {
     Quit,
 }
 
+#[derive(Debug, Clone, Copy, Command)]
+pub enum ServerSelectionCommand {
+    Quit,
+    Up,
+    Down,
+    Select,
+}
+
 #[derive(Debug, Clone, Copy, Command)]
 pub enum ErrorCommand {
     Quit,
+    Back,
     Kill,
     Up,
     Down,
-    Left,
-    Right,
+    PageUp,
+    PageDown,
     ShowLogs,
+    Retry,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum ItemDetailsCommand {
     Quit,
+    Back,
     Up,
     Down,
+    PageUp,
+    PageDown,
     Play,
     Reload,
     RefreshItem,
+    DownloadSubtitles,
+    PrevLink,
+    NextLink,
+    OpenLink,
 }
 
 #[derive(Debug, Clone, Copy, Command)]
 pub enum ItemListDetailsCommand {
     Quit,
+    Back,
     Reload,
     Up,
     Down,
@@ -143,4 +314,7 @@ pub enum ItemListDetailsCommand {
     OpenSeries,
     RefreshCurrentItem,
     RefreshParentItem,
+    MarkParentWatched,
+    AddToPlaylist,
+    RemoveFromPlaylist,
 }
@@ -3,21 +3,113 @@ use std::collections::HashMap;
 use color_eyre::{Result, eyre::Report};
 use entries::{image::available::ImagesAvailable, list::EntryList, screen::EntryScreen};
 use jellyfin::{
-    items::{MediaItem, RefreshItemQuery},
+    items::{MediaItem, NameIdPair, RefreshItemQuery},
+    subtitles::RemoteSubtitleInfo,
     user_views::UserView,
 };
+use keybinds::Command;
 use tracing::{debug, instrument};
 
+use crate::{config::QuitAction, keybinds::GlobalCommand};
+
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LoadPlay {
     Movie(MediaItem),
-    Series { id: String },
-    Season { series_id: String, id: String },
-    Episode { series_id: String, id: String },
-    Playlist { id: String },
-    Music { id: String, album_id: String },
-    MusicAlbum { id: String },
+    Series {
+        id: String,
+    },
+    Season {
+        series_id: String,
+        id: String,
+    },
+    Episode {
+        series_id: String,
+        id: String,
+    },
+    Playlist {
+        id: String,
+        /// playlist entry id of the item to start at, the first item if `None`
+        entry_id: Option<String>,
+    },
+    Music {
+        id: String,
+        album_id: String,
+    },
+    MusicAlbum {
+        id: String,
+    },
+    /// the movies and episodes of a collection in release order
+    BoxSet {
+        id: String,
+    },
+    /// every movie, episode and song below the item in random order
+    Shuffle {
+        id: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum PlaylistTarget {
+    Existing(String),
+    New(String),
+}
+
+/// Server side filters and sorting applied when fetching the items of a user view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserViewFilter {
+    pub unplayed: bool,
+    pub sort: ItemSort,
+    pub descending: bool,
+}
+
+impl Default for UserViewFilter {
+    fn default() -> Self {
+        Self {
+            unplayed: false,
+            sort: ItemSort::DateAdded,
+            descending: true,
+        }
+    }
+}
+
+/// Field the items of a user view are sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItemSort {
+    #[default]
+    DateAdded,
+    Name,
+    DatePlayed,
+    CommunityRating,
+    PremiereDate,
+}
+
+/// Item types included in search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchKind {
+    #[default]
+    All,
+    Movies,
+    Series,
+    Episodes,
+    Music,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Genre,
+    Studio,
+    Person,
+}
+
+/// Genre, studio or person of an item, opens all items sharing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemLink {
+    pub kind: LinkKind,
+    pub id: String,
+    pub name: String,
+    /// only items below this library are shown
+    pub parent: Option<String>,
 }
 
 #[derive(Debug)]
@@ -26,14 +118,38 @@ pub enum NextScreen {
     HomeScreenData {
         resume: Vec<MediaItem>,
         next_up: Vec<MediaItem>,
+        recently_added: Vec<MediaItem>,
+        favorites: Vec<MediaItem>,
         views: Vec<UserView>,
         latest: HashMap<String, Vec<MediaItem>>,
     },
     HomeScreen(EntryScreen, ImagesAvailable),
-    LoadUserView(UserView),
+    LoadUserView {
+        view: UserView,
+        filter: UserViewFilter,
+    },
     UserView {
         view: UserView,
+        filter: UserViewFilter,
+        /// first page of items
+        items: Vec<MediaItem>,
+        /// the server has more items than the first page
+        more: bool,
+    },
+    LoadGenres(UserView),
+    Genres {
+        view: UserView,
+        genres: Vec<NameIdPair>,
+    },
+    LoadLinkedItems {
+        link: ItemLink,
+        filter: UserViewFilter,
+    },
+    LinkedItems {
+        link: ItemLink,
+        filter: UserViewFilter,
         items: Vec<MediaItem>,
+        more: bool,
     },
     LoadPlayItem(LoadPlay),
     Play {
@@ -41,17 +157,108 @@ pub enum NextScreen {
         index: usize,
     },
     Error(Report),
+    /// error of a screen that only loads data, the error screen can open it again
+    RetryableError {
+        error: Report,
+        retry: Box<NextScreen>,
+    },
     ItemDetails(MediaItem),
     ItemListDetailsData(MediaItem, Vec<MediaItem>),
     ItemListDetails(MediaItem, EntryList, ImagesAvailable),
     FetchItemListDetails(MediaItem),
     FetchItemListDetailsRef(String),
+    /// marks the series or season with all its children played and shows it again
+    MarkPlayed(MediaItem),
     FetchItemDetails(String),
     UnsupportedItem,
     RefreshItem(String),
     SendRefreshItem(String, RefreshItemQuery),
+    /// starts a scan of all libraries
+    ScanLibrary,
+    LoadAddToPlaylist(String),
+    AddToPlaylist {
+        item: String,
+        playlists: Vec<MediaItem>,
+    },
+    SendAddToPlaylist {
+        item: String,
+        playlist: PlaylistTarget,
+    },
+    /// removes the entry from the playlist and returns to the reloaded playlist
+    SendRemoveFromPlaylist {
+        playlist: String,
+        entry: String,
+    },
+    FetchRemoteSubtitles(String),
+    RemoteSubtitles {
+        item: String,
+        subtitles: Vec<RemoteSubtitleInfo>,
+    },
+    DownloadRemoteSubtitles {
+        item: String,
+        subtitle: String,
+    },
     Stats,
     Logs,
+    History,
+    NowPlaying,
+    /// leaves all screens and connects to the next saved server
+    SwitchServer,
+    Search,
+    SearchResults {
+        term: String,
+        kind: SearchKind,
+        items: Vec<MediaItem>,
+    },
+}
+
+impl NextScreen {
+    /// copy of screens that only load data, offered as retry when they fail. Screens sending
+    /// changes are not offered, retrying them could repeat a request that reached the server.
+    pub fn retry(&self) -> Option<NextScreen> {
+        Some(match self {
+            NextScreen::LoadHomeScreen => NextScreen::LoadHomeScreen,
+            NextScreen::LoadUserView { view, filter } => NextScreen::LoadUserView {
+                view: view.clone(),
+                filter: *filter,
+            },
+            NextScreen::LoadGenres(view) => NextScreen::LoadGenres(view.clone()),
+            NextScreen::LoadLinkedItems { link, filter } => NextScreen::LoadLinkedItems {
+                link: link.clone(),
+                filter: *filter,
+            },
+            NextScreen::LoadPlayItem(load_play) => NextScreen::LoadPlayItem(load_play.clone()),
+            NextScreen::FetchItemListDetails(item) => {
+                NextScreen::FetchItemListDetails(item.clone())
+            }
+            NextScreen::FetchItemListDetailsRef(id) => {
+                NextScreen::FetchItemListDetailsRef(id.clone())
+            }
+            NextScreen::FetchItemDetails(id) => NextScreen::FetchItemDetails(id.clone()),
+            NextScreen::LoadAddToPlaylist(id) => NextScreen::LoadAddToPlaylist(id.clone()),
+            NextScreen::FetchRemoteSubtitles(id) => NextScreen::FetchRemoteSubtitles(id.clone()),
+            _ => return None,
+        })
+    }
+
+    /// error screen for `error`, offering to open `retry` again
+    pub fn error(error: Report, retry: Option<NextScreen>) -> NextScreen {
+        match retry {
+            Some(retry) => NextScreen::RetryableError {
+                error,
+                retry: Box::new(retry),
+            },
+            None => NextScreen::Error(error),
+        }
+    }
+}
+
+/// Screen opened by a command of the global keybind map
+pub fn global_screen(name: &str) -> Option<NextScreen> {
+    match GlobalCommand::from_name(name)? {
+        GlobalCommand::GoToNowPlaying => Some(NextScreen::NowPlaying),
+        GlobalCommand::SwitchServer => Some(NextScreen::SwitchServer),
+    }
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -63,12 +270,17 @@ pub enum Navigation {
         next: NextScreen,
     },
     Replace(NextScreen),
+    /// result of a quit command, depending on [QuitAction] this pops or exits
+    Quit,
     Exit,
+    /// leaves all screens and connects to the next saved server
+    SwitchServer,
 }
 
 #[derive(Debug)]
 pub struct State {
     screen_stack: Vec<NextScreen>,
+    quit_action: QuitAction,
 }
 
 impl State {
@@ -84,7 +296,8 @@ impl State {
                 self.screen_stack.push(current);
                 self.screen_stack.push(next);
             }
-            Navigation::Exit => {
+            Navigation::Quit if self.quit_action == QuitAction::Back => {}
+            Navigation::Quit | Navigation::Exit | Navigation::SwitchServer => {
                 debug!("full exit returned");
                 self.screen_stack.clear();
             }
@@ -95,21 +308,16 @@ impl State {
         debug!("state stack: {:?}", self.screen_stack);
         self.screen_stack.pop()
     }
-    pub fn new() -> Self {
+    pub fn new(quit_action: QuitAction) -> Self {
         let mut stack = Vec::with_capacity(8);
         stack.push(NextScreen::LoadHomeScreen);
         Self {
             screen_stack: stack,
+            quit_action,
         }
     }
 }
 
-impl Default for State {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 pub trait ToNavigation {
     fn to_nav(self) -> Navigation;
 }
@@ -10,6 +10,7 @@
   stdenv,
   attach ? false,
   withMpris ? stdenv.isLinux, # enable media player dbus interface
+  withInhibit ? stdenv.isLinux, # inhibit the screensaver during playback
 }:
 let
   fileset = lib.fileset.unions [
@@ -68,7 +69,10 @@ let
         inherit src;
         pname = "jellyhaj";
         version = (builtins.fromTOML (builtins.readFile ./Cargo.toml)).package.version;
-        features = (lib.optional attach "attach") ++ (lib.optional withMpris "mpris");
+        features =
+          (lib.optional attach "attach")
+          ++ (lib.optional withMpris "mpris")
+          ++ (lib.optional withInhibit "inhibit");
       }
     ).overrideAttrs
       (
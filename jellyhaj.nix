@@ -8,6 +8,7 @@
   sqlite,
   versionCheckHook,
   withMpris ? stdenv.isLinux, # enable media player dbus interface
+  withInhibit ? stdenv.isLinux, # inhibit the screensaver during playback
 }:
 let
   fileset = lib.fileset.unions [
@@ -54,7 +55,7 @@ rustPlatform.buildRustPackage {
     "--skip=tests::events"
   ];
   cargoTestFlags = [ "--workspace" ];
-  buildFeatures = lib.optional withMpris "mpris";
+  buildFeatures = (lib.optional withMpris "mpris") ++ (lib.optional withInhibit "inhibit");
 
   meta = {
     description = "Terminal client for Jellyfin trying to reimplement parts of the web ui";
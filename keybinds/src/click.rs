@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+/// longest time between the clicks of a double click
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// Decides which left clicks should play the clicked entry
+///
+/// Clicks are compared by the entry they hit, so a double click may move within a card.
+#[derive(Debug)]
+pub struct ClickTracker<K = usize> {
+    single_click: bool,
+    last: Option<(Instant, K)>,
+}
+
+impl<K: PartialEq> ClickTracker<K> {
+    /// with `single_click` every click plays, otherwise only double clicks do
+    pub fn new(single_click: bool) -> Self {
+        Self {
+            single_click,
+            last: None,
+        }
+    }
+
+    /// registers a click on the entry, returns true if it should play
+    pub fn click(&mut self, entry: K) -> bool {
+        self.click_at(entry, Instant::now())
+    }
+
+    fn click_at(&mut self, entry: K, now: Instant) -> bool {
+        if self.single_click {
+            return true;
+        }
+        match self.last.take() {
+            Some((at, last)) if last == entry && now.duration_since(at) <= DOUBLE_CLICK_TIME => {
+                true
+            }
+            _ => {
+                self.last = Some((now, entry));
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{ClickTracker, DOUBLE_CLICK_TIME};
+
+    #[test]
+    fn double_click_on_same_entry() {
+        let mut clicks = ClickTracker::new(false);
+        let now = Instant::now();
+        assert!(!clicks.click_at(3, now));
+        assert!(clicks.click_at(3, now + Duration::from_millis(100)));
+        assert!(!clicks.click_at(3, now + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn clicks_on_different_entries() {
+        let mut clicks = ClickTracker::new(false);
+        let now = Instant::now();
+        assert!(!clicks.click_at(3, now));
+        assert!(!clicks.click_at(4, now + Duration::from_millis(100)));
+        assert!(clicks.click_at(4, now + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn slow_double_click() {
+        let mut clicks = ClickTracker::new(false);
+        let now = Instant::now();
+        assert!(!clicks.click_at(3, now));
+        assert!(!clicks.click_at(3, now + DOUBLE_CLICK_TIME + Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn single_click() {
+        let mut clicks = ClickTracker::new(true);
+        assert!(clicks.click(3));
+        assert!(clicks.click(3));
+    }
+}
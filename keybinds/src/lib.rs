@@ -1,3 +1,4 @@
+pub mod click;
 pub mod parse_config;
 pub mod stream;
 pub mod widget;
@@ -8,10 +9,15 @@ use ratatui_fallible_widget::FallibleWidget;
 use std::{
     collections::BTreeMap,
     fmt::{Debug, Display},
+    pin::Pin,
     sync::Arc,
+    time::Duration,
 };
+use tokio::time::Sleep;
 use tracing::{Span, debug, info_span};
 
+pub use click::ClickTracker;
+pub use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 pub use futures_util::StreamExt;
 
 ///reexport for proc macro
@@ -96,11 +102,16 @@ pub enum KeybindEvent<T: Command> {
     Render,
     Command(T),
     Text(Text),
+    Mouse(MouseEvent),
+    /// name of a command bound in every stream, see [KeybindEvents::set_global]
+    Global(&'static str),
 }
 
 pub struct KeybindEvents {
     events: EventStream,
     finished: bool,
+    global: BTreeMap<Key, &'static str>,
+    sequence_timeout: Option<Duration>,
 }
 
 impl KeybindEvents {
@@ -108,8 +119,56 @@ impl KeybindEvents {
         Ok(Self {
             events: EventStream::new(),
             finished: false,
+            global: BTreeMap::new(),
+            sequence_timeout: None,
         })
     }
+
+    /// Partially entered key sequences are discarded after this duration without input.
+    /// `None` waits for the next key indefinitely.
+    pub fn set_sequence_timeout(&mut self, timeout: Option<Duration>) {
+        self.sequence_timeout = timeout;
+    }
+
+    /// Keys of this map take precedence over the map of every stream.
+    /// Only single keys are supported, groups are ignored.
+    pub fn set_global<T: Command>(&mut self, map: &BindingMap<T>) {
+        self.global = map
+            .iter()
+            .filter_map(|(key, binding)| match binding {
+                KeyBinding::Command(c) => Some((key.clone(), c.to_name())),
+                _ => None,
+            })
+            .collect();
+    }
+}
+
+/// Copy of the map with only the commands `keep` accepts, groups left empty are dropped
+pub fn retain_commands<T: Command>(
+    map: &BindingMap<T>,
+    keep: &impl Fn(T) -> bool,
+) -> BindingMap<T> {
+    Arc::new(
+        map.iter()
+            .filter_map(|(key, binding)| {
+                let binding = match binding {
+                    KeyBinding::Command(c) if !keep(*c) => return None,
+                    KeyBinding::Group { map, name } => {
+                        let map = retain_commands(map, keep);
+                        if map.is_empty() {
+                            return None;
+                        }
+                        KeyBinding::Group {
+                            map,
+                            name: name.clone(),
+                        }
+                    }
+                    binding => binding.clone(),
+                };
+                Some((key.clone(), binding))
+            })
+            .collect(),
+    )
 }
 
 pub struct KeybindEventStream<'e, T: Command, W: FallibleWidget> {
@@ -118,7 +177,13 @@ pub struct KeybindEventStream<'e, T: Command, W: FallibleWidget> {
     help_prefixes: &'e [String],
     top: BindingMap<T>,
     next_maps: Vec<BindingMap<T>>,
+    /// keys of the partially entered sequence
+    pending_keys: Vec<Key>,
+    /// name of the group the last pending key selected
+    pending_group: String,
+    timeout: Option<Pin<Box<Sleep>>>,
     text_input: bool,
+    mouse_input: bool,
     current_view: usize,
     minor: Vec<BindingMap<T>>,
     span: Span,
@@ -138,7 +203,11 @@ impl<'e, T: Command, W: FallibleWidget> KeybindEventStream<'e, T, W> {
             inner_widget,
             top: map,
             next_maps: Vec::with_capacity(0),
+            pending_keys: Vec::new(),
+            pending_group: String::new(),
+            timeout: None,
             text_input: false,
+            mouse_input: false,
             current_view: 0,
             minor: Vec::with_capacity(0),
             span,
@@ -160,7 +229,11 @@ impl<'e, T: Command, W: FallibleWidget> KeybindEventStream<'e, T, W> {
             inner_widget,
             top: map,
             next_maps: Vec::with_capacity(0),
+            pending_keys: Vec::new(),
+            pending_group: String::new(),
+            timeout: None,
             text_input: false,
+            mouse_input: false,
             current_view: 0,
             minor,
             span,
@@ -172,6 +245,10 @@ impl<'e, T: Command, W: FallibleWidget> KeybindEventStream<'e, T, W> {
         self.text_input = text_input;
     }
 
+    pub fn set_mouse_input(&mut self, mouse_input: bool) {
+        self.mouse_input = mouse_input;
+    }
+
     pub fn get_minor(&self) -> &Vec<BindingMap<T>> {
         &self.minor
     }
@@ -179,6 +256,15 @@ impl<'e, T: Command, W: FallibleWidget> KeybindEventStream<'e, T, W> {
     pub fn get_minor_mut(&mut self) -> &mut Vec<BindingMap<T>> {
         &mut self.minor
     }
+
+    /// replaces the top map, discarding partially entered key sequences
+    pub fn set_map(&mut self, map: BindingMap<T>) {
+        self.span.in_scope(|| debug!(?map, "replacing keybind map"));
+        self.top = map;
+        self.next_maps.clear();
+        self.pending_keys.clear();
+        self.timeout = None;
+    }
     pub fn get_inner(&mut self) -> &mut W {
         self.inner_widget
     }
@@ -154,6 +154,10 @@ fn parse_key_code(mut name: &str) -> Option<Key> {
         "back-tab" => KeyCode::BackTab,
         "delete" => KeyCode::Delete,
         "insert" => KeyCode::Insert,
+        "page-up" => KeyCode::PageUp,
+        "page-down" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
         "esc" => KeyCode::Esc,
         code => {
             let mut chars = code.chars();
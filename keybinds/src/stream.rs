@@ -3,6 +3,7 @@ use std::task::Poll;
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use futures_util::{Stream, StreamExt, stream::FusedStream};
 use ratatui_fallible_widget::FallibleWidget;
+use tokio::time::sleep;
 use tracing::{debug, warn};
 
 use crate::Key;
@@ -33,6 +34,13 @@ impl<T: Command, W: FallibleWidget> Stream for KeybindEventStream<'_, T, W> {
             Poll::Ready(None)
         } else {
             let event = 'outer: loop {
+                if let Some(timeout) = &mut this.timeout
+                    && timeout.as_mut().poll(cx).is_ready()
+                {
+                    debug!("key sequence timed out");
+                    this.next_maps = Vec::new();
+                    break Some(Ok(KeybindEvent::Render));
+                }
                 let event = std::task::ready!(this.keybind_events.events.poll_next_unpin(cx));
                 debug!(?event, "received event from terminal");
                 match event {
@@ -84,19 +92,27 @@ impl<T: Command, W: FallibleWidget> Stream for KeybindEventStream<'_, T, W> {
                             debug!("keyboard press in text field");
                             break Some(Ok(KeybindEvent::Text(Text::Char(c))));
                         }
+                        let key = Key {
+                            inner: code,
+                            control: modifiers.contains(KeyModifiers::CONTROL),
+                            alt: modifiers.contains(KeyModifiers::ALT),
+                        };
+                        if this.next_maps.is_empty()
+                            && let Some(name) = this.keybind_events.global.get(&key)
+                        {
+                            debug!(name, "found matching global command");
+                            break Some(Ok(KeybindEvent::Global(name)));
+                        }
                         let current_map = std::mem::take(&mut this.next_maps);
                         let (top, minor) = (&this.top, &this.minor);
                         debug!(?current_map, "matching on active keymaps");
+                        let mut group = None;
 
                         for c in if_non_empty(current_map.as_ref())
                             .map(|v| either::Right(v.iter()))
                             .unwrap_or_else(|| either::Left(std::iter::once(top).chain(minor)))
                         {
-                            match c.get(&Key {
-                                inner: code,
-                                control: modifiers.contains(KeyModifiers::CONTROL),
-                                alt: modifiers.contains(KeyModifiers::ALT),
-                            }) {
+                            match c.get(&key) {
                                 Some(KeyBinding::Command(c)) => {
                                     debug!("found matching command");
                                     this.next_maps = Vec::new();
@@ -105,6 +121,7 @@ impl<T: Command, W: FallibleWidget> Stream for KeybindEventStream<'_, T, W> {
                                 Some(KeyBinding::Group { map, name }) => {
                                     debug!(name, "found matching group");
                                     this.next_maps.push(map.clone());
+                                    group.get_or_insert(name);
                                 }
                                 Some(KeyBinding::Invalid(name)) => {
                                     warn!("'{name}' is an invalid command");
@@ -117,6 +134,14 @@ impl<T: Command, W: FallibleWidget> Stream for KeybindEventStream<'_, T, W> {
                                 None => {}
                             }
                         }
+                        if let Some(group) = group {
+                            this.pending_keys.push(key);
+                            this.pending_group = group.clone();
+                            this.timeout = this
+                                .keybind_events
+                                .sequence_timeout
+                                .map(|timeout| Box::pin(sleep(timeout)));
+                        }
                         if !(current_map.is_empty() && this.next_maps.is_empty()) {
                             debug!("should render");
                             break Some(Ok(KeybindEvent::Render));
@@ -130,10 +155,19 @@ impl<T: Command, W: FallibleWidget> Stream for KeybindEventStream<'_, T, W> {
                             debug!("currently no active text input");
                         }
                     }
+                    Some(Ok(Event::Mouse(mouse))) => {
+                        if this.mouse_input {
+                            break Some(Ok(KeybindEvent::Mouse(mouse)));
+                        }
+                    }
                     Some(Ok(Event::Resize(_, _))) => break Some(Ok(KeybindEvent::Render)),
                     _ => {}
                 }
             };
+            if this.next_maps.is_empty() {
+                this.pending_keys.clear();
+                this.timeout = None;
+            }
             debug!(?event, "emitting event");
             drop(e);
             Poll::Ready(event)
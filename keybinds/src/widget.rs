@@ -94,6 +94,24 @@ impl<T: Command, W: FallibleWidget> FallibleWidget for KeybindEventStream<'_, T,
             }
             let main = block.inner(area);
             block.render(area, buf);
+            if !self.pending_keys.is_empty() {
+                let keys = self.pending_keys.iter().join(" ");
+                Line::from(vec![
+                    Span::raw(keys),
+                    Span::raw(" "),
+                    Span::styled(self.pending_group.as_str(), Color::Blue),
+                    Span::raw(" …"),
+                ])
+                .render(
+                    Rect {
+                        x: main.x,
+                        y: area.y,
+                        width: main.width,
+                        height: 1,
+                    },
+                    buf,
+                );
+            }
             let items_per_screen = width as usize * usable_height;
             let items = self
                 .next_maps
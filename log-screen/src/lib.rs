@@ -1,7 +1,11 @@
 use std::{pin::Pin, time::Duration};
 
 use color_eyre::Result;
-use jellyhaj_core::{context::TuiContext, keybinds::LoggerCommand, state::Navigation};
+use jellyhaj_core::{
+    context::TuiContext,
+    keybinds::LoggerCommand,
+    state::{Navigation, NextScreen, global_screen},
+};
 use keybinds::{KeybindEvent, KeybindEventStream, StreamExt};
 use ratatui::{
     style::{Color, Style},
@@ -62,6 +66,16 @@ pub async fn show_tui(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
                 Some(Err(e)) => break  Err(e),
                 Some(Ok(KeybindEvent::Render)) => continue,
                 Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+                Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                    Some(next) => {
+                        break Ok(Navigation::Push {
+                            current: NextScreen::Logs,
+                            next,
+                        });
+                    }
+                    None => continue,
+                },
                 Some(Ok(KeybindEvent::Command(c))) => match c{
                     LoggerCommand::Space => TuiWidgetEvent::SpaceKey,
                     LoggerCommand::TargetUp => TuiWidgetEvent::UpKey,
@@ -75,7 +89,8 @@ pub async fn show_tui(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
                     LoggerCommand::MessagesUp => TuiWidgetEvent::PrevPageKey,
                     LoggerCommand::MessagesDown => TuiWidgetEvent::NextPageKey,
                     LoggerCommand::Escape => TuiWidgetEvent::EscapeKey,
-                    LoggerCommand::Quit => break Ok(Navigation::PopContext),
+                    LoggerCommand::Quit => break Ok(Navigation::Quit),
+                    LoggerCommand::Back => break Ok(Navigation::PopContext),
                 }
             }
         }
@@ -4,47 +4,83 @@ use std::{
     io::Write,
     os::unix::fs::OpenOptionsExt,
     pin::pin,
+    time::Duration,
 };
 
 use color_eyre::eyre::{Context, OptionExt, Report, Result, eyre};
 use futures_util::StreamExt;
-use jellyfin::{Auth, ClientInfo, JellyfinClient, NoAuth};
+use jellyfin::{
+    Auth, ClientInfo, JellyfinClient, NoAuth,
+    auth::{QUICK_CONNECT_USER, QuickConnectState},
+};
 use jellyhaj_core::{
-    config::Config,
-    keybinds::{Keybinds, LoadingCommand, LoginInfoCommand},
+    config::{Config, Theme},
+    keybinds::{Keybinds, LoadingCommand, LoginInfoCommand, ServerSelectionCommand},
 };
 use keybinds::{KeybindEvent, KeybindEventStream, KeybindEvents};
 use ratatui::{
     DefaultTerminal,
     layout::{Constraint, Layout},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::Text,
-    widgets::{Block, BorderType, Padding, Paragraph, Widget, Wrap},
+    widgets::{
+        Block, BorderType, List, ListState, Padding, Paragraph, StatefulWidget, Widget, Wrap,
+    },
 };
 use ratatui_fallible_widget::{FallibleWidget, TermExt};
 use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
 use tracing::{error, info, instrument};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 struct LoginInfo {
+    /// friendly name shown instead of the server url
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
     server_url: String,
     username: String,
     password: String,
     password_cmd: Option<Vec<String>>,
+    /// token obtained through Quick Connect
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    access_token: Option<String>,
+    /// API key used instead of the password, `username` selects the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    api_key: Option<String>,
+}
+
+/// The login file holds either a single server or a list of them
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum LoginFile {
+    Servers { servers: Vec<LoginInfo> },
+    Single(LoginInfo),
 }
 
 #[derive(Debug, Clone, Copy)]
 enum LoginSelection {
+    DisplayName,
     Server,
     Username,
     Password,
+    ApiKey,
     Retry,
+    QuickConnect,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LoginMode {
+    Password,
+    QuickConnect,
+    Token,
+    ApiKey,
 }
 
 struct LoginWidget<'s> {
     info: &'s mut LoginInfo,
     selection: LoginSelection,
     error: String,
+    theme: Theme,
 }
 
 impl FallibleWidget for LoginWidget<'_> {
@@ -54,14 +90,25 @@ impl FallibleWidget for LoginWidget<'_> {
         buf: &mut ratatui::prelude::Buffer,
     ) -> Result<()> {
         let error = Paragraph::new(self.error.to_string())
-            .block(Block::bordered().border_style(Color::Red))
+            .block(Block::bordered().border_style(self.theme.error))
             .wrap(Wrap::default());
-        let normal_block = Block::bordered();
-        let current_block = Block::bordered().border_type(ratatui::widgets::BorderType::Double);
+        let normal_block = Block::bordered().border_style(self.theme.border);
+        let current_block = Block::bordered()
+            .border_type(ratatui::widgets::BorderType::Double)
+            .border_style(self.theme.active_border);
         let outer_block = Block::bordered()
             .border_type(BorderType::Rounded)
             .padding(Padding::uniform(4))
             .title("Enter Jellyfin Server / Login Information");
+        let display_name = Paragraph::new(self.info.display_name.as_deref().unwrap_or_default())
+            .block(
+                if let LoginSelection::DisplayName = self.selection {
+                    current_block.clone()
+                } else {
+                    normal_block.clone()
+                }
+                .title("Name (optional)"),
+            );
         let server = Paragraph::new(self.info.server_url.as_str()).block(
             if let LoginSelection::Server = self.selection {
                 current_block.clone()
@@ -96,15 +143,49 @@ impl FallibleWidget for LoginWidget<'_> {
             }
             .title("Password"),
         );
+        let api_key = Paragraph::new(if self.info.api_key.is_some() {
+            "<hidden>"
+        } else {
+            ""
+        })
+        .block(
+            if let LoginSelection::ApiKey = self.selection {
+                current_block.clone()
+            } else {
+                normal_block.clone()
+            }
+            .title("API key (optional, replaces the password)"),
+        );
         let outer_area = area;
         let button =
             Paragraph::new("Connect").block(if let LoginSelection::Retry = self.selection {
                 current_block.clone()
             } else {
-                Block::bordered().border_type(BorderType::Thick)
+                Block::bordered()
+                    .border_type(BorderType::Thick)
+                    .border_style(self.theme.border)
             });
+        let quick_connect = Paragraph::new("Quick Connect").block(
+            if let LoginSelection::QuickConnect = self.selection {
+                current_block.clone()
+            } else {
+                Block::bordered()
+                    .border_type(BorderType::Thick)
+                    .border_style(self.theme.border)
+            },
+        );
 
-        let [layout_s, layout_u, layout_p, layout_b, layout_e] = Layout::vertical([
+        let [
+            layout_n,
+            layout_s,
+            layout_u,
+            layout_p,
+            layout_k,
+            layout_b,
+            layout_e,
+        ] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
             Constraint::Length(3),
@@ -113,17 +194,23 @@ impl FallibleWidget for LoginWidget<'_> {
         ])
         .vertical_margin(1)
         .areas(outer_block.inner(outer_area));
+        let [layout_b, layout_q] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(layout_b);
         outer_block.render(outer_area, buf);
+        display_name.render(layout_n, buf);
         server.render(layout_s, buf);
         username.render(layout_u, buf);
         password.render(layout_p, buf);
+        api_key.render(layout_k, buf);
         button.render(layout_b, buf);
+        quick_connect.render(layout_q, buf);
         error.render(layout_e, buf);
         Ok(())
     }
 }
 
 #[instrument(skip_all)]
+#[allow(clippy::too_many_arguments)]
 async fn get_login_info(
     term: &mut DefaultTerminal,
     info: &mut LoginInfo,
@@ -132,7 +219,8 @@ async fn get_login_info(
     events: &mut KeybindEvents,
     keybinds: &Keybinds,
     help_prefixes: &[String],
-) -> Result<bool> {
+    theme: Theme,
+) -> Result<Option<LoginMode>> {
     let selection = if info.server_url.is_empty() {
         LoginSelection::Server
     } else {
@@ -143,6 +231,7 @@ async fn get_login_info(
         info,
         selection,
         error,
+        theme,
     };
     let mut events = KeybindEventStream::new(
         events,
@@ -153,9 +242,22 @@ async fn get_login_info(
     loop {
         term.draw_fallible(&mut events)?;
         let selection = events.get_inner().selection;
-        events.set_text_input(!matches!(selection, LoginSelection::Retry));
+        events.set_text_input(!matches!(
+            selection,
+            LoginSelection::Retry | LoginSelection::QuickConnect
+        ));
         match events.next().await {
             Some(Ok(KeybindEvent::Command(LoginInfoCommand::Delete))) => match selection {
+                LoginSelection::DisplayName => {
+                    let info = &mut events.get_inner().info;
+                    if let Some(name) = &mut info.display_name {
+                        name.pop();
+                        if name.is_empty() {
+                            info.display_name = None;
+                        }
+                    }
+                    *changed = true;
+                }
                 LoginSelection::Server => {
                     events.get_inner().info.server_url.pop();
                     *changed = true;
@@ -168,33 +270,63 @@ async fn get_login_info(
                     events.get_inner().info.password.pop();
                     *changed = true;
                 }
-                LoginSelection::Retry => {}
+                LoginSelection::ApiKey => {
+                    let info = &mut events.get_inner().info;
+                    if let Some(key) = &mut info.api_key {
+                        key.pop();
+                        if key.is_empty() {
+                            info.api_key = None;
+                        }
+                    }
+                    *changed = true;
+                }
+                LoginSelection::Retry | LoginSelection::QuickConnect => {}
             },
-            Some(Ok(KeybindEvent::Command(LoginInfoCommand::Submit))) => break Ok(true),
+            Some(Ok(KeybindEvent::Command(LoginInfoCommand::Submit))) => {
+                break Ok(Some(if let LoginSelection::QuickConnect = selection {
+                    LoginMode::QuickConnect
+                } else if events.get_inner().info.api_key.is_some() {
+                    LoginMode::ApiKey
+                } else {
+                    LoginMode::Password
+                }));
+            }
             Some(Ok(KeybindEvent::Command(LoginInfoCommand::Prev))) => {
                 events.get_inner().selection = match selection {
-                    LoginSelection::Server => LoginSelection::Retry,
+                    LoginSelection::DisplayName => LoginSelection::QuickConnect,
+                    LoginSelection::Server => LoginSelection::DisplayName,
                     LoginSelection::Username => LoginSelection::Server,
                     LoginSelection::Password => LoginSelection::Username,
-                    LoginSelection::Retry => LoginSelection::Password,
+                    LoginSelection::ApiKey => LoginSelection::Password,
+                    LoginSelection::Retry => LoginSelection::ApiKey,
+                    LoginSelection::QuickConnect => LoginSelection::Retry,
                 }
             }
             Some(Ok(KeybindEvent::Command(LoginInfoCommand::Next))) => {
                 events.get_inner().selection = match selection {
+                    LoginSelection::DisplayName => LoginSelection::Server,
                     LoginSelection::Server => LoginSelection::Username,
                     LoginSelection::Username => LoginSelection::Password,
-                    LoginSelection::Password => LoginSelection::Retry,
-                    LoginSelection::Retry => LoginSelection::Server,
+                    LoginSelection::Password => LoginSelection::ApiKey,
+                    LoginSelection::ApiKey => LoginSelection::Retry,
+                    LoginSelection::Retry => LoginSelection::QuickConnect,
+                    LoginSelection::QuickConnect => LoginSelection::DisplayName,
                 }
             }
-            Some(Ok(KeybindEvent::Command(LoginInfoCommand::Quit))) => break Ok(false),
+            Some(Ok(KeybindEvent::Command(LoginInfoCommand::Quit))) => break Ok(None),
             Some(Ok(KeybindEvent::Text(text))) => {
                 let dest = match selection {
+                    LoginSelection::DisplayName => {
+                        events.get_inner().info.display_name.get_or_insert_default()
+                    }
                     LoginSelection::Server => &mut events.get_inner().info.server_url,
                     LoginSelection::Username => &mut events.get_inner().info.username,
                     LoginSelection::Password => &mut events.get_inner().info.password,
-                    LoginSelection::Retry => {
-                        unreachable!("selecting reply should disable text input")
+                    LoginSelection::ApiKey => {
+                        events.get_inner().info.api_key.get_or_insert_default()
+                    }
+                    LoginSelection::Retry | LoginSelection::QuickConnect => {
+                        unreachable!("selecting a button should disable text input")
                     }
                 };
                 match text {
@@ -203,43 +335,150 @@ async fn get_login_info(
                 }
                 *changed = true;
             }
-            Some(Ok(KeybindEvent::Render)) => {}
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Render | KeybindEvent::Global(_))) => {}
+            Some(Err(e)) => break Err(e).context("receiving terminal events"),
+            None => break Ok(None),
+        }
+    }
+}
+
+struct ServerSelection<'s> {
+    servers: &'s [LoginInfo],
+    state: ListState,
+}
+
+impl FallibleWidget for ServerSelection<'_> {
+    fn render_fallible(
+        &mut self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) -> Result<()> {
+        let list = List::new(
+            self.servers
+                .iter()
+                .map(LoginInfo::server_name)
+                .chain(["Add new server"]),
+        )
+        .highlight_style(Modifier::REVERSED)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .padding(Padding::uniform(1))
+                .title("Select Jellyfin Server"),
+        );
+        StatefulWidget::render(list, area, buf, &mut self.state);
+        Ok(())
+    }
+}
+
+/// index of the selected server, `servers.len()` adds a new one
+#[instrument(skip_all)]
+async fn select_server(
+    term: &mut DefaultTerminal,
+    servers: &[LoginInfo],
+    events: &mut KeybindEvents,
+    keybinds: &Keybinds,
+    help_prefixes: &[String],
+) -> Result<Option<usize>> {
+    let mut widget = ServerSelection {
+        servers,
+        state: ListState::default().with_selected(Some(0)),
+    };
+    let mut events = KeybindEventStream::new(
+        events,
+        &mut widget,
+        keybinds.server_selection.clone(),
+        help_prefixes,
+    );
+    loop {
+        term.draw_fallible(&mut events)?;
+        match events.next().await {
+            Some(Ok(KeybindEvent::Command(ServerSelectionCommand::Up))) => {
+                events.get_inner().state.select_previous();
+            }
+            Some(Ok(KeybindEvent::Command(ServerSelectionCommand::Down))) => {
+                events.get_inner().state.select_next();
+            }
+            Some(Ok(KeybindEvent::Command(ServerSelectionCommand::Select))) => {
+                if let Some(index) = events.get_inner().state.selected() {
+                    break Ok(Some(index.min(servers.len())));
+                }
+            }
+            Some(Ok(KeybindEvent::Command(ServerSelectionCommand::Quit))) | None => {
+                break Ok(None);
+            }
+            Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Render | KeybindEvent::Global(_))) => {}
             Some(Err(e)) => break Err(e).context("receiving terminal events"),
-            None => break Ok(false),
         }
     }
 }
 
+/// With `after` the server picker is skipped and the saved server following that index is used,
+/// the index of the server is returned with the client
 #[instrument(skip_all)]
 pub async fn login(
     term: &mut DefaultTerminal,
     config: &Config,
     events: &mut KeybindEvents,
-) -> Result<Option<JellyfinClient<Auth>>> {
-    let mut login_info: LoginInfo;
+    after: Option<usize>,
+) -> Result<Option<(JellyfinClient<Auth>, usize)>> {
+    let mut servers: Vec<LoginInfo>;
     let mut error: Option<Report>;
-    let mut connect_msg = Paragraph::new("Connecting to Server")
-        .centered()
-        .block(Block::bordered());
+    let mut multiple = false;
     match std::fs::read_to_string(&config.login_file)
         .context("reading login info file")
-        .and_then(|config| toml::from_str::<LoginInfo>(&config).context("parsing login info"))
+        .and_then(|config| toml::from_str::<LoginFile>(&config).context("parsing login info"))
     {
-        Ok(info) => {
-            login_info = info;
+        Ok(LoginFile::Single(info)) => {
+            servers = vec![info];
+            error = None;
+        }
+        Ok(LoginFile::Servers { servers: saved }) => {
+            servers = saved;
+            multiple = true;
             error = None;
         }
         Err(e) => {
-            login_info = LoginInfo {
-                server_url: String::new(),
-                username: String::new(),
-                password: String::new(),
-                password_cmd: None,
-            };
+            servers = Vec::new();
             error = Some(e);
         }
     }
+    let index = if let Some(after) = after
+        && !servers.is_empty()
+    {
+        (after + 1) % servers.len()
+    } else if multiple {
+        match select_server(
+            term,
+            &servers,
+            events,
+            &config.keybinds,
+            &config.help_prefixes,
+        )
+        .await
+        .context("selecting server")?
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        }
+    } else {
+        0
+    };
+    if index == servers.len() {
+        servers.push(LoginInfo::default());
+    }
+    let mut login_info = std::mem::take(&mut servers[index]);
     let mut info_changed = false;
+    let mut mode = if login_info.api_key.is_some() {
+        LoginMode::ApiKey
+    } else if login_info.access_token.is_some() {
+        LoginMode::Token
+    } else {
+        LoginMode::Password
+    };
     let device_name: Cow<'static, str> = whoami::fallible::hostname()
         .ok()
         .map(|v| v.into())
@@ -247,7 +486,11 @@ pub async fn login(
     let client = 'connect: loop {
         if let Some(e) = error.take() {
             error!("Error logging in: {e:?}");
-            if !get_login_info(
+            if mode == LoginMode::Token {
+                login_info.access_token = None;
+                info_changed = true;
+            }
+            mode = match get_login_info(
                 term,
                 &mut login_info,
                 &mut info_changed,
@@ -255,12 +498,14 @@ pub async fn login(
                 events,
                 &config.keybinds,
                 &config.help_prefixes,
+                config.theme,
             )
             .await
             .context("getting login information")?
             {
-                return Ok(None);
-            }
+                Some(mode) => mode,
+                None => return Ok(None),
+            };
         }
         if login_info.server_url.is_empty() {
             error = Some(eyre!("Server URI is empty"));
@@ -274,19 +519,18 @@ pub async fn login(
                 version: "0.1".into(),
             },
             device_name.clone(),
+            config.max_connections,
         ) {
-            Ok(client) => client,
+            Ok(client) => client.with_timeout(config.request_timeout, config.request_retries),
             Err(e) => {
                 error = Some(e);
                 continue;
             }
         };
-        let mut auth_request = pin!(jellyfin_login(
-            client,
-            &login_info.username,
-            &login_info.password,
-            login_info.password_cmd.as_deref()
-        ));
+        let (code_send, mut code_recv) = oneshot::channel();
+        let mut code_pending = true;
+        let mut auth_request = pin!(authenticate(client, mode, &login_info, code_send));
+        let mut connect_msg = connecting_message(&login_info);
 
         let mut events = KeybindEventStream::new(
             events,
@@ -299,16 +543,28 @@ pub async fn login(
             tokio::select! {
                 event = events.next() => {
                     match event {
-                        Some(Ok(KeybindEvent::Command(LoadingCommand::Quit)))|None => return Ok(None),
+                        Some(Ok(KeybindEvent::Command(LoadingCommand::Quit | LoadingCommand::Back)))|None => return Ok(None),
                         Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
-                        Some(Ok(KeybindEvent::Render)) => continue,
+                        Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+                        Some(Ok(KeybindEvent::Render | KeybindEvent::Global(_))) => continue,
                         Some(Err(e)) => return Err(e).context("Error getting key events from terminal"),
                     }
                 }
+                code = &mut code_recv, if code_pending => {
+                    code_pending = false;
+                    if let Ok(code) = code {
+                        *events.get_inner() = Paragraph::new(format!(
+                            "Quick Connect code: {code}\n\nApprove this code under Quick Connect in the settings of another Jellyfin client"
+                        ))
+                        .centered()
+                        .wrap(Wrap::default())
+                        .block(Block::bordered());
+                    }
+                }
                 request = &mut auth_request => {
                     match request {
                         Ok(client) => break 'connect client,
-                        Err((_,e)) => {
+                        Err(e) => {
                             error = Some(e.wrap_err("logging in"));
                             break
                         },
@@ -317,6 +573,17 @@ pub async fn login(
             };
         }
     };
+    match mode {
+        LoginMode::QuickConnect => {
+            login_info.username = client.get_auth().user.name.clone();
+            login_info.access_token = Some(client.get_auth().access_token.clone());
+            info_changed = true;
+        }
+        LoginMode::Password | LoginMode::ApiKey if login_info.access_token.take().is_some() => {
+            info_changed = true
+        }
+        LoginMode::Password | LoginMode::ApiKey | LoginMode::Token => {}
+    }
     if info_changed {
         create_dir_all(
             config
@@ -333,13 +600,18 @@ pub async fn login(
             .open(&config.login_file)
             .context("opening login info")?
             .write_all(
-                toml::to_string_pretty(&login_info)
-                    .context("serializing login info")?
-                    .as_bytes(),
+                if multiple {
+                    servers[index] = login_info;
+                    toml::to_string_pretty(&LoginFile::Servers { servers })
+                } else {
+                    toml::to_string_pretty(&login_info)
+                }
+                .context("serializing login info")?
+                .as_bytes(),
             )
             .context("writing out new login info")?;
     }
-    Ok(Some(client))
+    Ok(Some((client, index)))
 }
 
 async fn get_password_from_cmd(cmd: &[String]) -> Result<String> {
@@ -369,6 +641,100 @@ async fn get_password_from_cmd(cmd: &[String]) -> Result<String> {
     }
 }
 
+impl LoginInfo {
+    fn server_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.server_url)
+    }
+}
+
+fn connecting_message(info: &LoginInfo) -> Paragraph<'static> {
+    Paragraph::new(format!("Connecting to {}", info.server_name()))
+        .centered()
+        .block(Block::bordered())
+}
+
+async fn authenticate(
+    client: JellyfinClient<NoAuth>,
+    mode: LoginMode,
+    info: &LoginInfo,
+    code: oneshot::Sender<String>,
+) -> Result<JellyfinClient<Auth>> {
+    match (mode, &info.access_token, &info.api_key) {
+        (LoginMode::Token, Some(token), _) => client
+            .auth_key(token.clone(), QUICK_CONNECT_USER)
+            .get_self()
+            .await
+            .map_err(|(_, e)| e.wrap_err("restoring Quick Connect session")),
+        (LoginMode::QuickConnect, _, _) => jellyfin_quick_connect(client, code).await,
+        (LoginMode::ApiKey, _, Some(key)) => {
+            jellyfin_api_key_login(client, key, &info.username).await
+        }
+        (LoginMode::Password | LoginMode::Token | LoginMode::ApiKey, _, _) => jellyfin_login(
+            client,
+            &info.username,
+            &info.password,
+            info.password_cmd.as_deref(),
+        )
+        .await
+        .map_err(|(_, e)| e),
+    }
+}
+
+async fn jellyfin_quick_connect(
+    client: JellyfinClient<NoAuth>,
+    code: oneshot::Sender<String>,
+) -> Result<JellyfinClient<Auth>> {
+    info!("connecting to server using quick connect");
+    if !client
+        .quick_connect_enabled()
+        .await
+        .context("checking if Quick Connect is enabled")?
+    {
+        return Err(eyre!("Quick Connect is disabled on this server"));
+    }
+    let QuickConnectState {
+        mut authenticated,
+        secret,
+        code: quick_connect_code,
+    } = client
+        .quick_connect_initiate()
+        .await
+        .context("initiating Quick Connect")?;
+    let _ = code.send(quick_connect_code);
+    while !authenticated {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        authenticated = client
+            .quick_connect_state(&secret)
+            .await
+            .context("polling Quick Connect state")?
+            .authenticated;
+    }
+    client
+        .auth_quick_connect(&secret)
+        .await
+        .map_err(|(_, e)| e.wrap_err("authenticating with Quick Connect"))
+}
+
+async fn jellyfin_api_key_login(
+    client: JellyfinClient<NoAuth>,
+    key: &str,
+    username: &str,
+) -> Result<JellyfinClient<Auth>> {
+    info!("connecting to server using an API key");
+    let client = client.auth_key(key.to_string(), username);
+    if username.is_empty() {
+        client
+            .get_self()
+            .await
+            .map_err(|(_, e)| e.wrap_err("authenticating with API key"))
+    } else {
+        client
+            .get_user_by_name(username)
+            .await
+            .map_err(|(_, e)| e.wrap_err("authenticating with API key"))
+    }
+}
+
 async fn jellyfin_login(
     client: JellyfinClient<NoAuth>,
     username: &str,
@@ -0,0 +1,6 @@
+-- Add migration script here
+create table player_settings(
+       id integer primary key check (id = 0),
+       volume integer not null,
+       speed real not null
+) strict;
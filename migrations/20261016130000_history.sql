@@ -0,0 +1,6 @@
+-- Add migration script here
+create table history(
+       item_id text primary key,
+       item text not null,
+       opened integer not null default (unixepoch())
+) strict;
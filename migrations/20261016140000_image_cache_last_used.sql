@@ -0,0 +1,3 @@
+-- time an image was last shown, the least recently used images are evicted first
+alter table image_cache add column last_used integer not null default 0;
+update image_cache set last_used = added;
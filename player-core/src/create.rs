@@ -1,5 +1,5 @@
 use std::{
-    ffi::CString,
+    ffi::{CStr, CString},
     path::Path,
     sync::{Arc, atomic::AtomicBool},
     time::Duration,
@@ -7,11 +7,11 @@ use std::{
 
 use color_eyre::{
     Result,
-    eyre::{Context, OptionExt, eyre},
+    eyre::{Context, eyre},
 };
 use jellyfin::{
     JellyfinClient,
-    items::{ItemType, MediaItem},
+    items::{ItemType, MediaItem, Video3DFormat},
 };
 use libmpv::{
     Mpv, MpvProfile,
@@ -27,24 +27,38 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, instrument};
 
 use crate::{
-    OwnedPlayerHandle, PlayerHandle, PlaylistItem, PlaylistItemIdGen, mpv_stream::MpvStream,
-    poll::PollState,
+    MpvCacheOptions, OwnedPlayerHandle, PlayerHandle, PlaylistItem, PlaylistItemIdGen,
+    mpv_stream::MpvStream, poll::PollState,
 };
 
 impl OwnedPlayerHandle {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         jellyfin: JellyfinClient,
         hwdec: &str,
+        cache: &MpvCacheOptions,
         profile: MpvProfile,
         log_level: &str,
-        mpv_config_file: Option<&Path>,
+        mpv_config_files: &[&Path],
         minimized: bool,
+        preferred_audio_lang: Option<&str>,
+        preferred_subtitle_lang: Option<&str>,
         spawn: &Spawner,
     ) -> Result<Self> {
-        let mpv = MpvStream::new(&jellyfin, hwdec, profile, log_level, minimized)?;
-        if let Some(mpv_config_file) = mpv_config_file {
-            mpv.load_config(mpv_config_file)
-                .context("loading extra mpv config file")?
+        let mpv = MpvStream::new(
+            &jellyfin,
+            hwdec,
+            cache,
+            profile,
+            log_level,
+            minimized,
+            preferred_audio_lang,
+            preferred_subtitle_lang,
+        )?;
+        // later files override the options of earlier ones
+        for file in mpv_config_files {
+            mpv.load_config(file)
+                .with_context(|| format!("loading mpv config file {}", file.display()))?
         }
         let mut position_send_timer = interval(Duration::from_secs(1));
         position_send_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
@@ -72,6 +86,15 @@ impl OwnedPlayerHandle {
                 playlist_id_gen: PlaylistItemIdGen::default(),
                 minimized,
                 seeked: false,
+                duration: 0.0,
+                subtitle_tracks: Vec::new(),
+                audio_tracks: Vec::new(),
+                chapters: Vec::new(),
+                chapter: -1,
+                loop_file: false,
+                loop_playlist: false,
+                unshuffled: None,
+                hwdec: String::new(),
                 send_events,
             }
             .instrument(),
@@ -94,12 +117,7 @@ pub fn set_playlist(
     items: Vec<MediaItem>,
     index: usize,
 ) -> Result<Vec<Arc<PlaylistItem>>> {
-    let position = items[index]
-        .user_data
-        .as_ref()
-        .ok_or_eyre("user data missing")?
-        .playback_position_ticks
-        / 10000000;
+    let position = resume_position(&items[index]);
 
     for item in items[0..index].iter() {
         append(mpv, jellyfin, item)?
@@ -107,6 +125,17 @@ pub fn set_playlist(
     debug!("previous files added");
     let uri = jellyfin.get_video_uri(&items[index])?.to_string();
     debug!("adding {uri} to queue and play it");
+    let start = CString::new(position.to_string()).context("converting start to cstr")?;
+    let title = name(&items[index])?;
+    let mut keys = vec![
+        BorrowingCPtr::new(c"start"),
+        BorrowingCPtr::new(c"force-media-title"),
+    ];
+    let mut values = vec![start.to_node(), title.to_node()];
+    if let Some(filter) = stereo_filter(&items[index]) {
+        keys.push(BorrowingCPtr::new(c"vf"));
+        values.push(filter.to_node());
+    }
     mpv.command(&[
         c"loadfile".to_node(),
         CString::new(uri)
@@ -114,19 +143,7 @@ pub fn set_playlist(
             .to_node(),
         c"append-play".to_node(),
         0i64.to_node(),
-        MpvNodeMapRef::new(
-            &[
-                BorrowingCPtr::new(c"start"),
-                BorrowingCPtr::new(c"force-media-title"),
-            ],
-            &[
-                CString::new(position.to_string())
-                    .context("converting start to cstr")?
-                    .to_node(),
-                name(&items[index])?.to_node(),
-            ],
-        )
-        .to_node(),
+        MpvNodeMapRef::new(&keys, &values).to_node(),
     ])
     .context("added main item")?;
     debug!("main file added to playlist at index {index}");
@@ -149,6 +166,13 @@ pub fn set_playlist(
 fn append(mpv: &Mpv<EventContextAsync>, jellyfin: &JellyfinClient, item: &MediaItem) -> Result<()> {
     let uri = jellyfin.get_video_uri(item)?.to_string();
     debug!("adding {uri} to queue");
+    let title = name(item)?;
+    let mut keys = vec![BorrowingCPtr::new(c"force-media-title")];
+    let mut values = vec![title.to_node()];
+    if let Some(filter) = stereo_filter(item) {
+        keys.push(BorrowingCPtr::new(c"vf"));
+        values.push(filter.to_node());
+    }
     mpv.command(&[
         c"loadfile".to_node(),
         CString::new(uri)
@@ -156,22 +180,55 @@ fn append(mpv: &Mpv<EventContextAsync>, jellyfin: &JellyfinClient, item: &MediaI
             .to_node(),
         c"append".to_node(),
         0i64.to_node(),
-        MpvNodeMapRef::new(
-            &[BorrowingCPtr::new(c"force-media-title")],
-            &[name(item)?.to_node()],
-        )
-        .to_node(),
+        MpvNodeMapRef::new(&keys, &values).to_node(),
     ])?;
 
     Ok(())
 }
 
+/// minimum progress in seconds before playback resumes at the stored position
+const RESUME_MIN_SECONDS: u64 = 5;
+/// progress in percent after which an item starts from the beginning again
+const RESUME_MAX_PERCENT: u64 = 90;
+
+/// start position in seconds from the stored playback progress of the item
+pub fn resume_position(item: &MediaItem) -> u64 {
+    match &item.user_data {
+        Some(user_data) => resume_seconds(user_data.playback_position_ticks, item.run_time_ticks),
+        None => 0,
+    }
+}
+
+fn resume_seconds(ticks: u64, runtime: Option<u64>) -> u64 {
+    if ticks / 10_000_000 <= RESUME_MIN_SECONDS {
+        return 0;
+    }
+    if let Some(runtime) = runtime
+        && ticks >= runtime / 100 * RESUME_MAX_PERCENT
+    {
+        return 0;
+    }
+    ticks / 10_000_000
+}
+
+/// libavfilter stereo3d filter reducing frame packed 3d video to the left eye
+pub(crate) fn stereo_filter(item: &MediaItem) -> Option<&'static CStr> {
+    match item.video_3d_format? {
+        Video3DFormat::HalfSideBySide => Some(c"stereo3d=in=sbs2l:out=ml"),
+        Video3DFormat::FullSideBySide => Some(c"stereo3d=in=sbsl:out=ml"),
+        Video3DFormat::HalfTopAndBottom => Some(c"stereo3d=in=ab2l:out=ml"),
+        Video3DFormat::FullTopAndBottom => Some(c"stereo3d=in=abl:out=ml"),
+        Video3DFormat::Mvc | Video3DFormat::Unknown => None,
+    }
+}
+
 #[instrument(skip_all)]
 fn name(item: &MediaItem) -> Result<CString> {
     let name = match &item.item_type {
         ItemType::Music {
             album_id: _,
             album: _,
+            album_artist: _,
         } => item.name.clone(),
         ItemType::Movie => item.name.clone(),
         ItemType::Episode {
@@ -210,3 +267,29 @@ fn name(item: &MediaItem) -> Result<CString> {
     };
     Ok(CString::new(name)?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::resume_seconds;
+
+    const SECOND: u64 = 10_000_000;
+
+    #[test]
+    fn resume_after_minimum() {
+        assert_eq!(resume_seconds(0, Some(100 * SECOND)), 0);
+        assert_eq!(resume_seconds(5 * SECOND, Some(100 * SECOND)), 0);
+        assert_eq!(resume_seconds(6 * SECOND, Some(100 * SECOND)), 6);
+    }
+
+    #[test]
+    fn restart_when_almost_finished() {
+        assert_eq!(resume_seconds(89 * SECOND, Some(100 * SECOND)), 89);
+        assert_eq!(resume_seconds(90 * SECOND, Some(100 * SECOND)), 0);
+        assert_eq!(resume_seconds(99 * SECOND, Some(100 * SECOND)), 0);
+    }
+
+    #[test]
+    fn resume_without_runtime() {
+        assert_eq!(resume_seconds(3600 * SECOND, None), 3600);
+    }
+}
@@ -21,6 +21,8 @@ mod mpv_stream;
 mod poll;
 pub mod state;
 
+pub use create::resume_position;
+
 #[derive(Debug, Default)]
 pub struct PlaylistItemIdGen {
     id: u64,
@@ -55,6 +57,30 @@ impl FromStr for PlaylistItemId {
     }
 }
 
+/// Network cache options passed to mpv, `None` keeps the default of mpv
+#[derive(Debug, Clone, Default)]
+pub struct MpvCacheOptions {
+    /// "auto", "yes" or "no"
+    pub cache: Option<String>,
+    /// seconds of the stream mpv tries to read ahead
+    pub cache_secs: Option<u64>,
+    /// size of the forward demuxer cache, mpv accepts suffixes like "MiB"
+    pub demuxer_max_bytes: Option<String>,
+    /// open the next playlist item ahead of time
+    pub prefetch_playlist: bool,
+}
+
+/// What mpv repeats after the current item finished
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoopMode {
+    #[default]
+    None,
+    /// repeat the current item
+    File,
+    /// start the playlist again after the last item
+    Playlist,
+}
+
 #[derive(Debug)]
 pub enum Command {
     Pause(bool),
@@ -67,6 +93,21 @@ pub enum Command {
     SeekRelative(f64),
     Speed(f64),
     Volume(i64),
+    /// select the subtitle track with this id, `None` disables subtitles
+    SetSubtitle(Option<i64>),
+    /// select the audio track with this id
+    SetAudioTrack(i64),
+    /// load an external subtitle file if the item with this id is playing and does not have it yet
+    AddSubtitle {
+        item_id: String,
+        uri: String,
+        title: String,
+    },
+    /// seek to the start of the chapter with this index
+    SetChapter(i64),
+    SetLoop(LoopMode),
+    /// shuffle the items after the current one, `false` restores the previous order
+    Shuffle(bool),
     Play(PlaylistItemId),
     AddTrack {
         item: Box<MediaItem>,
@@ -74,6 +115,11 @@ pub enum Command {
         play: bool,
     },
     Remove(PlaylistItemId),
+    /// move the item behind `after`, to the start of the playlist if `after` is `None`
+    Move {
+        id: PlaylistItemId,
+        after: Option<PlaylistItemId>,
+    },
     ReplacePlaylist {
         items: Vec<MediaItem>,
         first: usize,
@@ -108,6 +154,19 @@ pub enum Events {
     Speed(f64),
     Fullscreen(bool),
     Volume(i64),
+    /// length of the current item in seconds
+    Duration(f64),
+    /// the current item failed to load, mpv continues with the next playlist item
+    PlaybackError(String),
+    SubtitleTracks(Vec<Track>),
+    AudioTracks(Vec<Track>),
+    Chapters(Vec<Chapter>),
+    /// index of the current chapter, -1 before the first one
+    Chapter(i64),
+    Loop(LoopMode),
+    Shuffle(bool),
+    /// hardware decoder used for the current item, "no" when decoding in software
+    Hwdec(String),
 }
 
 #[derive(Debug, Clone)]
@@ -120,6 +179,36 @@ pub struct PlayerState {
     pub speed: f64,
     pub fullscreen: bool,
     pub volume: i64,
+    /// length of the current item in seconds, 0 if unknown
+    pub duration: f64,
+    pub subtitle_tracks: Vec<Track>,
+    pub audio_tracks: Vec<Track>,
+    pub chapters: Vec<Chapter>,
+    /// index of the current chapter, -1 before the first one or without chapters
+    pub chapter: i64,
+    pub loop_mode: LoopMode,
+    pub shuffle: bool,
+    /// hardware decoder used for the current item, "no" when decoding in software and empty
+    /// until mpv reported it
+    pub hwdec: String,
+}
+
+/// Subtitle or audio track of the current item as reported by mpv
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    pub id: i64,
+    pub title: Option<String>,
+    pub lang: Option<String>,
+    pub external: bool,
+    pub selected: bool,
+}
+
+/// Chapter of the current item as reported by mpv
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub title: Option<String>,
+    /// start of the chapter in seconds
+    pub time: f64,
 }
 
 #[derive(Debug, Clone)]
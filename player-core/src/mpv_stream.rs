@@ -8,15 +8,17 @@ use color_eyre::eyre::{Context, Result};
 use futures_util::Stream;
 use jellyfin::JellyfinClient;
 use libmpv::{
-    Format, Mpv, MpvProfile,
+    Format, Mpv, MpvError, MpvProfile,
     events::{
         Event, EventContextAsync, EventContextAsyncExt, EventContextExt, PropertyData, mpv_event_id,
     },
-    node::{MpvNodeArrayRef, ToNode},
+    mpv_error,
+    node::{MpvNode, MpvNodeArrayRef, ToNode},
 };
 use tracing::{info, instrument, trace, warn};
 
 use super::log::log_message;
+use crate::{Chapter, MpvCacheOptions, Track};
 
 #[derive(Debug)]
 pub enum ObservedProperty {
@@ -28,6 +30,16 @@ pub enum ObservedProperty {
     PlaylistPos(i64),
     Volume(i64),
     Speed(f64),
+    Duration(f64),
+    Tracks {
+        subtitles: Vec<Track>,
+        audio: Vec<Track>,
+    },
+    Chapters(Vec<Chapter>),
+    Chapter(i64),
+    LoopFile(bool),
+    LoopPlaylist(bool),
+    Hwdec(String),
 }
 
 #[derive(Debug)]
@@ -40,6 +52,7 @@ pub enum MpvEvent {
     PropertyChanged(ObservedProperty),
     Command(ClientCommand),
     Seek,
+    PlaybackError(String),
 }
 
 pub struct MpvStream {
@@ -61,9 +74,13 @@ impl Stream for MpvStream {
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
         Poll::Ready(loop {
-            let event = match ready!(self.mpv.poll_wait_event(cx)).context("waiting for mpv events")
-            {
-                Err(e) => break Some(Err(e)),
+            let event = match ready!(self.mpv.poll_wait_event(cx)) {
+                Err(libmpv::Error::Raw(err)) if is_load_error(err) => {
+                    break Some(Ok(MpvEvent::PlaybackError(
+                        libmpv::Error::Raw(err).to_string(),
+                    )));
+                }
+                Err(e) => break Some(Err(e).context("waiting for mpv events")),
                 Ok(v) => v,
             };
             trace!(?event);
@@ -124,6 +141,42 @@ impl Stream for MpvStream {
                             volume,
                         ))));
                     }
+                    ("duration", PropertyData::Double(duration), 10) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Duration(
+                            duration,
+                        ))));
+                    }
+                    ("track-list", PropertyData::Node(tracks), 9) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Tracks {
+                            subtitles: tracks_of_type(tracks, "sub"),
+                            audio: tracks_of_type(tracks, "audio"),
+                        })));
+                    }
+                    ("chapter-list", PropertyData::Node(chapters), 11) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Chapters(
+                            chapters_of(chapters),
+                        ))));
+                    }
+                    ("chapter", PropertyData::Int64(chapter), 12) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Chapter(
+                            chapter,
+                        ))));
+                    }
+                    ("loop-file", PropertyData::Str(value), 13) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::LoopFile(
+                            value != "no",
+                        ))));
+                    }
+                    ("loop-playlist", PropertyData::Str(value), 14) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(
+                            ObservedProperty::LoopPlaylist(value != "no"),
+                        )));
+                    }
+                    ("hwdec-current", PropertyData::Str(value), 15) => {
+                        break Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Hwdec(
+                            value.to_string(),
+                        ))));
+                    }
                     (name, val, id) => {
                         warn!(name, ?val, id, "received unrequested property change event");
                     }
@@ -145,14 +198,85 @@ impl Stream for MpvStream {
     }
 }
 
+/// the entries of mpvs track-list property with type `ty`
+fn tracks_of_type(tracks: &MpvNode, ty: &str) -> Vec<Track> {
+    let Some(tracks) = tracks.as_ref().to_array() else {
+        warn!("track-list is not an array");
+        return Vec::new();
+    };
+    tracks
+        .into_iter()
+        .filter_map(|track| {
+            let mut matches = false;
+            let mut id = None;
+            let mut title = None;
+            let mut lang = None;
+            let mut external = false;
+            let mut selected = false;
+            for (key, value) in track.to_map()? {
+                match key.to_bytes() {
+                    b"type" => matches = value.to_str() == Some(ty),
+                    b"id" => id = value.to_i64(),
+                    b"title" => title = value.to_str().map(str::to_string),
+                    b"lang" => lang = value.to_str().map(str::to_string),
+                    b"external" => external = value.to_bool().unwrap_or_default(),
+                    b"selected" => selected = value.to_bool().unwrap_or_default(),
+                    _ => {}
+                }
+            }
+            matches.then_some(Track {
+                id: id?,
+                title,
+                lang,
+                external,
+                selected,
+            })
+        })
+        .collect()
+}
+
+/// the entries of mpvs chapter-list property
+fn chapters_of(chapters: &MpvNode) -> Vec<Chapter> {
+    let Some(chapters) = chapters.as_ref().to_array() else {
+        warn!("chapter-list is not an array");
+        return Vec::new();
+    };
+    chapters
+        .into_iter()
+        .filter_map(|chapter| {
+            let mut title = None;
+            let mut time = None;
+            for (key, value) in chapter.to_map()? {
+                match key.to_bytes() {
+                    b"title" => title = value.to_str().map(str::to_string),
+                    b"time" => time = value.to_f64(),
+                    _ => {}
+                }
+            }
+            Some(Chapter { title, time: time? })
+        })
+        .collect()
+}
+
+/// errors reported by the end-file event when an item could not be played
+fn is_load_error(err: MpvError) -> bool {
+    matches!(
+        err,
+        mpv_error::LoadingFailed | mpv_error::UnknownFormat | mpv_error::NothingToPlay
+    )
+}
+
 impl MpvStream {
     #[instrument(skip_all)]
     pub fn new(
         jellyfin: &JellyfinClient,
         hwdec: &str,
+        cache: &MpvCacheOptions,
         profile: MpvProfile,
         log_level: &str,
         minimized: bool,
+        preferred_audio_lang: Option<&str>,
+        preferred_subtitle_lang: Option<&str>,
     ) -> Result<Self> {
         let mpv = Mpv::with_initializer(|mpv| -> Result<()> {
             mpv.set_option(c"title", c"jellyhaj-player")?;
@@ -172,13 +296,59 @@ impl MpvStream {
             )?;
             mpv.set_option(c"input-default-bindings", true)?;
             mpv.set_option(c"input-vo-keyboard", true)?;
-            mpv.set_option(
+            // an unknown decoder is not fatal, mpv decodes in software then
+            if let Err(e) = mpv.set_option(
                 c"hwdec",
                 CString::new(hwdec)
                     .context("converting hwdec to cstr")?
                     .as_c_str(),
-            )?;
+            ) {
+                warn!("mpv does not accept hwdec {hwdec:?}, using software decoding: {e}");
+            }
             mpv.set_option(c"idle", c"yes")?;
+            // play albums without a gap between tracks, without prefetching a short gap remains
+            mpv.set_option(c"gapless-audio", c"yes")?;
+            if cache.prefetch_playlist {
+                mpv.set_option(c"prefetch-playlist", true)?;
+            }
+            if let Some(value) = &cache.cache {
+                mpv.set_option(
+                    c"cache",
+                    CString::new(value.as_str())
+                        .context("converting cache to cstr")?
+                        .as_c_str(),
+                )
+                .context("setting mpv cache")?;
+            }
+            if let Some(secs) = cache.cache_secs {
+                mpv.set_option(c"cache-secs", secs as f64)
+                    .context("setting mpv cache-secs")?;
+            }
+            if let Some(bytes) = &cache.demuxer_max_bytes {
+                mpv.set_option(
+                    c"demuxer-max-bytes",
+                    CString::new(bytes.as_str())
+                        .context("converting demuxer-max-bytes to cstr")?
+                        .as_c_str(),
+                )
+                .context("setting mpv demuxer-max-bytes")?;
+            }
+            if let Some(lang) = preferred_audio_lang {
+                mpv.set_option(
+                    c"alang",
+                    CString::new(lang)
+                        .context("converting audio language to cstr")?
+                        .as_c_str(),
+                )?;
+            }
+            if let Some(lang) = preferred_subtitle_lang {
+                mpv.set_option(
+                    c"slang",
+                    CString::new(lang)
+                        .context("converting subtitle language to cstr")?
+                        .as_c_str(),
+                )?;
+            }
             mpv.with_profile(profile)?;
             Ok(())
         })?
@@ -197,6 +367,13 @@ impl MpvStream {
         mpv.observe_property("playlist-pos", Format::Int64, 6)?;
         mpv.observe_property("speed", Format::Double, 7)?;
         mpv.observe_property("volume", Format::Int64, 8)?;
+        mpv.observe_property("track-list", Format::Node, 9)?;
+        mpv.observe_property("duration", Format::Double, 10)?;
+        mpv.observe_property("chapter-list", Format::Node, 11)?;
+        mpv.observe_property("chapter", Format::Int64, 12)?;
+        mpv.observe_property("loop-file", Format::String, 13)?;
+        mpv.observe_property("loop-playlist", Format::String, 14)?;
+        mpv.observe_property("hwdec-current", Format::String, 15)?;
         mpv.command(&[
             c"keybind".to_node(),
             c"q".to_node(),
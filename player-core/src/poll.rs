@@ -10,6 +10,7 @@ use jellyfin::{JellyfinClient, items::ItemType};
 use libmpv::Mpv;
 use libmpv::events::EventContextAsync;
 use libmpv::node::{BorrowingCPtr, MpvNode, MpvNodeMapRef, ToNode};
+use rand::seq::SliceRandom;
 use tokio::{
     sync::{broadcast, mpsc},
     time::Interval,
@@ -18,11 +19,11 @@ use tokio_util::sync::WaitForCancellationFutureOwned;
 use tracing::{Instrument, debug, error_span, instrument, instrument::Instrumented, warn};
 use tracing::{info, trace};
 
-use crate::create::set_playlist;
+use crate::create::{resume_position, set_playlist, stereo_filter};
 use crate::mpv_stream::ClientCommand;
 use crate::state::EventReceiver;
 use crate::{
-    Command, PlayerState, PlaylistItem,
+    Chapter, Command, LoopMode, PlayerState, PlaylistItem, Track,
     mpv_stream::{MpvEvent, MpvStream, ObservedProperty},
 };
 use crate::{Events, PlaylistItemId, PlaylistItemIdGen};
@@ -52,6 +53,16 @@ pin_project_lite::pin_project! {
         pub(crate) playlist: Arc<Vec<Arc<PlaylistItem>>>,
         pub(crate) playlist_id_gen: PlaylistItemIdGen,
         pub(crate) seeked: bool,
+        pub(crate) duration: f64,
+        pub(crate) subtitle_tracks: Vec<Track>,
+        pub(crate) audio_tracks: Vec<Track>,
+        pub(crate) chapters: Vec<Chapter>,
+        pub(crate) chapter: i64,
+        pub(crate) loop_file: bool,
+        pub(crate) loop_playlist: bool,
+        /// order of the playlist before it was shuffled, `None` if it is not shuffled
+        pub(crate) unshuffled: Option<Vec<PlaylistItemId>>,
+        pub(crate) hwdec: String,
         pub(crate) send_events: broadcast::Sender<Events>,
     }
 }
@@ -222,6 +233,11 @@ impl Future for PollState {
                                 .trace_error();
                         }
                         Some(Command::ReplacePlaylist { items, first }) => {
+                            if this.unshuffled.take().is_some() {
+                                this.send_events
+                                    .send(Events::Shuffle(false))
+                                    .trace_send_error();
+                            }
                             replace_playlist(
                                 &this.mpv,
                                 this.jellyfin,
@@ -244,6 +260,17 @@ impl Future for PollState {
                             )
                             .trace_error();
                         }
+                        Some(Command::Move { id, after }) => {
+                            move_playlist_item(
+                                this.playlist,
+                                &this.mpv,
+                                id,
+                                after,
+                                this.send_events,
+                                this.index,
+                            )
+                            .trace_error();
+                        }
                         Some(Command::TogglePause) => {
                             this.mpv
                                 .set_pause(!*this.paused)
@@ -255,6 +282,65 @@ impl Future for PollState {
                             .set_property(c"volume", volume)
                             .context("setting volume")
                             .trace_error(),
+                        Some(Command::SetSubtitle(Some(id))) => this
+                            .mpv
+                            .set_property(c"sid", id)
+                            .context("selecting subtitle track")
+                            .trace_error(),
+                        Some(Command::SetSubtitle(None)) => this
+                            .mpv
+                            .set_property(c"sid", c"no")
+                            .context("disabling subtitles")
+                            .trace_error(),
+                        Some(Command::AddSubtitle {
+                            item_id,
+                            uri,
+                            title,
+                        }) => {
+                            let playing = (*this.index)
+                                .and_then(|index| this.playlist.get(index))
+                                .is_some_and(|current| current.item.id == item_id);
+                            let loaded = this.subtitle_tracks.iter().any(|track| {
+                                track.external && track.title.as_deref() == Some(title.as_str())
+                            });
+                            if playing && !loaded {
+                                add_subtitle(&this.mpv, uri, title).trace_error();
+                            }
+                        }
+                        Some(Command::SetAudioTrack(id)) => this
+                            .mpv
+                            .set_property(c"aid", id)
+                            .context("selecting audio track")
+                            .trace_error(),
+                        Some(Command::SetChapter(chapter)) => this
+                            .mpv
+                            .set_property(c"chapter", chapter)
+                            .context("seeking to chapter")
+                            .trace_error(),
+                        Some(Command::SetLoop(mode)) => {
+                            let (file, playlist) = match mode {
+                                LoopMode::None => (c"no", c"no"),
+                                LoopMode::File => (c"inf", c"no"),
+                                LoopMode::Playlist => (c"no", c"inf"),
+                            };
+                            this.mpv
+                                .set_property(c"loop-file", file)
+                                .and_then(|_| this.mpv.set_property(c"loop-playlist", playlist))
+                                .context("setting loop mode")
+                                .trace_error();
+                        }
+                        Some(Command::Shuffle(shuffle)) => {
+                            shuffle_playlist(
+                                this.playlist,
+                                &this.mpv,
+                                shuffle,
+                                this.unshuffled,
+                                this.send_events,
+                                this.index,
+                            )
+                            .context("shuffling playlist")
+                            .trace_error();
+                        }
                         Some(Command::GetEventReceiver(sender)) => {
                             sender
                                 .send(EventReceiver {
@@ -267,6 +353,14 @@ impl Future for PollState {
                                         speed: *this.speed,
                                         fullscreen: *this.fullscreen,
                                         volume: *this.volume,
+                                        duration: *this.duration,
+                                        subtitle_tracks: this.subtitle_tracks.clone(),
+                                        audio_tracks: this.audio_tracks.clone(),
+                                        chapters: this.chapters.clone(),
+                                        chapter: *this.chapter,
+                                        loop_mode: loop_mode(*this.loop_file, *this.loop_playlist),
+                                        shuffle: this.unshuffled.is_some(),
+                                        hwdec: this.hwdec.clone(),
                                     },
                                     receive: this.send_events.subscribe(),
                                 })
@@ -354,6 +448,76 @@ impl Future for PollState {
                         .send(Events::Volume(volume))
                         .trace_send_error();
                 }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Duration(duration)))) => {
+                    *this.duration = duration;
+                    this.send_events
+                        .send(Events::Duration(duration))
+                        .trace_send_error();
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Tracks {
+                    subtitles,
+                    audio,
+                }))) => {
+                    if *this.subtitle_tracks != subtitles {
+                        *this.subtitle_tracks = subtitles.clone();
+                        this.send_events
+                            .send(Events::SubtitleTracks(subtitles))
+                            .trace_send_error();
+                    }
+                    if *this.audio_tracks != audio {
+                        *this.audio_tracks = audio.clone();
+                        this.send_events
+                            .send(Events::AudioTracks(audio))
+                            .trace_send_error();
+                    }
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Chapters(chapters)))) => {
+                    if *this.chapters != chapters {
+                        *this.chapters = chapters.clone();
+                        this.send_events
+                            .send(Events::Chapters(chapters))
+                            .trace_send_error();
+                    }
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Chapter(chapter)))) => {
+                    *this.chapter = chapter;
+                    this.send_events
+                        .send(Events::Chapter(chapter))
+                        .trace_send_error();
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::LoopFile(looping)))) => {
+                    *this.loop_file = looping;
+                    this.send_events
+                        .send(Events::Loop(loop_mode(
+                            *this.loop_file,
+                            *this.loop_playlist,
+                        )))
+                        .trace_send_error();
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::LoopPlaylist(looping)))) => {
+                    *this.loop_playlist = looping;
+                    this.send_events
+                        .send(Events::Loop(loop_mode(
+                            *this.loop_file,
+                            *this.loop_playlist,
+                        )))
+                        .trace_send_error();
+                }
+                Some(Ok(MpvEvent::PropertyChanged(ObservedProperty::Hwdec(hwdec)))) => {
+                    if *this.hwdec != hwdec {
+                        info!("decoding with hwdec {hwdec}");
+                        *this.hwdec = hwdec.clone();
+                        this.send_events
+                            .send(Events::Hwdec(hwdec))
+                            .trace_send_error();
+                    }
+                }
+                Some(Ok(MpvEvent::PlaybackError(error))) => {
+                    warn!("unable to play current item: {error}");
+                    this.send_events
+                        .send(Events::PlaybackError(error))
+                        .trace_send_error();
+                }
                 Some(Ok(MpvEvent::Command(ClientCommand::Stop))) => {
                     stop(&this.mpv, this.playlist, this.index, this.send_events)
                         .context("stopping player")
@@ -373,6 +537,20 @@ impl Future for PollState {
     }
 }
 
+fn add_subtitle(mpv: &MpvStream, uri: String, title: String) -> Result<()> {
+    mpv.command(&[
+        c"sub-add".to_node(),
+        CString::new(uri)
+            .context("converting subtitle url to cstr")?
+            .to_node(),
+        c"auto".to_node(),
+        CString::new(title)
+            .context("converting subtitle title to cstr")?
+            .to_node(),
+    ])
+    .context("adding external subtitle")
+}
+
 fn play_index(mpv: &MpvStream, index: i64) -> Result<()> {
     mpv.playlist_play_index(index)
         .context("setting current playlist index")?;
@@ -425,6 +603,109 @@ fn remove_playlist_item(
     assert_shadow_playlist_state(mpv, playlist)
 }
 
+fn move_playlist_item(
+    playlist: &mut Arc<Vec<Arc<PlaylistItem>>>,
+    mpv: &MpvStream,
+    id: PlaylistItemId,
+    after: Option<PlaylistItemId>,
+    send_events: &broadcast::Sender<Events>,
+    cur_index: &mut Option<usize>,
+) -> Result<()> {
+    let from = index_of(playlist, id).ok_or_eyre("no such playlist item")?;
+    let to = match after {
+        Some(after) => index_of(playlist, after).ok_or_eyre("could not find this item id!")? + 1,
+        None => 0,
+    };
+    mpv.playlist_move(
+        from.try_into().context("converting index to i64")?,
+        to.try_into().context("converting index to i64")?,
+    )
+    .context("moving item in mpv playlist")?;
+    let current = cur_index.map(|i| playlist[i].id);
+    let mut playlist_vec = Vec::clone(playlist);
+    let item = playlist_vec.remove(from);
+    playlist_vec.insert(if to > from { to - 1 } else { to }, item);
+    *playlist = Arc::new(playlist_vec);
+    *cur_index = current.and_then(|id| index_of(playlist, id));
+    send_events
+        .send(Events::ReplacePlaylist {
+            current,
+            current_index: *cur_index,
+            new_playlist: playlist.clone(),
+        })
+        .trace_send_error();
+    assert_shadow_playlist_state(mpv, playlist)
+}
+
+fn loop_mode(file: bool, playlist: bool) -> LoopMode {
+    if file {
+        LoopMode::File
+    } else if playlist {
+        LoopMode::Playlist
+    } else {
+        LoopMode::None
+    }
+}
+
+/// Reorders the items after the current one randomly or back into the order before shuffling
+fn shuffle_playlist(
+    playlist: &mut Arc<Vec<Arc<PlaylistItem>>>,
+    mpv: &MpvStream,
+    shuffle: bool,
+    unshuffled: &mut Option<Vec<PlaylistItemId>>,
+    send_events: &broadcast::Sender<Events>,
+    cur_index: &mut Option<usize>,
+) -> Result<()> {
+    if shuffle == unshuffled.is_some() {
+        return Ok(());
+    }
+    let present: Vec<_> = playlist.iter().map(|item| item.id).collect();
+    let order = if shuffle {
+        let mut order = present.clone();
+        let start = cur_index.map_or(0, |i| i + 1);
+        order[start..].shuffle(&mut rand::rng());
+        *unshuffled = Some(present);
+        order
+    } else {
+        let previous = unshuffled.take().expect("checked to be shuffled");
+        // items added while shuffled stay behind the ones from before
+        let added = present.iter().filter(|id| !previous.contains(id));
+        previous
+            .iter()
+            .filter(|id| present.contains(id))
+            .chain(added)
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    let current = cur_index.map(|i| playlist[i].id);
+    let mut playlist_vec = Vec::clone(playlist);
+    for (to, id) in order.into_iter().enumerate() {
+        let from = index_of(&playlist_vec, id).ok_or_eyre("no such playlist item")?;
+        if from != to {
+            mpv.playlist_move(
+                from.try_into().context("converting index to i64")?,
+                to.try_into().context("converting index to i64")?,
+            )
+            .context("moving item in mpv playlist")?;
+            let item = playlist_vec.remove(from);
+            playlist_vec.insert(to, item);
+        }
+    }
+    *playlist = Arc::new(playlist_vec);
+    *cur_index = current.and_then(|id| index_of(playlist, id));
+    send_events
+        .send(Events::ReplacePlaylist {
+            current,
+            current_index: *cur_index,
+            new_playlist: playlist.clone(),
+        })
+        .trace_send_error();
+    send_events
+        .send(Events::Shuffle(shuffle))
+        .trace_send_error();
+    assert_shadow_playlist_state(mpv, playlist)
+}
+
 fn replace_playlist(
     mpv: &MpvStream,
     jellyfin: &JellyfinClient,
@@ -480,34 +761,28 @@ fn insert_at(
         0
     };
     info!("inserting item at index {index}");
-    let position = item
-        .user_data
-        .as_ref()
-        .ok_or_eyre("user data missing")?
-        .playback_position_ticks
-        / 10000000;
+    let position = resume_position(&item);
 
     debug!("adding {uri} to queue");
     let at = i64::try_from(index).context("converting index to i64")?;
+    let start = CString::new(position.to_string()).context("converting start to cstr")?;
+    let title = name(&item)?;
+    let mut keys = vec![
+        BorrowingCPtr::new(c"start"),
+        BorrowingCPtr::new(c"force-media-title"),
+    ];
+    let mut values = vec![start.to_node(), title.to_node()];
+    if let Some(filter) = stereo_filter(&item) {
+        keys.push(BorrowingCPtr::new(c"vf"));
+        values.push(filter.to_node());
+    }
     mpv.command(&[
         c"loadfile".to_node(),
         CString::new(uri)
             .context("converting video url to cstr")?
             .to_node(),
         at.to_node(),
-        MpvNodeMapRef::new(
-            &[
-                BorrowingCPtr::new(c"start"),
-                BorrowingCPtr::new(c"force-media-title"),
-            ],
-            &[
-                CString::new(position.to_string())
-                    .context("converting start to cstr")?
-                    .to_node(),
-                name(&item)?.to_node(),
-            ],
-        )
-        .to_node(),
+        MpvNodeMapRef::new(&keys, &values).to_node(),
     ])?;
 
     let id = mk_id.next();
@@ -531,6 +806,11 @@ fn insert_at(
 #[instrument(skip_all)]
 fn name(item: &MediaItem) -> Result<CString> {
     let name = match &item.item_type {
+        ItemType::Music {
+            album_id: _,
+            album: _,
+            album_artist: _,
+        } => item.name.clone(),
         ItemType::Movie => item.name.clone(),
         ItemType::Episode {
             season_id: _,
@@ -570,10 +850,5 @@ fn name(item: &MediaItem) -> Result<CString> {
 }
 
 fn index_of(playlist: &[Arc<PlaylistItem>], id: PlaylistItemId) -> Option<usize> {
-    playlist
-        .iter()
-        .filter(|i| i.id == id)
-        .enumerate()
-        .next()
-        .map(|(i, _)| i)
+    playlist.iter().position(|i| i.id == id)
 }
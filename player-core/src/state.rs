@@ -37,6 +37,15 @@ impl State for PlayerState {
             Events::Speed(s) => self.speed = s,
             Events::Fullscreen(f) => self.fullscreen = f,
             Events::Volume(v) => self.volume = v,
+            Events::Duration(d) => self.duration = d,
+            Events::PlaybackError(_) => {}
+            Events::SubtitleTracks(tracks) => self.subtitle_tracks = tracks,
+            Events::AudioTracks(tracks) => self.audio_tracks = tracks,
+            Events::Chapters(chapters) => self.chapters = chapters,
+            Events::Chapter(c) => self.chapter = c,
+            Events::Loop(l) => self.loop_mode = l,
+            Events::Shuffle(s) => self.shuffle = s,
+            Events::Hwdec(h) => self.hwdec = h,
         }
     }
 }
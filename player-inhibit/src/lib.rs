@@ -0,0 +1,77 @@
+use color_eyre::eyre::{Context, Result, eyre};
+use player_core::PlayerHandle;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+use zbus::Connection;
+
+const SCREENSAVER: &str = "org.freedesktop.ScreenSaver";
+const SCREENSAVER_PATH: &str = "/org/freedesktop/ScreenSaver";
+
+async fn inhibit(conn: &Connection) -> Result<u32> {
+    conn.call_method(
+        Some(SCREENSAVER),
+        SCREENSAVER_PATH,
+        Some(SCREENSAVER),
+        "Inhibit",
+        &("jellyhaj", "playing media"),
+    )
+    .await
+    .context("calling Inhibit")?
+    .body()
+    .deserialize()
+    .context("deserializing inhibit cookie")
+}
+
+async fn uninhibit(conn: &Connection, cookie: u32) -> Result<()> {
+    conn.call_method(
+        Some(SCREENSAVER),
+        SCREENSAVER_PATH,
+        Some(SCREENSAVER),
+        "UnInhibit",
+        &(cookie,),
+    )
+    .await
+    .context("calling UnInhibit")?;
+    Ok(())
+}
+
+/// Keeps the screensaver from activating while the player is playing and not paused
+pub async fn run_inhibit_service(handle: PlayerHandle) -> Result<()> {
+    let mut state = handle
+        .get_state()
+        .await
+        .map_err(|_| eyre!("mpv handle is already closed"))?;
+    let conn = Connection::session()
+        .await
+        .context("connecting to session bus")?;
+    let mut cookie = None;
+    loop {
+        let playing = !state.pause && !state.stopped;
+        match cookie {
+            None if playing => {
+                debug!("inhibiting screensaver");
+                cookie = Some(inhibit(&conn).await?);
+            }
+            Some(inhibited) if !playing => {
+                debug!("releasing screensaver inhibit");
+                cookie = None;
+                uninhibit(&conn, inhibited).await?;
+            }
+            _ => {}
+        }
+        match state.receive().await {
+            Ok(()) => {}
+            Err(RecvError::Closed) => {
+                info!("inhibit player closed");
+                break;
+            }
+            Err(RecvError::Lagged(_)) => {
+                warn!("lagged while processing events, inhibit state might be outdated");
+            }
+        }
+    }
+    if let Some(cookie) = cookie {
+        uninhibit(&conn, cookie).await?;
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+mod prefetch;
+
 use std::{mem, sync::Arc};
 
 use color_eyre::eyre::Context;
@@ -6,6 +8,8 @@ use player_core::PlayerHandle;
 use spawn::Spawner;
 use tracing::{error_span, info, instrument};
 
+pub use prefetch::prefetch_next;
+
 fn send_playing(id: Arc<String>, jellyfin: JellyfinClient, spawner: &Spawner) {
     let span = error_span!("send_playing");
     spawner.spawn_res(
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use entries::image::{ImageSize, cache::ImageProtocolKey, prefetch_image};
+use jellyfin::{JellyfinClient, image::select_images, items::ImageType};
+use player_core::PlayerHandle;
+use spawn::Spawner;
+use sqlx::SqliteConnection;
+use stats_data::Stats;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error_span, info, instrument, warn};
+
+/// Loads the image of the item after the current one into the image cache whenever the current item changes
+#[instrument(skip_all)]
+pub async fn prefetch_next(
+    player: PlayerHandle,
+    jellyfin: JellyfinClient,
+    db: Arc<tokio::sync::Mutex<SqliteConnection>>,
+    size: impl Fn(ImageType) -> ImageSize,
+    stats: Stats,
+    spawner: Spawner,
+) {
+    let mut state = match player.get_state().await {
+        Ok(v) => v,
+        Err(_) => {
+            info!("player is already closed");
+            return;
+        }
+    };
+    let mut current = None;
+    loop {
+        match state.receive().await {
+            Ok(()) => {}
+            Err(RecvError::Closed) => {
+                info!("prefetch player closed");
+                break;
+            }
+            Err(RecvError::Lagged(_)) => {
+                warn!("lagged while processing events, prefetching might be skipped");
+            }
+        }
+        if current == state.current {
+            continue;
+        }
+        current = state.current;
+        let Some(next) = current.and_then(|i| state.playlist.get(i + 1)) else {
+            continue;
+        };
+        if let Some((image_type, tag)) = select_images(&next.item).next() {
+            debug!("prefetching image of {}", next.item.name);
+            spawner.spawn_res(
+                prefetch_image(
+                    ImageProtocolKey::new(
+                        image_type,
+                        next.item.id.clone(),
+                        tag.to_string(),
+                        size(image_type),
+                    ),
+                    jellyfin.clone(),
+                    db.clone(),
+                    stats.clone(),
+                ),
+                error_span!("prefetch_image"),
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod media_player;
+mod notify;
 mod player;
 mod track_list;
 mod types;
@@ -32,9 +33,11 @@ use crate::{
     media_player::MediaPlayer2,
     player::{Player, pos_to_mpris},
     track_list::TrackList,
-    types::{Metadata, PlaybackStatus, track_id_as_object},
+    types::{LoopStatus, Metadata, PlaybackStatus, track_id_as_object},
 };
 
+pub use notify::run_notify_service;
+
 const MPRIS: &str = "/org/mpris/MediaPlayer2";
 
 async fn property_changed<I: Interface>(
@@ -189,6 +192,13 @@ pub async fn run_mpris_service(
                         .trace_error();
                     }
                     player_core::Events::Position(_) => {}
+                    player_core::Events::PlaybackError(_)
+                    | player_core::Events::Duration(_)
+                    | player_core::Events::SubtitleTracks(_)
+                    | player_core::Events::AudioTracks(_)
+                    | player_core::Events::Chapters(_)
+                    | player_core::Events::Chapter(_)
+                    | player_core::Events::Hwdec(_) => {}
                     player_core::Events::Seek(pos) => Player::seeked(&emitter, pos_to_mpris(*pos))
                         .await
                         .context("sending seek signal")
@@ -199,6 +209,13 @@ pub async fn run_mpris_service(
                     player_core::Events::Fullscreen(f) => {
                         property_changed::<MediaPlayer2>(&emitter, "Fullscreen", f).await
                     }
+                    player_core::Events::Loop(mode) => {
+                        property_changed::<Player>(&emitter, "LoopStatus", LoopStatus::from(*mode))
+                            .await
+                    }
+                    player_core::Events::Shuffle(shuffle) => {
+                        property_changed::<Player>(&emitter, "Shuffle", shuffle).await
+                    }
                     player_core::Events::Volume(vol) => {
                         property_changed::<Player>(&emitter, "Volume", (*vol as f64) / 100.0).await
                     }
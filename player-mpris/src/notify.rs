@@ -0,0 +1,141 @@
+use std::{collections::HashMap, path::Path};
+
+use color_eyre::eyre::{Context, Result, eyre};
+use jellyfin::{
+    JellyfinClient,
+    image::{GetImageQuery, select_images},
+    items::ItemType,
+};
+use player_core::{Events, PlayerHandle, PlaylistItem, PlaylistItemId};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+use zbus::{Connection, zvariant::Value};
+
+/// maximum width and height of the image shown in the notification
+const IMAGE_SIZE: u32 = 256;
+/// milliseconds the notification is shown
+const EXPIRE_TIMEOUT: i32 = 5000;
+
+/// Shows a desktop notification whenever a new item starts playing
+pub async fn run_notify_service(handle: PlayerHandle, jellyfin: JellyfinClient) -> Result<()> {
+    let mut state = handle
+        .get_state()
+        .await
+        .map_err(|_| eyre!("mpv handle is already closed"))?;
+    let conn = Connection::session()
+        .await
+        .context("connecting to session bus")?;
+    let image_path =
+        std::env::temp_dir().join(format!("jellyhaj-notification-{}.png", std::process::id()));
+    let mut replaces_id = 0;
+    let mut last: Option<PlaylistItemId> = None;
+    loop {
+        let started = state
+            .receive_inspect(async |event, state| match event {
+                Events::Current(Some(index)) => state.playlist.get(*index).cloned(),
+                _ => None,
+            })
+            .await;
+        match started {
+            Ok(Some(item)) if last != Some(item.id) => {
+                last = Some(item.id);
+                match notify(&conn, &jellyfin, &item, &image_path, replaces_id).await {
+                    Ok(id) => replaces_id = id,
+                    Err(e) => warn!("error sending now playing notification: {e:?}"),
+                }
+            }
+            Ok(_) => {}
+            Err(RecvError::Closed) => {
+                info!("notification player closed");
+                break;
+            }
+            Err(RecvError::Lagged(_)) => {
+                warn!("lagged while processing events, notifications might be skipped");
+            }
+        }
+    }
+    if image_path.exists() {
+        std::fs::remove_file(&image_path).context("removing notification image")?;
+    }
+    Ok(())
+}
+
+async fn notify(
+    conn: &Connection,
+    jellyfin: &JellyfinClient,
+    item: &PlaylistItem,
+    image_path: &Path,
+    replaces_id: u32,
+) -> Result<u32> {
+    let item = &item.item;
+    let body = match &item.item_type {
+        ItemType::Episode {
+            series_name,
+            season_name,
+            ..
+        } => match season_name {
+            Some(season) => format!("{series_name} - {season}"),
+            None => series_name.clone(),
+        },
+        ItemType::Music {
+            album,
+            album_artist,
+            ..
+        } => match album_artist {
+            Some(artist) => format!("{artist} - {album}"),
+            None => album.clone(),
+        },
+        _ => String::new(),
+    };
+    let mut hints = HashMap::new();
+    if let Some((image_type, tag)) = select_images(item).next() {
+        match jellyfin
+            .get_image(
+                &item.id,
+                image_type,
+                &GetImageQuery {
+                    tag: Some(tag),
+                    format: Some("Png"),
+                    max_width: Some(IMAGE_SIZE),
+                    max_height: Some(IMAGE_SIZE),
+                },
+            )
+            .await
+            .context("fetching notification image")
+            .and_then(|image| {
+                std::fs::write(image_path, image).context("writing notification image")
+            }) {
+            Ok(()) => {
+                hints.insert(
+                    "image-path",
+                    Value::from(format!("file://{}", image_path.display())),
+                );
+            }
+            Err(e) => warn!("showing notification without image: {e:?}"),
+        }
+    }
+    let actions: &[&str] = &[];
+    let reply = conn
+        .call_method(
+            Some("org.freedesktop.Notifications"),
+            "/org/freedesktop/Notifications",
+            Some("org.freedesktop.Notifications"),
+            "Notify",
+            &(
+                "jellyhaj",
+                replaces_id,
+                "",
+                item.name.as_str(),
+                body.as_str(),
+                actions,
+                hints,
+                EXPIRE_TIMEOUT,
+            ),
+        )
+        .await
+        .context("calling Notify")?;
+    reply
+        .body()
+        .deserialize()
+        .context("deserializing notification id")
+}
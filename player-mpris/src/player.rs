@@ -30,6 +30,10 @@ pub fn pos_to_mpris(secs: f64) -> i64 {
     (secs * 1000000.0) as i64
 }
 
+pub fn mpris_to_pos(micros: i64) -> f64 {
+    (micros as f64) / 1000000.0
+}
+
 #[interface(name = "org.mpris.MediaPlayer2.Player", spawn = false)]
 impl Player {
     fn next(&self) {
@@ -51,15 +55,22 @@ impl Player {
         self.player.send(Command::Pause(false));
     }
     fn seek(&self, micros: i64) {
-        let secs = (micros as f64) / 1000000.0;
-        self.player.send(Command::SeekRelative(secs));
+        self.player
+            .send(Command::SeekRelative(mpris_to_pos(micros)));
     }
     #[zbus(name = "SetPosition")]
     fn set_playback_position(&self, track: ObjectPath<'_>, micros: i64) -> Result<()> {
         let track_id = parse_track_id(&track)?
             .ok_or_else(|| Error::InvalidArgs("Track id is NoTrack".to_owned()))?;
-        self.player.send(Command::Play(track_id));
-        self.player.send(Command::Seek((micros as f64) / 1000000.0));
+        let state = self.state.lock();
+        let current = state.current.and_then(|i| state.playlist.get(i));
+        // the spec requires ignoring positions for other tracks or outside of the current one
+        if current.is_some_and(|current| current.id == track_id)
+            && micros >= 0
+            && (state.duration <= 0.0 || micros <= pos_to_mpris(state.duration))
+        {
+            self.player.send(Command::Seek(mpris_to_pos(micros)));
+        }
         Ok(())
     }
     fn open_uri(&self, _uri: &str) -> Result<()> {
@@ -85,11 +96,13 @@ impl Player {
 
     #[zbus(property)]
     fn loop_status(&self) -> LoopStatus {
-        LoopStatus::None
+        self.state.lock().loop_mode.into()
     }
 
     #[zbus(property)]
-    fn set_loop_status(&self, _l: LoopStatus) {}
+    fn set_loop_status(&self, status: LoopStatus) {
+        self.player.send(Command::SetLoop(status.into()));
+    }
 
     #[zbus(property)]
     fn rate(&self) -> f64 {
@@ -106,11 +119,13 @@ impl Player {
     }
     #[zbus(property)]
     fn shuffle(&self) -> bool {
-        false
+        self.state.lock().shuffle
     }
 
     #[zbus(property)]
-    fn set_shuffle(&self, _v: bool) {}
+    fn set_shuffle(&self, shuffle: bool) {
+        self.player.send(Command::Shuffle(shuffle));
+    }
 
     #[zbus(property)]
     fn metadata(&self) -> Result<Metadata> {
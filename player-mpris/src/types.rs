@@ -2,7 +2,7 @@ use jellyfin::{
     JellyfinClient,
     image::{GetImageQuery, select_images},
 };
-use player_core::{PlaylistItem, PlaylistItemId};
+use player_core::{LoopMode, PlaylistItem, PlaylistItemId};
 use serde::{Deserialize, Serialize, Serializer};
 use std::{collections::HashMap, result::Result as StdResult};
 use tracing::error;
@@ -76,6 +76,26 @@ pub enum LoopStatus {
     Playlist,
 }
 
+impl From<LoopMode> for LoopStatus {
+    fn from(mode: LoopMode) -> Self {
+        match mode {
+            LoopMode::None => LoopStatus::None,
+            LoopMode::File => LoopStatus::Track,
+            LoopMode::Playlist => LoopStatus::Playlist,
+        }
+    }
+}
+
+impl From<LoopStatus> for LoopMode {
+    fn from(status: LoopStatus) -> Self {
+        match status {
+            LoopStatus::None => LoopMode::None,
+            LoopStatus::Track => LoopMode::File,
+            LoopStatus::Playlist => LoopMode::Playlist,
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Type)]
 #[zvariant(signature = "a{sv}")]
 pub struct Metadata {
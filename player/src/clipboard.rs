@@ -0,0 +1,26 @@
+use std::io::Write;
+
+use base64::{Engine, prelude::BASE64_STANDARD};
+use color_eyre::eyre::{Context, Result};
+use jellyfin::{JellyfinClient, items::MediaItem};
+use ratatui::DefaultTerminal;
+
+/// Copies `text` to the clipboard through the terminal with an OSC 52 escape sequence.
+///
+/// This also works over ssh, but some terminals ignore it or ask for permission first.
+fn copy_to_clipboard(term: &mut DefaultTerminal, text: &str) -> Result<()> {
+    let backend = term.backend_mut();
+    write!(backend, "\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text))
+        .context("writing clipboard escape sequence")?;
+    Write::flush(backend).context("flushing terminal")
+}
+
+/// Copies the url of a direct stream of `item` to play it in an external player
+pub fn copy_stream_url(
+    term: &mut DefaultTerminal,
+    jellyfin: &JellyfinClient,
+    item: &MediaItem,
+) -> Result<()> {
+    let uri = jellyfin.get_stream_uri(item)?;
+    copy_to_clipboard(term, &uri.to_string())
+}
@@ -1,6 +1,9 @@
 use std::pin::Pin;
 
-use color_eyre::{Result, eyre::Context};
+use color_eyre::{
+    Result,
+    eyre::{Context, eyre},
+};
 use jellyfin::{
     Auth, JellyfinClient, JellyfinVec,
     items::{GetItemsQuery, MediaItem},
@@ -11,9 +14,14 @@ use jellyhaj_core::{
     context::TuiContext,
     state::{LoadPlay, Navigation, NextScreen},
 };
+use rand::seq::SliceRandom;
 use tracing::warn;
 
-async fn fetch_items(cx: &JellyfinClient<Auth>, item: LoadPlay) -> Result<(Vec<MediaItem>, usize)> {
+async fn fetch_items(
+    cx: &JellyfinClient<Auth>,
+    item: LoadPlay,
+    autoplay_next: bool,
+) -> Result<(Vec<MediaItem>, usize)> {
     Ok(match item {
         LoadPlay::Series { id } => (fetch_series(cx, &id).await?, 0),
         LoadPlay::Season { series_id, id } => {
@@ -51,10 +59,14 @@ async fn fetch_items(cx: &JellyfinClient<Auth>, item: LoadPlay) -> Result<(Vec<M
             }
         }
         LoadPlay::Episode { series_id, id } => {
-            let all = fetch_series(cx, &series_id).await?;
+            let mut all = fetch_series(cx, &series_id).await?;
 
             if let Some(position) = item_position(&id, &all) {
-                (all, position)
+                if autoplay_next {
+                    (all, position)
+                } else {
+                    (vec![all.swap_remove(position)], 0)
+                }
             } else {
                 let item = cx
                     .get_item(&id, Some(&cx.get_auth().user.id))
@@ -64,7 +76,7 @@ async fn fetch_items(cx: &JellyfinClient<Auth>, item: LoadPlay) -> Result<(Vec<M
                 (vec![item], 0)
             }
         }
-        LoadPlay::Playlist { id } => {
+        LoadPlay::Playlist { id, entry_id } => {
             let user_id = cx.get_auth().user.id.as_str();
             let items = JellyfinVec::collect(async |start| {
                 cx.get_playlist_items(
@@ -86,7 +98,14 @@ async fn fetch_items(cx: &JellyfinClient<Auth>, item: LoadPlay) -> Result<(Vec<M
                 .context("deserializing playlist items")
             })
             .await?;
-            (items, 0)
+            let pos = entry_id
+                .and_then(|entry_id| {
+                    items
+                        .iter()
+                        .position(|item| item.playlist_item_id.as_ref() == Some(&entry_id))
+                })
+                .unwrap_or(0);
+            (items, pos)
         }
         LoadPlay::Movie(item) => (vec![item], 0),
         LoadPlay::Music { id, album_id } => {
@@ -95,6 +114,16 @@ async fn fetch_items(cx: &JellyfinClient<Auth>, item: LoadPlay) -> Result<(Vec<M
             (items, pos)
         }
         LoadPlay::MusicAlbum { id } => (fetch_childs(cx, &id).await?, 0),
+        LoadPlay::BoxSet { id } => {
+            let mut items = fetch_playable(cx, &id).await?;
+            items.sort_by_key(|item| (item.production_year.is_none(), item.production_year));
+            (items, 0)
+        }
+        LoadPlay::Shuffle { id } => {
+            let mut items = fetch_playable(cx, &id).await?;
+            items.shuffle(&mut rand::rng());
+            (items, 0)
+        }
     })
 }
 
@@ -134,6 +163,33 @@ async fn fetch_childs(cx: &JellyfinClient<Auth>, parent_id: &str) -> Result<Vec<
     Ok(res)
 }
 
+/// every movie, episode and song below `parent_id`
+async fn fetch_playable(cx: &JellyfinClient<Auth>, parent_id: &str) -> Result<Vec<MediaItem>> {
+    let user_id = cx.get_auth().user.id.as_str();
+    let res = JellyfinVec::collect(async |start| {
+        cx.get_items(&GetItemsQuery {
+            user_id: user_id.into(),
+            start_index: start.into(),
+            limit: 100.into(),
+            parent_id: parent_id.into(),
+            include_item_types: "Movie, Episode, Audio".into(),
+            enable_images: Some(true),
+            image_type_limit: 1.into(),
+            enable_image_types: "Primary, Backdrop, Thumb".into(),
+            enable_user_data: true.into(),
+            recursive: true.into(),
+            ..Default::default()
+        })
+        .await
+        .context("fetching media items")?
+        .deserialize()
+        .await
+        .context("deserializing media items")
+    })
+    .await?;
+    Ok(res)
+}
+
 async fn fetch_series(cx: &JellyfinClient<Auth>, series_id: &str) -> Result<Vec<MediaItem>> {
     let user_id = cx.get_auth().user.id.as_str();
     let res = JellyfinVec::collect(async |start| {
@@ -164,12 +220,18 @@ async fn fetch_series(cx: &JellyfinClient<Auth>, series_id: &str) -> Result<Vec<
 pub async fn fetch_screen(cx: Pin<&mut TuiContext>, item: LoadPlay) -> Result<Navigation> {
     let cx = cx.project();
     let jellyfin = cx.jellyfin;
+    let autoplay_next = cx.config.autoplay_next;
     fetch::fetch_screen(
         "Loading related items for playlist",
         async {
-            let (items, index) = fetch_items(jellyfin, item)
+            let (items, index) = fetch_items(jellyfin, item, autoplay_next)
                 .await
                 .context("loading home screen data")?;
+            if items.is_empty() {
+                return Ok(Navigation::Replace(NextScreen::Error(eyre!(
+                    "Nothing to play, the item has no playable children"
+                ))));
+            }
             Ok(Navigation::Replace(NextScreen::Play { items, index }))
         },
         cx.events,
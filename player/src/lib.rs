@@ -1,28 +1,59 @@
+mod clipboard;
 pub mod fetch_items;
+mod playback_settings;
+mod resume;
+mod trickplay;
+mod visualizer;
 
-use std::{borrow::Cow, pin::Pin};
+use std::{
+    borrow::Cow,
+    pin::{Pin, pin},
+    sync::Arc,
+    time::Duration,
+};
 
 use color_eyre::eyre::{Context, Result, eyre};
-use futures_util::StreamExt;
-use jellyfin::items::MediaItem;
+use futures_util::{
+    FutureExt, StreamExt,
+    future::{Fuse, FusedFuture},
+};
+use jellyfin::items::{ItemType, MediaItem, MediaType};
 use jellyhaj_core::{
-    context::TuiContext,
+    config::{PlaybackErrorAction, SleepTimerAction},
+    context::{TuiContext, TuiContextProj},
     keybinds::MpvCommand,
     state::{Navigation, NextScreen},
 };
 use keybinds::{KeybindEvent, KeybindEventStream};
 use player_core::{
-    Command, PlayerHandle,
+    Chapter, Command, PlayerHandle, PlayerState, Track, resume_position,
     state::{EventReceiver, SharedPlayerState},
 };
 use ratatui::{
-    layout::{Constraint, Layout},
-    widgets::{Block, Padding, Paragraph, Widget},
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Modifier,
+    text::Line,
+    widgets::{
+        Block, Clear, LineGauge, List, ListItem, ListState, Padding, Paragraph, StatefulWidget,
+        Widget,
+    },
 };
 use ratatui_fallible_widget::{FallibleWidget, TermExt};
-use tokio::{select, sync::broadcast::error::RecvError};
+use ratatui_image::picker::Picker;
+use tokio::{
+    select,
+    sync::broadcast::error::RecvError,
+    time::{Instant, interval, sleep_until},
+};
 use tracing::{info, instrument, warn};
 
+use crate::{
+    clipboard::copy_stream_url,
+    resume::{ResumeChoice, ask_resume},
+    trickplay::{Thumbnail, TrickplayCache, load_thumbnail},
+    visualizer::render_visualizer,
+};
+
 struct MinimizeGuard {
     handle: PlayerHandle,
 }
@@ -36,7 +67,7 @@ impl Drop for MinimizeGuard {
 #[instrument(skip_all)]
 pub async fn play(
     cx: Pin<&mut TuiContext>,
-    items: Vec<MediaItem>,
+    mut items: Vec<MediaItem>,
     index: usize,
 ) -> Result<Navigation> {
     if items.is_empty() {
@@ -44,13 +75,36 @@ pub async fn play(
             "Unable to play, item is empty"
         ))));
     }
-    let cx = cx.project();
+    let mut cx = cx.project();
+    let position = items.get(index).map_or(0, resume_position);
+    if cx.config.resume_prompt && position > 0 {
+        match ask_resume(&mut cx, position).await? {
+            ResumeChoice::Resume => {}
+            ResumeChoice::StartOver => {
+                if let Some(user_data) = &mut items[index].user_data {
+                    user_data.playback_position_ticks = 0;
+                }
+            }
+            ResumeChoice::Leave(nav) => return Ok(nav),
+            ResumeChoice::Open(next) => {
+                return Ok(Navigation::Push {
+                    current: NextScreen::Play { items, index },
+                    next,
+                });
+            }
+        }
+    }
     let mut state = cx
         .mpv_handle
         .get_state()
         .await
         .map_err(|_| eyre!("player is already closed"))?
         .with_shared_state();
+    if cx.config.clear_images_on_play {
+        cx.image_cache
+            .invalidate(cx.image_picker)
+            .context("clearing images before playback")?;
+    }
     cx.mpv_handle.send(Command::Minimized(false));
     cx.mpv_handle.send(Command::Fullscreen(true));
     cx.mpv_handle.send(Command::ReplacePlaylist {
@@ -58,32 +112,194 @@ pub async fn play(
 
         first: index,
     });
+    if cx.config.remember_volume_speed {
+        match playback_settings::load(cx.cache).await {
+            Ok(Some((volume, speed))) => {
+                cx.mpv_handle.send(Command::Volume(volume));
+                cx.mpv_handle.send(Command::Speed(speed));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("unable to restore playback settings: {e:?}"),
+        }
+    }
     let minimize = MinimizeGuard {
         handle: cx.mpv_handle.clone(),
     };
+    let nav = run_player(&mut cx, &mut state, false).await;
+    //some ffmpeg stuff still writes to stdout
+    cx.term.clear()?;
+    drop(minimize);
+    if cx.config.remember_volume_speed {
+        let (volume, speed) = {
+            let state = state.lock();
+            (state.volume, state.speed)
+        };
+        if let Err(e) = playback_settings::store(cx.cache, volume, speed).await {
+            warn!("unable to persist playback settings: {e:?}")
+        }
+    }
+    nav
+}
+
+/// Shows the player for the playlist that is already playing, leaving it keeps playback running
+#[instrument(skip_all)]
+pub async fn now_playing(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
+    let mut cx = cx.project();
+    let mut state = cx
+        .mpv_handle
+        .get_state()
+        .await
+        .map_err(|_| eyre!("player is already closed"))?
+        .with_shared_state();
+    if cx.config.clear_images_on_play {
+        cx.image_cache
+            .invalidate(cx.image_picker)
+            .context("clearing images before playback")?;
+    }
+    let nav = run_player(&mut cx, &mut state, true).await;
+    cx.term.clear()?;
+    nav
+}
+
+/// `attached` is set if the playback was not started by this screen
+async fn run_player(
+    cx: &mut TuiContextProj<'_>,
+    state: &mut EventReceiver<SharedPlayerState>,
+    attached: bool,
+) -> Result<Navigation> {
+    let mut idle = state.lock().stopped;
     let mut widget = PlayerWidget {
-        state: state.clone(),
+        state: SharedPlayerState::clone(state),
+        sleep_timer: None,
+        up_next: None,
+        overlay: None,
+        volume_shown: None,
+        preview: None,
+        thumbnail: None,
+        picker: cx.image_picker.clone(),
+        visualizer: cx.config.music_visualizer,
+        toast: (attached && idle).then(|| {
+            (
+                "Nothing is playing".to_string(),
+                Instant::now() + TOAST_DURATION,
+            )
+        }),
     };
+    let sleep_presets = sleep_timer_presets(cx.config.sleep_timer_minutes.get());
+    let mut sleep_preset = None;
+    let mut sleep_tick = interval(Duration::from_secs(1));
+    let jellyfin = cx.jellyfin;
+    let mut trickplay = TrickplayCache::default();
+    let mut thumbnail = pin!(Fuse::terminated());
+    // requested while the previous thumbnail was still loading
+    let mut pending_thumbnail: Option<(String, f64)> = None;
     let mut events = KeybindEventStream::new(
         cx.events,
         &mut widget,
         cx.config.keybinds.play_mpv.clone(),
         &cx.config.help_prefixes,
     );
-    let mut idle = state.lock().stopped;
+    // a full clear removes leftover images, it is skipped for updates that only change text
+    let mut clear = true;
     loop {
-        cx.term.clear()?;
+        if clear {
+            cx.term.clear()?;
+        }
+        clear = true;
         cx.term.draw_fallible(&mut events)?;
 
+        let sleep_timer = events.get_inner().sleep_timer;
+        let up_next = events.get_inner().up_next;
+        let toast = events
+            .get_inner()
+            .toast
+            .as_ref()
+            .map(|(_, deadline)| *deadline);
+        let volume = events.get_inner().volume_shown;
+        let preview = events.get_inner().preview;
         select! {
-            cont = watch_state(&mut state) => {
-                if ! cont{
+            _ = sleep_until(sleep_timer.unwrap_or_else(Instant::now)), if sleep_timer.is_some() => {
+                events.get_inner().sleep_timer = None;
+                sleep_preset = None;
+                match cx.config.sleep_timer_action {
+                    SleepTimerAction::Pause => {
+                        info!("sleep timer expired, pausing");
+                        cx.mpv_handle.send(Command::Pause(true));
+                    }
+                    SleepTimerAction::Quit => {
+                        info!("sleep timer expired, exiting");
+                        if attached {
+                            cx.mpv_handle.send(Command::Stop);
+                        }
+                        break Ok(Navigation::PopContext);
+                    }
+                }
+            }
+            _ = sleep_until(up_next.unwrap_or_else(Instant::now)), if up_next.is_some() => {
+                events.get_inner().up_next = None;
+                cx.mpv_handle.send(Command::Pause(false));
+            }
+            _ = sleep_until(toast.unwrap_or_else(Instant::now)), if toast.is_some() => {
+                events.get_inner().toast = None;
+                if attached && state.lock().stopped {
+                    break Ok(Navigation::PopContext);
+                }
+            }
+            _ = sleep_until(volume.unwrap_or_else(Instant::now)), if volume.is_some() => {
+                events.get_inner().volume_shown = None;
+            }
+            _ = sleep_until(preview.unwrap_or_else(Instant::now)), if preview.is_some() => {
+                events.get_inner().preview = None;
+            }
+            (cache, loaded) = &mut thumbnail, if !thumbnail.is_terminated() => {
+                trickplay = cache;
+                match loaded {
+                    Ok(image) => events.get_inner().thumbnail = image.map(Thumbnail::new),
+                    Err(e) => warn!("unable to load trickplay thumbnail: {e:?}"),
+                }
+                if let Some((item_id, position)) = pending_thumbnail.take() {
+                    let cache = std::mem::take(&mut trickplay);
+                    thumbnail.set(load_thumbnail(jellyfin, cache, item_id, position).fuse());
+                }
+            }
+            _ = sleep_tick.tick(), if sleep_timer.is_some() || up_next.is_some() => {
+                clear = false;
+            }
+            update = watch_state(state) => {
+                if let StateUpdate::Position = update {
+                    clear = false;
+                }
+                if let StateUpdate::Closed = update {
                     info!("mpv sender is closed, exiting");
-                    break;
-                }else if idle != state.lock().stopped {
+                    break Ok(Navigation::PopContext);
+                }
+                if let StateUpdate::Finished = update
+                    && cx.config.up_next_seconds > 0
+                {
+                    cx.mpv_handle.send(Command::Pause(true));
+                    events.get_inner().up_next =
+                        Some(Instant::now() + Duration::from_secs(cx.config.up_next_seconds));
+                } else if let StateUpdate::Resumed = update {
+                    events.get_inner().up_next = None;
+                } else if let StateUpdate::Volume = update {
+                    events.get_inner().volume_shown = Some(Instant::now() + VOLUME_SHOWN);
+                } else if let StateUpdate::PlaybackError(message) = update {
+                    match cx.config.on_playback_error {
+                        // mpv already moved on to the next playlist item
+                        PlaybackErrorAction::Skip => {
+                            events.get_inner().toast =
+                                Some((message, Instant::now() + TOAST_DURATION));
+                        }
+                        PlaybackErrorAction::Stop => {
+                            cx.mpv_handle.send(Command::Stop);
+                            break Ok(Navigation::Replace(NextScreen::Error(eyre!(message))));
+                        }
+                    }
+                }
+                if idle != state.lock().stopped {
                     if !idle {
                         info!("mpv is idle, exiting");
-                        break;
+                        break Ok(Navigation::PopContext);
                     }else {
                         idle = false
                     }
@@ -91,60 +307,670 @@ pub async fn play(
             }
             event = events.next() => {
                 match event {
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Quit | MpvCommand::Back)))
+                        if events.get_inner().overlay.is_some() =>
+                    {
+                        events.get_inner().overlay = None;
+                        events.set_map(cx.config.keybinds.play_mpv.clone());
+                    }
                     Some(Ok(KeybindEvent::Command(MpvCommand::Quit)))
-                     => {break;}
+                     => {break Ok(Navigation::Quit);}
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Back)))
+                     => {break Ok(Navigation::PopContext);}
                     Some(Ok(KeybindEvent::Command(MpvCommand::Pause))) => {
                         cx.mpv_handle.send(Command::TogglePause);
                     }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::SleepTimer))) => {
+                        let next = sleep_preset.map(|i| (i + 1) % sleep_presets.len()).unwrap_or(0);
+                        sleep_preset = Some(next);
+                        let minutes = sleep_presets[next];
+                        info!("sleep timer set to {minutes} minutes");
+                        events.get_inner().sleep_timer =
+                            Some(Instant::now() + Duration::from_secs(minutes * 60));
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::CancelSleepTimer))) => {
+                        sleep_preset = None;
+                        events.get_inner().sleep_timer = None;
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Next))) => {
+                        cx.mpv_handle.send(Command::Next);
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Previous))) => {
+                        cx.mpv_handle.send(Command::Previous);
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::SeekForward | MpvCommand::SeekBackward),
+                    ))) => {
+                        let step = if let MpvCommand::SeekForward = cmd {
+                            SEEK_STEP
+                        } else {
+                            -SEEK_STEP
+                        };
+                        cx.mpv_handle.send(Command::SeekRelative(step));
+                        if let Some((item_id, position)) = seek_target(&state.lock(), step) {
+                            events.get_inner().preview = Some(Instant::now() + PREVIEW_SHOWN);
+                            if thumbnail.is_terminated() {
+                                let cache = std::mem::take(&mut trickplay);
+                                thumbnail.set(
+                                    load_thumbnail(jellyfin, cache, item_id, position).fuse(),
+                                );
+                            } else {
+                                pending_thumbnail = Some((item_id, position));
+                            }
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::VolumeUp | MpvCommand::VolumeDown),
+                    ))) => {
+                        let step = if let MpvCommand::VolumeUp = cmd {
+                            VOLUME_STEP
+                        } else {
+                            -VOLUME_STEP
+                        };
+                        let current = state.lock().volume;
+                        cx.mpv_handle.send(Command::Volume((current + step).clamp(0, MAX_VOLUME)));
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::SpeedUp | MpvCommand::SpeedDown),
+                    ))) => {
+                        let step = if let MpvCommand::SpeedUp = cmd { 1 } else { -1 };
+                        // count whole steps so repeated changes don't accumulate float errors
+                        let steps = (state.lock().speed / SPEED_STEP).round() as i64 + step;
+                        let steps = steps.clamp(MIN_SPEED_STEPS, MAX_SPEED_STEPS);
+                        cx.mpv_handle.send(Command::Speed(steps as f64 * SPEED_STEP));
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::SpeedReset))) => {
+                        cx.mpv_handle.send(Command::Speed(1.0));
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::NextChapter | MpvCommand::PrevChapter),
+                    ))) => {
+                        let state = state.lock();
+                        let chapter = if let MpvCommand::NextChapter = cmd {
+                            state.chapter + 1
+                        } else {
+                            (state.chapter - 1).max(0)
+                        };
+                        if chapter < state.chapters.len() as i64 {
+                            cx.mpv_handle.send(Command::SetChapter(chapter));
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::CancelUpNext))) => {
+                        events.get_inner().up_next = None;
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::PlayUpNext))) => {
+                        if events.get_inner().up_next.take().is_some() {
+                            cx.mpv_handle.send(Command::Pause(false));
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::Subtitles
+                        | MpvCommand::AudioTracks
+                        | MpvCommand::ShowQueue
+                        | MpvCommand::ShowChapters),
+                    ))) => {
+                        let keybinds = &cx.config.keybinds;
+                        let (overlay, map) = match cmd {
+                            MpvCommand::Subtitles => {
+                                (Overlay::Tracks(TrackKind::Subtitles), &keybinds.play_mpv_tracks)
+                            }
+                            MpvCommand::AudioTracks => {
+                                (Overlay::Tracks(TrackKind::Audio), &keybinds.play_mpv_tracks)
+                            }
+                            MpvCommand::ShowChapters => {
+                                (Overlay::Chapters, &keybinds.play_mpv_chapters)
+                            }
+                            _ => (Overlay::Queue, &keybinds.play_mpv_queue),
+                        };
+                        if toggle_overlay(events.get_inner(), overlay) {
+                            events.set_map(map.clone());
+                        } else {
+                            events.set_map(cx.config.keybinds.play_mpv.clone());
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Up))) => {
+                        if let Some((_, list)) = &mut events.get_inner().overlay {
+                            list.select_previous();
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Down))) => {
+                        if let Some((_, list)) = &mut events.get_inner().overlay {
+                            list.select_next();
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Select))) => {
+                        let widget = events.get_inner();
+                        if let Some((overlay, list)) = widget.overlay.take() {
+                            let state = widget.state.lock();
+                            let command = list.selected().and_then(|i| match overlay {
+                                Overlay::Tracks(kind) => kind.command(&state, i),
+                                Overlay::Queue => {
+                                    state.playlist.get(i).map(|item| Command::Play(item.id))
+                                }
+                                Overlay::Chapters => (i < state.chapters.len())
+                                    .then(|| Command::SetChapter(i as i64)),
+                            });
+                            drop(state);
+                            if let Some(command) = command {
+                                cx.mpv_handle.send(command);
+                            }
+                            events.set_map(cx.config.keybinds.play_mpv.clone());
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::CopyStreamUrl))) => {
+                        let item = {
+                            let state = events.get_inner().state.lock();
+                            state.current.and_then(|i| state.playlist.get(i).cloned())
+                        };
+                        if let Some(item) = item {
+                            let message = match copy_stream_url(cx.term, jellyfin, &item.item) {
+                                Ok(()) => "Copied stream URL".to_string(),
+                                Err(e) => {
+                                    warn!("unable to copy stream url: {e:?}");
+                                    "Unable to copy stream URL".to_string()
+                                }
+                            };
+                            events.get_inner().toast =
+                                Some((message, Instant::now() + TOAST_DURATION));
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(MpvCommand::Remove))) => {
+                        let widget = events.get_inner();
+                        if let Some((Overlay::Queue, list)) = &widget.overlay
+                            && let Some(id) = list.selected().and_then(|i| {
+                                widget.state.lock().playlist.get(i).map(|item| item.id)
+                            })
+                        {
+                            cx.mpv_handle.send(Command::Remove(id));
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Command(
+                        cmd @ (MpvCommand::MoveUp | MpvCommand::MoveDown),
+                    ))) => {
+                        let widget = events.get_inner();
+                        if let Some((Overlay::Queue, list)) = &mut widget.overlay
+                            && let Some(index) = list.selected()
+                        {
+                            let playlist = widget.state.lock().playlist.clone();
+                            let target = if let MpvCommand::MoveUp = cmd {
+                                index.checked_sub(1)
+                            } else {
+                                Some(index + 1).filter(|i| *i < playlist.len())
+                            };
+                            if let Some(target) = target
+                                && let Some(item) = playlist.get(index)
+                            {
+                                // the entry that precedes the item at its new position
+                                let after = if target > index {
+                                    Some(playlist[target].id)
+                                } else {
+                                    target.checked_sub(1).map(|i| playlist[i].id)
+                                };
+                                cx.mpv_handle.send(Command::Move { id: item.id, after });
+                                list.select(Some(target));
+                            }
+                        }
+                    }
                     Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
-                    Some(Ok(KeybindEvent::Render)) => {},
-                    Some(Err(e)) => return Err(e).context("getting key events from terminal"),
-                    None => {return Ok(Navigation::Exit);}
+                    Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Render | KeybindEvent::Global(_))) => {},
+                    Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+                    None => break Ok(Navigation::Exit),
                 }
             }
         }
     }
-    //some ffmpeg stuff still writes to stdout
-    cx.term.clear()?;
-    drop(minimize);
-    Ok(Navigation::PopContext)
 }
 
 struct PlayerWidget {
     state: SharedPlayerState,
+    sleep_timer: Option<Instant>,
+    /// the next episode is paused until this deadline
+    up_next: Option<Instant>,
+    /// message shown until the deadline
+    toast: Option<(String, Instant)>,
+    /// open overlay and its selection
+    overlay: Option<(Overlay, ListState)>,
+    /// the volume is shown until the deadline after it changed
+    volume_shown: Option<Instant>,
+    /// the trickplay thumbnail is shown until the deadline after seeking
+    preview: Option<Instant>,
+    thumbnail: Option<Thumbnail>,
+    picker: Arc<Picker>,
+    /// animate bars below the track information of music
+    visualizer: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    Tracks(TrackKind),
+    Queue,
+    Chapters,
+}
+
+/// closes `overlay` if it is open and opens it otherwise, returns if it is open
+fn toggle_overlay(widget: &mut PlayerWidget, overlay: Overlay) -> bool {
+    if widget
+        .overlay
+        .take()
+        .is_some_and(|(open, _)| open == overlay)
+    {
+        return false;
+    }
+    let selected = {
+        let state = widget.state.lock();
+        match overlay {
+            Overlay::Tracks(kind) => kind.current(&state),
+            Overlay::Queue => state.current.unwrap_or(0),
+            Overlay::Chapters => state.chapter.max(0) as usize,
+        }
+    };
+    widget.overlay = Some((overlay, ListState::default().with_selected(Some(selected))));
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrackKind {
+    Subtitles,
+    Audio,
+}
+
+impl TrackKind {
+    fn tracks(self, state: &PlayerState) -> &[Track] {
+        match self {
+            TrackKind::Subtitles => &state.subtitle_tracks,
+            TrackKind::Audio => &state.audio_tracks,
+        }
+    }
+
+    /// subtitles start with an entry disabling them
+    fn offset(self) -> usize {
+        match self {
+            TrackKind::Subtitles => 1,
+            TrackKind::Audio => 0,
+        }
+    }
+
+    /// list index of the selected track
+    fn current(self, state: &PlayerState) -> usize {
+        self.tracks(state)
+            .iter()
+            .position(|track| track.selected)
+            .map_or(0, |i| i + self.offset())
+    }
+
+    fn command(self, state: &PlayerState, index: usize) -> Option<Command> {
+        match (self, index) {
+            (TrackKind::Subtitles, 0) => Some(Command::SetSubtitle(None)),
+            (TrackKind::Subtitles, i) => state
+                .subtitle_tracks
+                .get(i - 1)
+                .map(|track| Command::SetSubtitle(Some(track.id))),
+            (TrackKind::Audio, i) => state
+                .audio_tracks
+                .get(i)
+                .map(|track| Command::SetAudioTrack(track.id)),
+        }
+    }
+}
+
+/// how long a toast message stays visible
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+
+/// seconds skipped by the seek commands
+const SEEK_STEP: f64 = 10.0;
+
+/// percent the volume commands change the volume by
+const VOLUME_STEP: i64 = 5;
+
+/// mpv allows amplifying up to 130% by default
+const MAX_VOLUME: i64 = 130;
+
+/// playback speed change of the speed commands
+const SPEED_STEP: f64 = 0.25;
+
+/// slowest and fastest speed in multiples of [SPEED_STEP], 0.25 and 4.0
+const MIN_SPEED_STEPS: i64 = 1;
+const MAX_SPEED_STEPS: i64 = 16;
+
+/// how long the volume stays visible after it changed
+const VOLUME_SHOWN: Duration = Duration::from_secs(1);
+
+/// how long the trickplay thumbnail stays visible after seeking
+const PREVIEW_SHOWN: Duration = Duration::from_secs(2);
+
+/// the last seconds of an item that count as watching it until the end
+const FINISHED_MARGIN: f64 = 5.0;
+
+enum StateUpdate {
+    Changed,
+    /// the playback position advanced, only needs a redraw
+    Position,
+    /// an episode played until the end and the following one started
+    Finished,
+    Resumed,
+    Volume,
+    /// the current item could not be played, contains a message for the user
+    PlaybackError(String),
+    Closed,
+}
+
+fn finished_into_episode(state: &PlayerState, next: usize) -> bool {
+    let Some(current) = state.current else {
+        return false;
+    };
+    if current + 1 != next {
+        return false;
+    }
+    let (Some(current), Some(next)) = (state.playlist.get(current), state.playlist.get(next))
+    else {
+        return false;
+    };
+    if !matches!(next.item.item_type, ItemType::Episode { .. }) {
+        return false;
+    }
+    match current.item.run_time_ticks {
+        Some(ticks) => state.position >= ticks as f64 / 10_000_000.0 - FINISHED_MARGIN,
+        None => true,
+    }
+}
+
+/// current video and the position a relative seek by `step` lands on
+fn seek_target(state: &PlayerState, step: f64) -> Option<(String, f64)> {
+    let item = &state.playlist.get(state.current?)?.item;
+    if item.media_type != MediaType::Video {
+        return None;
+    }
+    let mut position = (state.position + step).max(0.0);
+    if state.duration > 0.0 {
+        position = position.min(state.duration);
+    }
+    Some((item.id.clone(), position))
+}
+
+fn episode_code(item: &MediaItem) -> Option<String> {
+    if item.episode_index.is_none() && item.season_index.is_none() {
+        return None;
+    }
+    if item.is_special() {
+        return Some(match item.episode_index {
+            Some(episode) => format!("Special {episode}"),
+            None => "Special".to_string(),
+        });
+    }
+    let mut code = String::new();
+    if let Some(season) = item.season_index {
+        code.push('S');
+        code.push_str(&season.to_string());
+    }
+    if let Some(episode) = item.episode_index {
+        code.push('E');
+        code.push_str(&episode.to_string());
+    }
+    Some(code)
+}
+
+fn describe_track(track: &Track) -> String {
+    let mut text = match &track.title {
+        Some(title) => format!("{}: {title}", track.id),
+        None => format!("{}: Track {}", track.id, track.id),
+    };
+    if let Some(lang) = &track.lang {
+        text.push_str(&format!(" [{lang}]"));
+    }
+    if track.external {
+        text.push_str(" (external)");
+    }
+    if track.selected {
+        text.push_str(" *");
+    }
+    text
+}
+
+/// centered popup with room for `entries` lines, cleared and surrounded by `block`
+fn render_popup(
+    block: Block<'_>,
+    entries: usize,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Percentage(60)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(entries.max(1) as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(area);
+    Clear.render(area, buf);
+    let inner = block.inner(area);
+    block.render(area, buf);
+    inner
+}
+
+/// small box with the current volume in the top right corner
+fn render_volume(state: &PlayerState, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+    let [area] = Layout::horizontal([Constraint::Length(24)])
+        .flex(Flex::End)
+        .horizontal_margin(2)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(3)])
+        .vertical_margin(1)
+        .areas(area);
+    Clear.render(area, buf);
+    let block = Block::bordered().title("Volume");
+    let inner = block.inner(area);
+    block.render(area, buf);
+    LineGauge::default()
+        .ratio(state.volume.clamp(0, MAX_VOLUME) as f64 / MAX_VOLUME as f64)
+        .label(format!("{:>3}%", state.volume))
+        .render(inner, buf);
+}
+
+/// centered popup listing the tracks of `kind`
+fn render_tracks(
+    kind: TrackKind,
+    state: &PlayerState,
+    list: &mut ListState,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let tracks = kind.tracks(state);
+    let (title, empty) = match kind {
+        TrackKind::Subtitles => ("Subtitles", "This item has no subtitles"),
+        TrackKind::Audio => ("Audio tracks", "This item has no audio tracks"),
+    };
+    let inner = render_popup(
+        Block::bordered()
+            .title(title)
+            .padding(Padding::horizontal(1)),
+        tracks.len() + kind.offset(),
+        area,
+        buf,
+    );
+    if tracks.is_empty() {
+        Paragraph::new(empty).render(inner, buf);
+        return;
+    }
+    let disabled = match kind {
+        TrackKind::Subtitles if tracks.iter().any(|track| track.selected) => {
+            Some("No subtitles".to_string())
+        }
+        TrackKind::Subtitles => Some("No subtitles *".to_string()),
+        TrackKind::Audio => None,
+    };
+    let list_widget = List::new(
+        disabled
+            .into_iter()
+            .chain(tracks.iter().map(describe_track)),
+    )
+    .highlight_style(Modifier::REVERSED);
+    StatefulWidget::render(list_widget, inner, buf, list);
+}
+
+/// centered popup listing the playlist, the current item is bold
+fn render_queue(
+    state: &PlayerState,
+    list: &mut ListState,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let inner = render_popup(
+        Block::bordered()
+            .title("Queue")
+            .padding(Padding::horizontal(1)),
+        state.playlist.len(),
+        area,
+        buf,
+    );
+    if state.playlist.is_empty() {
+        Paragraph::new("The queue is empty").render(inner, buf);
+        return;
+    }
+    let items = state.playlist.iter().enumerate().map(|(i, item)| {
+        let name = match episode_code(&item.item) {
+            Some(code) => format!("{code} — {}", item.item.name),
+            None => item.item.name.clone(),
+        };
+        if state.current == Some(i) {
+            ListItem::new(format!("▶ {name}")).style(Modifier::BOLD)
+        } else {
+            ListItem::new(format!("  {name}"))
+        }
+    });
+    let list_widget = List::new(items).highlight_style(Modifier::REVERSED);
+    StatefulWidget::render(list_widget, inner, buf, list);
+}
+
+/// centered popup listing the chapters with their start, the current one is bold
+fn render_chapters(
+    state: &PlayerState,
+    list: &mut ListState,
+    area: Rect,
+    buf: &mut ratatui::prelude::Buffer,
+) {
+    let inner = render_popup(
+        Block::bordered()
+            .title("Chapters")
+            .padding(Padding::horizontal(1)),
+        state.chapters.len(),
+        area,
+        buf,
+    );
+    if state.chapters.is_empty() {
+        Paragraph::new("This item has no chapters").render(inner, buf);
+        return;
+    }
+    let items = state.chapters.iter().enumerate().map(|(i, chapter)| {
+        let name = describe_chapter(i, chapter);
+        if state.chapter == i as i64 {
+            ListItem::new(format!("▶ {name}")).style(Modifier::BOLD)
+        } else {
+            ListItem::new(format!("  {name}"))
+        }
+    });
+    let list_widget = List::new(items).highlight_style(Modifier::REVERSED);
+    StatefulWidget::render(list_widget, inner, buf, list);
+}
+
+fn describe_chapter(index: usize, chapter: &Chapter) -> String {
+    let start = format_timestamp(chapter.time);
+    match &chapter.title {
+        Some(title) => format!("{start}  {title}"),
+        None => format!("{start}  Chapter {}", index + 1),
+    }
+}
+
+/// `H:MM:SS`
+fn format_timestamp(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
 }
-async fn watch_state(events: &mut EventReceiver<SharedPlayerState>) -> bool {
+
+fn render_progress(state: &PlayerState, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+    let mut position = format_timestamp(state.position);
+    if state.duration > 0.0 {
+        position = format!("{position} / {}", format_timestamp(state.duration));
+    }
+    if state.speed != 1.0 {
+        position = format!("{position} ({}x)", state.speed);
+    }
+    if state.duration > 0.0 {
+        LineGauge::default()
+            .ratio((state.position / state.duration).clamp(0.0, 1.0))
+            .label(position)
+            .filled_style(Modifier::BOLD)
+            .render(area, buf);
+    } else {
+        Paragraph::new(position).render(area, buf);
+    }
+}
+
+/// configured default first, then the remaining fixed durations
+fn sleep_timer_presets(default: u64) -> Vec<u64> {
+    let mut presets = vec![default];
+    presets.extend([15, 30, 60].into_iter().filter(|m| *m != default));
+    presets
+}
+async fn watch_state(events: &mut EventReceiver<SharedPlayerState>) -> StateUpdate {
     loop {
         match events
-            .receive_inspect(async |events, _| match events {
+            .receive_inspect(async |events, state| match events {
                 player_core::Events::ReplacePlaylist {
                     current: _,
                     current_index: _,
                     new_playlist: _,
-                } => true,
+                } => Some(StateUpdate::Changed),
                 player_core::Events::AddPlaylistItem {
                     after: _,
                     index: _,
                     new_playlist: _,
-                } => false,
+                } => None,
                 player_core::Events::RemovePlaylistItem {
                     removed: _,
                     new_playlist: _,
-                } => false,
-                player_core::Events::Current(_) => true,
-                player_core::Events::Paused(_) => true,
-                player_core::Events::Stopped(_) => true,
-                player_core::Events::Position(_) => false,
-                player_core::Events::Seek(_) => false,
-                player_core::Events::Speed(_) => false,
-                player_core::Events::Fullscreen(_) => false,
-                player_core::Events::Volume(_) => false,
+                } => None,
+                player_core::Events::Current(Some(next))
+                    if finished_into_episode(&state.lock(), *next) =>
+                {
+                    Some(StateUpdate::Finished)
+                }
+                player_core::Events::Current(_) => Some(StateUpdate::Changed),
+                player_core::Events::Paused(false) => Some(StateUpdate::Resumed),
+                player_core::Events::Paused(true) => Some(StateUpdate::Changed),
+                player_core::Events::Stopped(_) => Some(StateUpdate::Changed),
+                player_core::Events::Position(_) => Some(StateUpdate::Position),
+                player_core::Events::Seek(_) => Some(StateUpdate::Changed),
+                player_core::Events::Duration(_) => Some(StateUpdate::Changed),
+                player_core::Events::Speed(_) => Some(StateUpdate::Changed),
+                player_core::Events::Fullscreen(_) => None,
+                player_core::Events::Volume(_) => Some(StateUpdate::Volume),
+                player_core::Events::SubtitleTracks(_) | player_core::Events::AudioTracks(_) => {
+                    Some(StateUpdate::Changed)
+                }
+                player_core::Events::Chapters(_) | player_core::Events::Chapter(_) => {
+                    Some(StateUpdate::Changed)
+                }
+                player_core::Events::Loop(_) | player_core::Events::Shuffle(_) => None,
+                player_core::Events::Hwdec(_) => Some(StateUpdate::Changed),
+                player_core::Events::PlaybackError(error) => {
+                    let state = state.lock();
+                    Some(StateUpdate::PlaybackError(
+                        match state.current.and_then(|i| state.playlist.get(i)) {
+                            Some(item) => format!("Unable to play {}: {error}", item.item.name),
+                            None => format!("Unable to play item: {error}"),
+                        },
+                    ))
+                }
             })
             .await
         {
-            Ok(true) => break true,
-            Ok(false) => (),
-            Err(RecvError::Closed) => break false,
+            Ok(Some(update)) => break update,
+            Ok(None) => (),
+            Err(RecvError::Closed) => break StateUpdate::Closed,
             Err(RecvError::Lagged(n)) => {
                 warn!("player ui missed {n} events, display might be unreliable")
             }
@@ -159,12 +985,51 @@ impl FallibleWidget for PlayerWidget {
         buf: &mut ratatui::prelude::Buffer,
     ) -> Result<()> {
         let block_area = area;
-        let block = Block::bordered()
+        let mut block = Block::bordered()
             .title("Now playing")
             .padding(Padding::uniform(1));
-        let area = block.inner(block_area);
+        if let Some(deadline) = self.sleep_timer {
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_secs();
+            block = block.title_bottom(
+                Line::from(format!(
+                    "Sleep timer {:02}:{:02}",
+                    remaining / 60,
+                    remaining % 60
+                ))
+                .right_aligned(),
+            );
+        }
+        if let Some((message, _)) = &self.toast {
+            block = block.title_bottom(Line::from(message.as_str()).left_aligned());
+        }
         let state = self.state.lock();
+        if let Some(deadline) = self.up_next
+            && let Some(index) = state.current
+        {
+            let item = &state.playlist[index].item;
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_secs();
+            let next = match episode_code(item) {
+                Some(code) => format!("{code} — {}", item.name),
+                None => item.name.clone(),
+            };
+            block = block.title_bottom(
+                Line::from(format!("Next: {next} (starting in {remaining}s)")).centered(),
+            );
+        }
+        if let Some(index) = state.current
+            && state.playlist[index].item.media_type == MediaType::Video
+            && !state.hwdec.is_empty()
+        {
+            let decoder = match state.hwdec.as_str() {
+                "no" => "software",
+                hwdec => hwdec,
+            };
+            block = block.title(Line::from(format!("hwdec: {decoder}")).right_aligned());
+        }
+        let [area, progress_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(1)])
+            .areas(block.inner(block_area));
         if let Some(index) = state.current {
+            render_progress(&state, progress_area, buf);
             let media_item = &state.playlist[index].item;
             match &media_item.item_type {
                 jellyfin::items::ItemType::Movie => {
@@ -182,18 +1047,10 @@ impl FallibleWidget for PlayerWidget {
                         Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)])
                             .vertical_margin(3)
                             .areas(area);
-                    let mut series_str = Cow::from(series_name.as_str());
-                    if media_item.episode_index.is_some() || media_item.season_index.is_some() {
-                        series_str.to_mut().push(' ');
-                        if let Some(season) = media_item.season_index {
-                            series_str.to_mut().push('S');
-                            series_str.to_mut().push_str(&season.to_string());
-                        }
-                        if let Some(episode) = media_item.episode_index {
-                            series_str.to_mut().push('E');
-                            series_str.to_mut().push_str(&episode.to_string());
-                        }
-                    }
+                    let series_str = match episode_code(media_item) {
+                        Some(code) => Cow::from(format!("{series_name} {code}")),
+                        None => Cow::from(series_name.as_str()),
+                    };
 
                     Paragraph::new(series_str).centered().render(series, buf);
                     Paragraph::new(media_item.name.clone())
@@ -213,34 +1070,107 @@ impl FallibleWidget for PlayerWidget {
                     ])
                     .vertical_margin(3)
                     .areas(area);
-                    let mut series_str = Cow::from(series_name.as_str());
-                    if media_item.episode_index.is_some() || media_item.season_index.is_some() {
-                        series_str.to_mut().push(' ');
-                        if let Some(season) = media_item.season_index {
-                            series_str.to_mut().push('S');
-                            series_str.to_mut().push_str(&season.to_string());
-                        }
-                        if let Some(episode) = media_item.episode_index {
-                            series_str.to_mut().push('E');
-                            series_str.to_mut().push_str(&episode.to_string());
-                        }
-                    }
+                    let series_str = match episode_code(media_item) {
+                        Some(code) => Cow::from(format!("{series_name} {code}")),
+                        None => Cow::from(series_name.as_str()),
+                    };
                     Paragraph::new(series_str).centered().render(series, buf);
-                    Paragraph::new(season_name.clone())
-                        .centered()
-                        .render(season, buf);
+                    let season_name = if media_item.is_special() {
+                        "Specials"
+                    } else {
+                        season_name.as_str()
+                    };
+                    Paragraph::new(season_name).centered().render(season, buf);
                     Paragraph::new(media_item.name.clone())
                         .centered()
                         .render(episode, buf);
                 }
-                _ => {
-                    panic!("unexpected media item type: {media_item:#?}");
+                jellyfin::items::ItemType::Music {
+                    album_id: _,
+                    album,
+                    album_artist,
+                } => {
+                    let area = if self.visualizer {
+                        let [area, bars] =
+                            Layout::vertical([Constraint::Fill(1), Constraint::Percentage(40)])
+                                .areas(area);
+                        render_visualizer(state.position, state.volume, bars, buf);
+                        area
+                    } else {
+                        area
+                    };
+                    let [album_area, artist_area, track_area] = Layout::vertical([
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                    ])
+                    .vertical_margin(3)
+                    .areas(area);
+                    Paragraph::new(album.as_str())
+                        .centered()
+                        .render(album_area, buf);
+                    if let Some(artist) = album_artist {
+                        Paragraph::new(artist.as_str())
+                            .centered()
+                            .render(artist_area, buf);
+                    }
+                    let track = match media_item.episode_index {
+                        Some(index) => Cow::from(format!("{index}. {}", media_item.name)),
+                        None => Cow::from(media_item.name.as_str()),
+                    };
+                    Paragraph::new(track).centered().render(track_area, buf);
+                }
+                item_type => {
+                    let subtitle = match item_type {
+                        jellyfin::items::ItemType::Season {
+                            series_id: _,
+                            series_name,
+                        } => Some(Cow::from(series_name.as_str())),
+                        _ => media_item
+                            .production_year
+                            .map(|year| Cow::from(year.to_string())),
+                    };
+                    let [name_area, subtitle_area] =
+                        Layout::vertical([Constraint::Length(1), Constraint::Length(1)])
+                            .spacing(1)
+                            .flex(Flex::Center)
+                            .areas(area);
+                    Paragraph::new(media_item.name.clone())
+                        .centered()
+                        .render(name_area, buf);
+                    if let Some(subtitle) = subtitle {
+                        Paragraph::new(subtitle)
+                            .centered()
+                            .render(subtitle_area, buf);
+                    }
                 }
             }
         } else {
             Paragraph::new("Nowthing is currently playing").render(area, buf);
         }
         block.render(block_area, buf);
+        if self.preview.is_some()
+            && state.duration > 0.0
+            && let Some(thumbnail) = &mut self.thumbnail
+        {
+            thumbnail.render(
+                &self.picker,
+                state.position / state.duration,
+                progress_area,
+                buf,
+            )?;
+        }
+        match &mut self.overlay {
+            Some((Overlay::Tracks(kind), list)) => {
+                render_tracks(*kind, &state, list, block_area, buf)
+            }
+            Some((Overlay::Queue, list)) => render_queue(&state, list, block_area, buf),
+            Some((Overlay::Chapters, list)) => render_chapters(&state, list, block_area, buf),
+            None => {}
+        }
+        if self.volume_shown.is_some() {
+            render_volume(&state, block_area, buf);
+        }
         Ok(())
     }
 }
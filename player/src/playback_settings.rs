@@ -0,0 +1,25 @@
+use std::ops::DerefMut;
+
+use color_eyre::{Result, eyre::Context};
+use sqlx::SqliteConnection;
+use tokio::sync::Mutex;
+
+pub async fn load(db: &Mutex<SqliteConnection>) -> Result<Option<(i64, f64)>> {
+    let settings = sqlx::query!("select volume, speed from player_settings where id = 0")
+        .fetch_optional(db.lock().await.deref_mut())
+        .await
+        .context("loading playback settings")?;
+    Ok(settings.map(|s| (s.volume, s.speed)))
+}
+
+pub async fn store(db: &Mutex<SqliteConnection>, volume: i64, speed: f64) -> Result<()> {
+    sqlx::query!(
+        "insert or replace into player_settings (id, volume, speed) values (0, ?, ?)",
+        volume,
+        speed
+    )
+    .execute(db.lock().await.deref_mut())
+    .await
+    .context("storing playback settings")?;
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use color_eyre::Result;
+use futures_util::StreamExt;
+use jellyhaj_core::{
+    context::TuiContextProj,
+    keybinds::ResumeCommand,
+    state::{Navigation, NextScreen, global_screen},
+};
+use keybinds::{KeybindEvent, KeybindEventStream};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::Modifier,
+    widgets::{Block, BorderType, Clear, Widget},
+};
+use ratatui_fallible_widget::TermExt;
+
+use crate::format_timestamp;
+
+pub enum ResumeChoice {
+    Resume,
+    StartOver,
+    /// the prompt was left without choosing
+    Leave(Navigation),
+    /// another screen was opened on top of the player
+    Open(NextScreen),
+}
+
+struct ResumePrompt {
+    options: [String; 2],
+    start_over: bool,
+}
+
+impl Widget for &ResumePrompt {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = self.options.iter().map(|o| o.len()).max().unwrap_or(0) as u16 + 4;
+        let area = area.centered(Constraint::Length(width), Constraint::Length(4));
+        Clear.render(area, buf);
+        let block = Block::bordered()
+            .border_type(BorderType::Thick)
+            .title("Resume playback");
+        let inner = block.inner(area);
+        for (i, option) in self.options.iter().enumerate() {
+            let mut area = inner;
+            area.y += i as u16;
+            area.height = 1;
+            option.as_str().render(area, buf);
+            if (i == 1) == self.start_over {
+                for x in area.x..area.right() {
+                    buf[(x, area.y)].set_style(Modifier::REVERSED);
+                }
+            }
+        }
+        block.render(area, buf);
+    }
+}
+
+/// Asks whether playback should continue at `position` seconds or start from the beginning
+pub async fn ask_resume(cx: &mut TuiContextProj<'_>, position: u64) -> Result<ResumeChoice> {
+    let mut widget = ResumePrompt {
+        options: [
+            format!("Resume from {}", format_timestamp(position as f64)),
+            "Start over".to_string(),
+        ],
+        start_over: false,
+    };
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut widget,
+        cx.config.keybinds.resume.clone(),
+        &cx.config.help_prefixes,
+    );
+    loop {
+        cx.term.draw_fallible(&mut events)?;
+        match events.next().await {
+            None => return Ok(ResumeChoice::Leave(Navigation::Exit)),
+            Some(Err(e)) => return Err(e),
+            Some(Ok(KeybindEvent::Render)) => {}
+            Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    return Ok(ResumeChoice::Open(next));
+                }
+            }
+            Some(Ok(KeybindEvent::Command(ResumeCommand::Quit))) => {
+                return Ok(ResumeChoice::Leave(Navigation::Quit));
+            }
+            Some(Ok(KeybindEvent::Command(ResumeCommand::Back))) => {
+                return Ok(ResumeChoice::Leave(Navigation::PopContext));
+            }
+            Some(Ok(KeybindEvent::Command(ResumeCommand::Up | ResumeCommand::Down))) => {
+                events.get_inner().start_over ^= true;
+            }
+            Some(Ok(KeybindEvent::Command(ResumeCommand::Select))) => {
+                return Ok(if events.get_inner().start_over {
+                    ResumeChoice::StartOver
+                } else {
+                    ResumeChoice::Resume
+                });
+            }
+        }
+    }
+}
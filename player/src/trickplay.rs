@@ -0,0 +1,141 @@
+use color_eyre::eyre::{Context, Result};
+use image::DynamicImage;
+use jellyfin::{Auth, JellyfinClient, trickplay::TrickplayInfo};
+use ratatui::{
+    layout::Rect,
+    widgets::{Clear, Widget},
+};
+use ratatui_image::{Image, Resize, picker::Picker, protocol::Protocol};
+
+/// width of the thumbnail in cells
+const THUMBNAIL_WIDTH: u16 = 32;
+
+/// Layout and last fetched tile of the item that was seeked in last
+#[derive(Default)]
+pub struct TrickplayCache {
+    /// item the layout belongs to, the layout is `None` if the item has no trickplay images
+    info: Option<(String, Option<TrickplayInfo>)>,
+    /// item and index of the last fetched tile
+    tile: Option<(String, u32, DynamicImage)>,
+}
+
+/// Thumbnail of `item_id` at `position` seconds, `None` if the item has no trickplay images.
+///
+/// The cache is moved into the future and returned with the result.
+pub async fn load_thumbnail(
+    jellyfin: &JellyfinClient<Auth>,
+    mut cache: TrickplayCache,
+    item_id: String,
+    position: f64,
+) -> (TrickplayCache, Result<Option<DynamicImage>>) {
+    let thumbnail = load_thumbnail_inner(jellyfin, &mut cache, item_id, position).await;
+    (cache, thumbnail)
+}
+
+async fn load_thumbnail_inner(
+    jellyfin: &JellyfinClient<Auth>,
+    cache: &mut TrickplayCache,
+    item_id: String,
+    position: f64,
+) -> Result<Option<DynamicImage>> {
+    let info = match &cache.info {
+        Some((id, info)) if *id == item_id => *info,
+        _ => {
+            let info = jellyfin
+                .get_trickplay_info(&item_id, Some(jellyfin.get_auth().user.id.as_str()))
+                .await
+                .context("requesting trickplay info")?
+                .deserialize()
+                .await
+                .context("deserializing trickplay info")?
+                .smallest();
+            cache.info = Some((item_id.clone(), info));
+            info
+        }
+    };
+    let Some(info) = info else {
+        return Ok(None);
+    };
+    let thumbnail = info.thumbnail(position);
+    let tile = match cache.tile.take() {
+        Some((id, index, tile)) if id == item_id && index == thumbnail.tile => tile,
+        _ => {
+            let tile = jellyfin
+                .get_trickplay_tile(&item_id, info.width, thumbnail.tile)
+                .await
+                .context("fetching trickplay tile")?;
+            tokio::task::spawn_blocking(move || image::load_from_memory(&tile))
+                .await
+                .context("decoding trickplay tile")?
+                .context("parsing trickplay tile")?
+        }
+    };
+    let image = tile.crop_imm(thumbnail.x, thumbnail.y, info.width, info.height);
+    cache.tile = Some((item_id, thumbnail.tile, tile));
+    Ok(Some(image))
+}
+
+pub struct Thumbnail {
+    image: DynamicImage,
+    /// protocol for the image and the size it was created for
+    protocol: Option<(Protocol, Rect)>,
+}
+
+impl Thumbnail {
+    pub fn new(image: DynamicImage) -> Self {
+        Self {
+            image,
+            protocol: None,
+        }
+    }
+
+    /// renders the thumbnail above `progress`, centered on the fraction `ratio` of its width
+    pub fn render(
+        &mut self,
+        picker: &Picker,
+        ratio: f64,
+        progress: Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) -> Result<()> {
+        let (font_width, font_height) = picker.font_size();
+        let width = THUMBNAIL_WIDTH.min(progress.width);
+        let height = (self.image.height() as u64 * width as u64 * font_width as u64)
+            .div_ceil(self.image.width().max(1) as u64 * font_height.max(1) as u64)
+            as u16;
+        let height = height.min(progress.y);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let center = progress.x + (progress.width as f64 * ratio.clamp(0.0, 1.0)) as u16;
+        let x = center
+            .saturating_sub(width / 2)
+            .clamp(progress.x, progress.right() - width);
+        let area = Rect {
+            x,
+            y: progress.y - height,
+            width,
+            height,
+        };
+        let size = Rect {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        };
+        if self
+            .protocol
+            .as_ref()
+            .is_none_or(|(_, created)| *created != size)
+        {
+            let protocol = picker
+                .new_protocol(self.image.clone(), size, Resize::Fit(None))
+                .context("generating thumbnail protocol")?;
+            self.protocol = Some((protocol, size));
+        }
+        Clear.render(area, buf);
+        if let Some((protocol, _)) = &self.protocol {
+            Image::new(protocol).render(area, buf);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use ratatui::{buffer::Buffer, layout::Rect};
+
+/// partial blocks from one to eight eighths of a cell
+const BLOCKS: [&str; 8] = ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+/// Bars that move with the playback position, they stand still while paused.
+///
+/// This is only an animation and does not analyze the audio, the bars scale with the volume.
+pub fn render_visualizer(position: f64, volume: i64, area: Rect, buf: &mut Buffer) {
+    let scale = (volume.clamp(0, 100) as f64 / 100.0).sqrt();
+    let eighths = f64::from(area.height) * 8.0;
+    // bars are two cells wide with a gap of one cell
+    for (bar, x) in (area.x..area.right()).step_by(3).enumerate() {
+        let phase = bar as f64;
+        let level = 0.5
+            + 0.25 * (position * 2.3 + phase * 0.7).sin()
+            + 0.15 * (position * 5.1 + phase * 1.9).sin()
+            + 0.1 * (position * 9.7 + phase * 0.3).sin();
+        let mut remaining = (level.clamp(0.0, 1.0) * scale * eighths) as u32;
+        for y in (area.y..area.bottom()).rev() {
+            if remaining == 0 {
+                break;
+            }
+            let symbol = BLOCKS[remaining.min(8) as usize - 1];
+            remaining = remaining.saturating_sub(8);
+            for x in x..(x + 2).min(area.right()) {
+                buf[(x, y)].set_symbol(symbol);
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use std::pin::Pin;
+
+use color_eyre::{Result, eyre::Context};
+use futures_util::StreamExt;
+use jellyfin::{
+    JellyfinClient, JellyfinVec,
+    items::{GetItemsQuery, MediaItem},
+    playlist::{AddToPlaylistQuery, CreatePlaylist, RemoveFromPlaylistQuery},
+};
+use jellyhaj_core::{
+    context::TuiContext,
+    keybinds::AddToPlaylistCommand,
+    state::{Navigation, NextScreen, PlaylistTarget, ToNavigation, global_screen},
+};
+use keybinds::{KeybindEvent, KeybindEventStream, Text};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::Modifier,
+    widgets::{Block, BorderType, List, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+use ratatui_fallible_widget::{FallibleWidget, TermExt};
+
+async fn fetch_playlists(jellyfin: &JellyfinClient) -> Result<Vec<MediaItem>> {
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    JellyfinVec::collect(async |start| {
+        jellyfin
+            .get_items(&GetItemsQuery {
+                user_id: user_id.into(),
+                start_index: start.into(),
+                limit: 100.into(),
+                recursive: true.into(),
+                include_item_types: "Playlist".into(),
+                enable_images: false.into(),
+                enable_user_data: false.into(),
+                sort_by: "SortName".into(),
+                sort_order: "Ascending".into(),
+                ..Default::default()
+            })
+            .await
+            .context("requesting playlists")?
+            .deserialize()
+            .await
+            .context("deserializing playlists")
+    })
+    .await
+}
+
+pub async fn fetch_add_to_playlist(cx: Pin<&mut TuiContext>, item: String) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    fetch::fetch_screen(
+        "Loading playlists",
+        async move {
+            Ok(fetch_playlists(jellyfin)
+                .await
+                .map(move |playlists| {
+                    Navigation::Replace(NextScreen::AddToPlaylist { item, playlists })
+                })
+                .to_nav())
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+struct AddToPlaylist {
+    playlists: Vec<MediaItem>,
+    new_name: String,
+    state: ListState,
+}
+
+impl AddToPlaylist {
+    fn new_selected(&self) -> bool {
+        self.state.selected() == Some(self.playlists.len())
+    }
+}
+
+impl FallibleWidget for AddToPlaylist {
+    fn render_fallible(&mut self, area: Rect, buf: &mut Buffer) -> Result<()> {
+        let block = Block::bordered()
+            .title("Add to Playlist")
+            .padding(Padding::uniform(1));
+        let [list_area, new_area] = Layout::vertical([Constraint::Min(1), Constraint::Length(3)])
+            .spacing(1)
+            .areas(block.inner(area));
+        block.render(area, buf);
+        let new_selected = self.new_selected();
+        let list = List::new(self.playlists.iter().map(|p| p.name.as_str()))
+            .highlight_style(Modifier::REVERSED);
+        let mut state = if new_selected {
+            ListState::default()
+        } else {
+            self.state
+        };
+        StatefulWidget::render(list, list_area, buf, &mut state);
+        if !new_selected {
+            self.state = state;
+        }
+        Paragraph::new(self.new_name.as_str())
+            .block(
+                Block::bordered()
+                    .title("New playlist")
+                    .border_type(if new_selected {
+                        BorderType::Double
+                    } else {
+                        BorderType::Plain
+                    }),
+            )
+            .render(new_area, buf);
+        Ok(())
+    }
+}
+
+pub async fn show_add_to_playlist(
+    cx: Pin<&mut TuiContext>,
+    item: String,
+    playlists: Vec<MediaItem>,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let mut widget = AddToPlaylist {
+        playlists,
+        new_name: String::new(),
+        state: ListState::default().with_selected(Some(0)),
+    };
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut widget,
+        cx.config.keybinds.add_to_playlist.clone(),
+        &cx.config.help_prefixes,
+    );
+    loop {
+        let new_selected = events.get_inner().new_selected();
+        events.set_text_input(new_selected);
+        cx.term.draw_fallible(&mut events)?;
+        match events.next().await {
+            None => break Ok(Navigation::Exit),
+            Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+            Some(Ok(KeybindEvent::Render)) => {}
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    let playlists = std::mem::take(&mut events.get_inner().playlists);
+                    break Ok(Navigation::Push {
+                        current: NextScreen::AddToPlaylist { item, playlists },
+                        next,
+                    });
+                }
+            }
+            Some(Ok(KeybindEvent::Text(text))) => {
+                let name = &mut events.get_inner().new_name;
+                match text {
+                    Text::Char(c) => name.push(c),
+                    Text::Str(s) => name.push_str(&s),
+                }
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Quit))) => {
+                break Ok(Navigation::Quit);
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Back))) => {
+                break Ok(Navigation::PopContext);
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Delete))) => {
+                if new_selected {
+                    events.get_inner().new_name.pop();
+                }
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Up))) => {
+                let widget = events.get_inner();
+                let current = widget.state.selected().unwrap_or(0);
+                widget.state.select(Some(if current == 0 {
+                    widget.playlists.len()
+                } else {
+                    current - 1
+                }));
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Down))) => {
+                let widget = events.get_inner();
+                let current = widget.state.selected().unwrap_or(0);
+                widget
+                    .state
+                    .select(Some(if current >= widget.playlists.len() {
+                        0
+                    } else {
+                        current + 1
+                    }));
+            }
+            Some(Ok(KeybindEvent::Command(AddToPlaylistCommand::Select))) => {
+                let widget = events.get_inner();
+                let playlist = if new_selected {
+                    if widget.new_name.is_empty() {
+                        continue;
+                    }
+                    PlaylistTarget::New(std::mem::take(&mut widget.new_name))
+                } else if let Some(playlist) = widget
+                    .state
+                    .selected()
+                    .and_then(|i| widget.playlists.get(i))
+                {
+                    PlaylistTarget::Existing(playlist.id.clone())
+                } else {
+                    continue;
+                };
+                break Ok(Navigation::Replace(NextScreen::SendAddToPlaylist {
+                    item,
+                    playlist,
+                }));
+            }
+        }
+    }
+}
+
+pub async fn send_add_to_playlist(
+    cx: Pin<&mut TuiContext>,
+    item: String,
+    playlist: PlaylistTarget,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    fetch::fetch_screen(
+        "Adding to playlist",
+        async {
+            match &playlist {
+                PlaylistTarget::Existing(id) => {
+                    jellyfin
+                        .add_to_playlist(
+                            id,
+                            &AddToPlaylistQuery {
+                                ids: &item,
+                                user_id: user_id.into(),
+                            },
+                        )
+                        .await
+                        .context("adding item to playlist")?;
+                    Ok(Navigation::PopContext)
+                }
+                PlaylistTarget::New(name) => {
+                    let created = jellyfin
+                        .create_playlist(&CreatePlaylist {
+                            name,
+                            ids: &[&item],
+                            user_id: user_id.into(),
+                            media_type: None,
+                            is_public: None,
+                        })
+                        .await
+                        .context("creating playlist")?
+                        .deserialize()
+                        .await
+                        .context("deserializing created playlist")?;
+                    let playlist = fetch::fetch_item(jellyfin, &created.id).await?;
+                    // show the new playlist in place of the picker
+                    Ok(Navigation::Replace(NextScreen::FetchItemListDetails(
+                        playlist,
+                    )))
+                }
+            }
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+pub async fn send_remove_from_playlist(
+    cx: Pin<&mut TuiContext>,
+    playlist: String,
+    entry: String,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    fetch::fetch_screen(
+        "Removing from playlist",
+        async {
+            jellyfin
+                .remove_from_playlist(&playlist, &RemoveFromPlaylistQuery { entry_ids: &entry })
+                .await
+                .context("removing item from playlist")?;
+            Ok(Navigation::PopContext)
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
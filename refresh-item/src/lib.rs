@@ -1,13 +1,21 @@
-use std::pin::Pin;
+use std::{pin::Pin, time::Duration};
 
 use checkbox::Checkbox;
-use color_eyre::{Result, eyre::Context};
+use color_eyre::{
+    Result,
+    eyre::{Context, OptionExt},
+};
+use fetch::Progress;
 use futures_util::StreamExt;
-use jellyfin::items::{RefreshItemQuery, RefreshMode};
+use jellyfin::{
+    items::{RefreshItemQuery, RefreshMode},
+    scheduled_tasks::{GetScheduledTasksQuery, TaskState, known_keys},
+};
 use jellyhaj_core::{
+    config::Icons,
     context::TuiContext,
     keybinds::RefreshItemCommand,
-    state::{Navigation, NextScreen},
+    state::{Navigation, NextScreen, global_screen},
 };
 use keybinds::{KeybindEvent, KeybindEventStream};
 use ratatui::{
@@ -17,6 +25,10 @@ use ratatui::{
     widgets::{Block, BorderType, Clear, Padding, Widget, WidgetRef},
 };
 use ratatui_fallible_widget::TermExt;
+use tokio::{
+    sync::watch,
+    time::{MissedTickBehavior, interval},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum Action {
@@ -51,12 +63,13 @@ enum Active {
     Refresh,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct RefreshItem {
     action: Action,
     active: Active,
     replace_images: bool,
     replace_trickplay: bool,
+    icons: Icons,
 }
 
 impl RefreshItem {
@@ -92,7 +105,13 @@ impl RefreshItem {
 
 pub async fn show_refresh_item(cx: Pin<&mut TuiContext>, item: String) -> Result<Navigation> {
     let cx = cx.project();
-    let mut widget = RefreshItem::default();
+    let mut widget = RefreshItem {
+        action: Action::default(),
+        active: Active::default(),
+        replace_images: false,
+        replace_trickplay: false,
+        icons: cx.config.icons.clone(),
+    };
     let mut events = KeybindEventStream::new(
         cx.events,
         &mut widget,
@@ -106,10 +125,23 @@ pub async fn show_refresh_item(cx: Pin<&mut TuiContext>, item: String) -> Result
             Some(Err(e)) => return Err(e),
             Some(Ok(KeybindEvent::Render)) => {}
             Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
-            Some(Ok(KeybindEvent::Command(RefreshItemCommand::Quit))) => {
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    return Ok(Navigation::Push {
+                        current: NextScreen::RefreshItem(item),
+                        next,
+                    });
+                }
+            }
+            Some(Ok(KeybindEvent::Command(
+                cmd @ (RefreshItemCommand::Quit | RefreshItemCommand::Back),
+            ))) => {
                 let widget = events.get_inner();
                 if let Active::ActionSelection(_) = widget.active {
                     widget.active = Active::Action
+                } else if let RefreshItemCommand::Quit = cmd {
+                    return Ok(Navigation::Quit);
                 } else {
                     return Ok(Navigation::PopContext);
                 }
@@ -215,24 +247,23 @@ impl Widget for &RefreshItem {
                 });
         let mut action_inner = action_block.inner(action_area);
         let arrow_char = if let Active::ActionSelection(_) = self.active {
-            '⮙'
+            self.icons.arrow_up
         } else {
-            '⮛'
+            self.icons.arrow_down
         };
         buf[(action_inner.x + action_inner.width - 1, action_inner.y)].set_char(arrow_char);
         action_inner.width -= 2;
         self.action.to_str().render_ref(action_inner, buf);
         action_block.render(action_area, buf);
         if self.action != Action::NewUpdated {
-            Checkbox::new(self.active == Active::ReplaceImages, self.replace_images).render_with(
-                replace_images_area,
-                buf,
-                "Replace existing images",
-            );
+            Checkbox::new(self.active == Active::ReplaceImages, self.replace_images)
+                .with_marks(self.icons.checkbox_set, self.icons.checkbox_unset)
+                .render_with(replace_images_area, buf, "Replace existing images");
             Checkbox::new(
                 self.active == Active::ReplaceTrickplay,
                 self.replace_trickplay,
             )
+            .with_marks(self.icons.checkbox_set, self.icons.checkbox_unset)
             .render_with(
                 replace_trickplay_area,
                 buf,
@@ -305,3 +336,66 @@ pub async fn refresh_screen(
     )
     .await
 }
+
+/// time between requests for the progress of a library scan
+const SCAN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// starts the scheduled task scanning all libraries, shows its progress and returns to the
+/// previous screen once it is finished
+pub async fn scan_library(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    let (progress, progress_receiver) = watch::channel(Progress::message("Starting library scan"));
+    fetch::fetch_screen_progress(
+        async move {
+            let task = jellyfin
+                .get_scheduled_tasks(&GetScheduledTasksQuery::default())
+                .await
+                .context("requesting scheduled tasks")?
+                .deserialize()
+                .await
+                .context("deserializing scheduled tasks")?
+                .into_iter()
+                .find(|task| task.key == known_keys::REFRESH_LIBRARY)
+                .ok_or_eyre("the server has no library scan task")?;
+            jellyfin
+                .run_scheduled_task(&task.id)
+                .await
+                .context("starting library scan")?;
+            // the task may still be idle right after starting it, so it is finished once its
+            // last result changed
+            let mut poll = interval(SCAN_POLL_INTERVAL);
+            poll.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                poll.tick().await;
+                let current = jellyfin
+                    .get_scheduled_task(&task.id)
+                    .await
+                    .context("requesting library scan progress")?
+                    .deserialize()
+                    .await
+                    .context("deserializing library scan progress")?;
+                match current.state {
+                    TaskState::Idle
+                        if current.last_execution_result != task.last_execution_result =>
+                    {
+                        break Ok(Navigation::PopContext);
+                    }
+                    TaskState::Idle => {}
+                    TaskState::Running | TaskState::Cancelling => {
+                        progress.send_replace(Progress::ratio(
+                            "Scanning libraries",
+                            current.current_progress_percentage.unwrap_or_default() / 100.0,
+                        ));
+                    }
+                }
+            }
+        },
+        progress_receiver,
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
@@ -0,0 +1,234 @@
+use std::pin::Pin;
+
+use color_eyre::{Result, eyre::Context};
+use futures_util::StreamExt;
+use jellyfin::{
+    JellyfinClient,
+    connect::StatusError,
+    subtitles::{MediaStreamType, RemoteSubtitleInfo, SearchRemoteSubtitlesQuery},
+};
+use jellyhaj_core::{
+    context::TuiContext,
+    keybinds::RemoteSubtitlesCommand,
+    state::{Navigation, NextScreen, ToNavigation, global_screen},
+};
+use keybinds::{KeybindEvent, KeybindEventStream};
+use player_core::{Command, PlayerHandle};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    widgets::{Block, List, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+use ratatui_fallible_widget::{FallibleWidget, TermExt};
+
+/// an empty list or not found means that the server has no subtitle provider
+async fn search_subtitles(
+    jellyfin: &JellyfinClient,
+    item: &str,
+    language: &str,
+) -> Result<Vec<RemoteSubtitleInfo>> {
+    match jellyfin
+        .search_remote_subtitles(item, language, &SearchRemoteSubtitlesQuery::default())
+        .await
+    {
+        Ok(res) => res
+            .deserialize()
+            .await
+            .context("deserializing remote subtitles"),
+        Err(e) if e.downcast_ref::<StatusError>().is_some_and(|e| e.0 == 404) => Ok(Vec::new()),
+        Err(e) => Err(e).context("searching remote subtitles"),
+    }
+}
+
+pub async fn fetch_remote_subtitles(cx: Pin<&mut TuiContext>, item: String) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    let language = cx.config.subtitle_search_language.as_str();
+    fetch::fetch_screen(
+        "Searching subtitles",
+        async move {
+            Ok(search_subtitles(jellyfin, &item, language)
+                .await
+                .map(move |subtitles| {
+                    Navigation::Replace(NextScreen::RemoteSubtitles { item, subtitles })
+                })
+                .to_nav())
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+fn describe(subtitle: &RemoteSubtitleInfo) -> String {
+    let mut text = String::new();
+    if let Some(language) = &subtitle.language {
+        text.push_str(language);
+        text.push(' ');
+    }
+    text.push_str(subtitle.name.as_deref().unwrap_or(&subtitle.id));
+    if let Some(format) = &subtitle.format {
+        text.push_str(" [");
+        text.push_str(format);
+        text.push(']');
+    }
+    if let Some(provider) = &subtitle.provider_name {
+        text.push_str(" from ");
+        text.push_str(provider);
+    }
+    if let Some(downloads) = subtitle.download_count {
+        text.push_str(&format!(", {downloads} downloads"));
+    }
+    if subtitle.is_hash_match == Some(true) {
+        text.push_str(", exact match");
+    }
+    text
+}
+
+struct RemoteSubtitles {
+    subtitles: Vec<RemoteSubtitleInfo>,
+    state: ListState,
+}
+
+impl FallibleWidget for RemoteSubtitles {
+    fn render_fallible(&mut self, area: Rect, buf: &mut Buffer) -> Result<()> {
+        let block = Block::bordered()
+            .title("Download Subtitles")
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if self.subtitles.is_empty() {
+            Paragraph::new("No subtitles found, the server might not have a subtitle provider")
+                .render(inner, buf);
+        } else {
+            let list =
+                List::new(self.subtitles.iter().map(describe)).highlight_style(Modifier::REVERSED);
+            StatefulWidget::render(list, inner, buf, &mut self.state);
+        }
+        Ok(())
+    }
+}
+
+pub async fn show_remote_subtitles(
+    cx: Pin<&mut TuiContext>,
+    item: String,
+    subtitles: Vec<RemoteSubtitleInfo>,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let mut widget = RemoteSubtitles {
+        subtitles,
+        state: ListState::default().with_selected(Some(0)),
+    };
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut widget,
+        cx.config.keybinds.remote_subtitles.clone(),
+        &cx.config.help_prefixes,
+    );
+    loop {
+        cx.term.draw_fallible(&mut events)?;
+        match events.next().await {
+            None => break Ok(Navigation::Exit),
+            Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+            Some(Ok(KeybindEvent::Render)) => {}
+            Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    let subtitles = std::mem::take(&mut events.get_inner().subtitles);
+                    break Ok(Navigation::Push {
+                        current: NextScreen::RemoteSubtitles { item, subtitles },
+                        next,
+                    });
+                }
+            }
+            Some(Ok(KeybindEvent::Command(RemoteSubtitlesCommand::Quit))) => {
+                break Ok(Navigation::Quit);
+            }
+            Some(Ok(KeybindEvent::Command(RemoteSubtitlesCommand::Back))) => {
+                break Ok(Navigation::PopContext);
+            }
+            Some(Ok(KeybindEvent::Command(RemoteSubtitlesCommand::Up))) => {
+                events.get_inner().state.select_previous();
+            }
+            Some(Ok(KeybindEvent::Command(RemoteSubtitlesCommand::Down))) => {
+                events.get_inner().state.select_next();
+            }
+            Some(Ok(KeybindEvent::Command(RemoteSubtitlesCommand::Select))) => {
+                let widget = events.get_inner();
+                if let Some(subtitle) = widget
+                    .state
+                    .selected()
+                    .and_then(|i| widget.subtitles.get(i))
+                {
+                    let subtitle = subtitle.id.clone();
+                    break Ok(Navigation::Replace(NextScreen::DownloadRemoteSubtitles {
+                        item,
+                        subtitle,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+pub async fn download_remote_subtitles(
+    cx: Pin<&mut TuiContext>,
+    item: String,
+    subtitle: String,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    let mpv_handle = cx.mpv_handle;
+    fetch::fetch_screen(
+        "Downloading subtitles",
+        async {
+            jellyfin
+                .download_remote_subtitles(&item, &subtitle)
+                .await
+                .context("downloading remote subtitles")?;
+            reload_subtitles(jellyfin, mpv_handle, &item)
+                .await
+                .context("reloading subtitle tracks")?;
+            Ok(Navigation::PopContext)
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+/// adds the external subtitles of the item to the player, it skips those that are already loaded
+/// and does nothing if the item is not playing
+async fn reload_subtitles(
+    jellyfin: &JellyfinClient,
+    mpv_handle: &PlayerHandle,
+    item: &str,
+) -> Result<()> {
+    let streams = jellyfin
+        .get_media_streams(item)
+        .await
+        .context("fetching media streams")?
+        .deserialize()
+        .await
+        .context("deserializing media streams")?;
+    for stream in streams.media_streams {
+        if stream.stream_type == MediaStreamType::Subtitle && stream.is_external {
+            let title = stream
+                .display_title
+                .clone()
+                .unwrap_or_else(|| format!("External {}", stream.index));
+            mpv_handle.send(Command::AddSubtitle {
+                item_id: item.to_string(),
+                uri: jellyfin.get_subtitle_uri(item, &stream)?.to_string(),
+                title,
+            });
+        }
+    }
+    Ok(())
+}
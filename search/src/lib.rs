@@ -0,0 +1,366 @@
+use std::{
+    pin::{Pin, pin},
+    time::Duration,
+};
+
+use color_eyre::eyre::{Context, Result};
+use entries::{
+    entry::{Entry, EntryInner},
+    grid::EntryGrid,
+    image::available::ImagesAvailable,
+    mouse::MouseNavigation,
+};
+use futures_util::{
+    FutureExt, StreamExt,
+    future::{Fuse, FusedFuture},
+};
+use jellyfin::{
+    Auth, JellyfinClient,
+    items::{GetItemsQuery, MediaItem},
+};
+use jellyhaj_core::{
+    config::Theme,
+    context::TuiContext,
+    entries::{EntryExt, UserDataChange, change_user_data},
+    keybinds::SearchCommand,
+    state::{Navigation, NextScreen, SearchKind, global_screen},
+};
+use keybinds::{ClickTracker, KeybindEvent, KeybindEventStream, Text};
+use ratatui::{
+    layout::{Constraint, Layout},
+    widgets::{Block, BorderType, Paragraph, Widget, Wrap},
+};
+use ratatui_fallible_widget::{FallibleWidget, TermExt};
+use tokio::time::{Instant, sleep_until};
+use tracing::{debug, instrument, warn};
+
+/// time without input after which the search is sent
+const DEBOUNCE: Duration = Duration::from_millis(400);
+const RESULT_LIMIT: u32 = 100;
+
+const KINDS: [SearchKind; 5] = [
+    SearchKind::All,
+    SearchKind::Movies,
+    SearchKind::Series,
+    SearchKind::Episodes,
+    SearchKind::Music,
+];
+
+fn kind_name(kind: SearchKind) -> &'static str {
+    match kind {
+        SearchKind::All => "all",
+        SearchKind::Movies => "movies",
+        SearchKind::Series => "series",
+        SearchKind::Episodes => "episodes",
+        SearchKind::Music => "music",
+    }
+}
+
+fn item_types(kind: SearchKind) -> &'static str {
+    match kind {
+        SearchKind::All => "Movie, Series, Episode, MusicAlbum, Audio, Playlist",
+        SearchKind::Movies => "Movie",
+        SearchKind::Series => "Series",
+        SearchKind::Episodes => "Episode",
+        SearchKind::Music => "MusicAlbum, Audio",
+    }
+}
+
+fn cycle_kind(kind: SearchKind, forward: bool) -> SearchKind {
+    let index = KINDS.iter().position(|k| *k == kind).unwrap_or(0);
+    let next = if forward {
+        (index + 1) % KINDS.len()
+    } else {
+        (index + KINDS.len() - 1) % KINDS.len()
+    };
+    KINDS[next]
+}
+
+#[instrument(skip(jellyfin))]
+async fn search_items(
+    jellyfin: &JellyfinClient<Auth>,
+    term: String,
+    kind: SearchKind,
+) -> Result<Vec<MediaItem>> {
+    if term.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let items = jellyfin
+        .get_items(&GetItemsQuery {
+            user_id: jellyfin.get_auth().user.id.as_str().into(),
+            limit: RESULT_LIMIT.into(),
+            search_term: term.as_str().into(),
+            recursive: true.into(),
+            include_item_types: item_types(kind).into(),
+            enable_images: true.into(),
+            enable_image_types: "Thumb, Backdrop, Primary".into(),
+            image_type_limit: 1.into(),
+            enable_user_data: true.into(),
+            sort_by: "SortName".into(),
+            sort_order: "Ascending".into(),
+            ..Default::default()
+        })
+        .await
+        .context("searching items")?
+        .deserialize()
+        .await
+        .context("deserializing search results")?;
+    Ok(items.items)
+}
+
+struct SearchWidget {
+    term: String,
+    kind: SearchKind,
+    /// items shown in the grid, kept to restore the results when returning to the search
+    items: Vec<MediaItem>,
+    grid: EntryGrid,
+    searching: bool,
+    /// error of the last search, shown instead of the results until a search succeeds
+    error: Option<String>,
+    theme: Theme,
+}
+
+impl FallibleWidget for SearchWidget {
+    fn render_fallible(
+        &mut self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) -> Result<()> {
+        let [input_area, grid_area] =
+            Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+        let title = if self.searching {
+            format!("Search {} (searching…)", kind_name(self.kind))
+        } else {
+            format!("Search {}", kind_name(self.kind))
+        };
+        Paragraph::new(self.term.as_str())
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Double)
+                    .title(title),
+            )
+            .render(input_area, buf);
+        if let Some(error) = &self.error {
+            Paragraph::new(error.as_str())
+                .block(
+                    Block::bordered()
+                        .border_style(self.theme.error)
+                        .title("Search failed"),
+                )
+                .wrap(Wrap::default())
+                .render(grid_area, buf);
+            Ok(())
+        } else {
+            self.grid.render_fallible(grid_area, buf)
+        }
+    }
+}
+
+/// Search screen, `term`, `kind` and `items` restore earlier results
+pub async fn show_search(
+    cx: Pin<&mut TuiContext>,
+    term: String,
+    kind: SearchKind,
+    items: Vec<MediaItem>,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let images_available = ImagesAvailable::new();
+    let entry_cx = cx.entry_context(&images_available);
+    let mut widget = SearchWidget {
+        term,
+        kind,
+        items: Vec::new(),
+        grid: EntryGrid::new(
+            Vec::new(),
+            "Results".to_string(),
+            cx.image_picker.clone(),
+            cx.config.image_layout,
+            cx.config.scroll_style,
+            cx.config.prefetch_margin,
+        ),
+        searching: false,
+        error: None,
+        theme: cx.config.theme,
+    };
+    let mut results = Some(items);
+    let mut search_at: Option<Instant> = None;
+    let mut search = pin!(Fuse::terminated());
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut widget,
+        cx.config.keybinds.search.clone(),
+        &cx.config.help_prefixes,
+    );
+    events.set_text_input(true);
+    events.set_mouse_input(cx.config.capture_mouse());
+    let mut clicks = ClickTracker::new(cx.config.single_click_play);
+    loop {
+        if let Some(items) = results.take() {
+            let entries = items
+                .iter()
+                .cloned()
+                .filter_map(|item| {
+                    Entry::from_media_item(item, &entry_cx, &cx.config.icons).transpose()
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let widget = events.get_inner();
+            widget.grid = EntryGrid::new(
+                entries,
+                "Results".to_string(),
+                cx.image_picker.clone(),
+                cx.config.image_layout,
+                cx.config.scroll_style,
+                cx.config.prefetch_margin,
+            );
+            widget.items = items;
+        }
+        cx.term.draw_fallible(&mut events)?;
+        let cmd = tokio::select! {
+            _ = images_available.wait_available() => {continue}
+            _ = sleep_until(search_at.unwrap_or_else(Instant::now)), if search_at.is_some() => {
+                search_at = None;
+                let widget = events.get_inner();
+                widget.searching = true;
+                search.set(search_items(cx.jellyfin, widget.term.clone(), widget.kind).fuse());
+                continue
+            }
+            found = &mut search, if !search.is_terminated() => {
+                let widget = events.get_inner();
+                widget.searching = false;
+                match found {
+                    Ok(found) => {
+                        widget.error = None;
+                        results = Some(found);
+                    }
+                    Err(e) => {
+                        warn!("search failed: {e:?}");
+                        widget.error = Some(format!("{e:#}"));
+                        results = Some(Vec::new());
+                    }
+                }
+                continue
+            }
+            term = events.next() => {
+                match term {
+                    Some(Ok(KeybindEvent::Command(cmd))) => cmd,
+                    Some(Ok(KeybindEvent::Render)) => continue,
+                    Some(Ok(KeybindEvent::Text(text))) => {
+                        let term = &mut events.get_inner().term;
+                        match text {
+                            Text::Char(c) => term.push(c),
+                            Text::Str(s) => term.push_str(&s),
+                        }
+                        search_at = Some(Instant::now() + DEBOUNCE);
+                        continue
+                    }
+                    Some(Ok(KeybindEvent::Mouse(event))) => {
+                        let hover = cx.config.focus_follows_mouse;
+                        if events.get_inner().grid.handle_mouse(event, hover, &mut clicks) {
+                            SearchCommand::Play
+                        } else {
+                            continue
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: current(events.get_inner()),
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
+                    Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+                    None => break Ok(Navigation::PopContext)
+                }
+            }
+        };
+        debug!("received command {cmd:?}");
+        match cmd {
+            SearchCommand::Quit => {
+                break Ok(Navigation::Quit);
+            }
+            SearchCommand::Back => {
+                break Ok(Navigation::PopContext);
+            }
+            SearchCommand::Delete => {
+                if events.get_inner().term.pop().is_some() {
+                    search_at = Some(Instant::now() + DEBOUNCE);
+                }
+            }
+            SearchCommand::NextKind | SearchCommand::PrevKind => {
+                let widget = events.get_inner();
+                widget.kind = cycle_kind(widget.kind, matches!(cmd, SearchCommand::NextKind));
+                search_at = Some(Instant::now());
+            }
+            SearchCommand::ToggleFavorite => {
+                let widget = events.get_inner();
+                if let Some(entry) = widget.grid.get_mut() {
+                    let change = UserDataChange::ToggleFavorite;
+                    if let Err(e) =
+                        change_user_data(cx.jellyfin, entry, change, &cx.config.icons).await
+                    {
+                        break Ok(Navigation::Push {
+                            current: current(events.get_inner()),
+                            next: NextScreen::Error(e),
+                        });
+                    }
+                    if let EntryInner::Item(changed) = entry.inner()
+                        && let Some(item) = widget.items.iter_mut().find(|i| i.id == changed.id)
+                    {
+                        item.user_data = changed.user_data.clone();
+                    }
+                }
+            }
+            SearchCommand::Up => {
+                events.get_inner().grid.up();
+            }
+            SearchCommand::Down => {
+                events.get_inner().grid.down();
+            }
+            SearchCommand::Left => {
+                events.get_inner().grid.left();
+            }
+            SearchCommand::Right => {
+                events.get_inner().grid.right();
+            }
+            SearchCommand::Open => {
+                if let Some(entry) = events.get_inner().grid.get() {
+                    let next = entry.open();
+                    break Ok(Navigation::Push {
+                        current: current(events.get_inner()),
+                        next,
+                    });
+                }
+            }
+            SearchCommand::Play => {
+                if let Some(entry) = events.get_inner().grid.get()
+                    && let Some(next) = entry.play()
+                {
+                    break Ok(Navigation::Push {
+                        current: current(events.get_inner()),
+                        next,
+                    });
+                }
+            }
+            SearchCommand::Activate => {
+                if let Some(entry) = events.get_inner().grid.get() {
+                    let next = entry.activate(cx.config.default_item_action);
+                    break Ok(Navigation::Push {
+                        current: current(events.get_inner()),
+                        next,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Screen restoring the current results after returning to the search
+fn current(widget: &mut SearchWidget) -> NextScreen {
+    NextScreen::SearchResults {
+        term: widget.term.clone(),
+        kind: widget.kind,
+        items: std::mem::take(&mut widget.items),
+    }
+}
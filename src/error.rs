@@ -4,26 +4,35 @@ use ansi_to_tui::IntoText;
 use color_eyre::eyre::{Context, Report, Result};
 use futures_util::StreamExt;
 use jellyhaj_core::{
+    config::Theme,
     keybinds::{ErrorCommand, Keybinds},
-    state::{Navigation, NextScreen},
+    state::{Navigation, NextScreen, global_screen},
 };
 use keybinds::{KeybindEvent, KeybindEventStream, KeybindEvents};
 use ratatui::{
     DefaultTerminal,
     layout::Margin,
+    text::{Line, Span, Text},
     widgets::{
         Block, Padding, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget,
-        Widget,
+        Widget, Wrap,
     },
 };
 use ratatui_fallible_widget::{FallibleWidget, TermExt};
+use serde_json::Value;
+
+/// header of the section containing a response that did not match the expected type
+const JSON_HEADER: &str = "Value:";
 
 struct ErrorWidget {
     text: String,
-    pos_x: usize,
-    pos_y: usize,
-    scroll_x: usize,
-    scroll_y: usize,
+    theme: Theme,
+    /// first visible line of the wrapped text
+    pos: usize,
+    /// last valid value of `pos`
+    scroll: usize,
+    /// number of visible lines
+    page: usize,
 }
 
 impl FallibleWidget for ErrorWidget {
@@ -32,41 +41,113 @@ impl FallibleWidget for ErrorWidget {
         area: ratatui::prelude::Rect,
         buf: &mut ratatui::prelude::Buffer,
     ) -> Result<()> {
-        let text = self
-            .text
-            .to_text()
-            .context("handling color eyre error message")?;
-        let width = text.width();
-        let height = text.height();
-        let mut text = Paragraph::new(text).block(
-            Block::bordered()
-                .title("Error encountered")
-                .padding(Padding::uniform(1)),
-        );
-        self.scroll_x = width.saturating_sub(area.width as usize);
-        self.scroll_y = height.saturating_sub(area.height as usize);
-        self.pos_x = min(self.scroll_x.saturating_sub(1), self.pos_x);
-        self.pos_y = min(self.scroll_y.saturating_sub(1), self.pos_y);
-        text = std::mem::take(&mut text).scroll((self.pos_y as u16, self.pos_x as u16));
-        text.render(area, buf);
-        if self.scroll_y > 0 {
+        let block = Block::bordered()
+            .title("Error encountered")
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        let text = Paragraph::new(error_text(&self.text, self.theme)?).wrap(Wrap { trim: false });
+        let height = text.line_count(inner.width);
+        self.page = inner.height as usize;
+        self.scroll = height.saturating_sub(self.page);
+        self.pos = min(self.scroll, self.pos);
+        text.block(block)
+            .scroll((self.pos as u16, 0))
+            .render(area, buf);
+        if self.scroll > 0 {
             Scrollbar::new(ScrollbarOrientation::VerticalRight).render(
-                area.inner(Margin::new(0, 2)),
-                buf,
-                &mut ScrollbarState::new(self.scroll_y).position(self.pos_y),
-            );
-        }
-        if self.scroll_x > 0 {
-            Scrollbar::new(ScrollbarOrientation::HorizontalBottom).render(
-                area.inner(Margin::new(2, 0)),
+                area.inner(Margin::new(0, 1)),
                 buf,
-                &mut ScrollbarState::new(self.scroll_x).position(self.pos_x),
+                &mut ScrollbarState::new(height)
+                    .viewport_content_length(self.page)
+                    .position(self.pos),
             );
         }
         Ok(())
     }
 }
 
+/// the report with its json section highlighted, other sections keep their ansi colors
+fn error_text(report: &str, theme: Theme) -> Result<Text<'_>> {
+    let mut text = report
+        .to_text()
+        .context("handling color eyre error message")?;
+    let content = |line: &Line<'_>| -> String {
+        line.spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect()
+    };
+    let Some(header) = text
+        .lines
+        .iter()
+        .position(|line| content(line).trim() == JSON_HEADER)
+    else {
+        return Ok(text);
+    };
+    let start = header + 1;
+    let end = text.lines[start..]
+        .iter()
+        .position(|line| !content(line).starts_with(char::is_whitespace))
+        .map_or(text.lines.len(), |i| start + i);
+    let json: String = text.lines[start..end]
+        .iter()
+        .map(|line| content(line) + "\n")
+        .collect();
+    if let Ok(value) = serde_json::from_str::<Value>(&json) {
+        let mut lines = Vec::new();
+        json_lines(&value, 4, Vec::new(), "", theme, &mut lines);
+        text.lines.splice(start..end, lines);
+    }
+    Ok(text)
+}
+
+/// pretty prints `value` starting with `prefix`, keys use the accent color
+fn json_lines(
+    value: &Value,
+    indent: usize,
+    prefix: Vec<Span<'static>>,
+    suffix: &'static str,
+    theme: Theme,
+    lines: &mut Vec<Line<'static>>,
+) {
+    let line = |mut spans: Vec<Span<'static>>, end: Vec<Span<'static>>| {
+        spans.insert(0, Span::raw(" ".repeat(indent)));
+        spans.extend(end);
+        Line::from(spans)
+    };
+    let (open, close, entries): (_, _, Vec<(Option<&String>, &Value)>) = match value {
+        Value::Object(map) => ("{", "}", map.iter().map(|(k, v)| (Some(k), v)).collect()),
+        Value::Array(values) => ("[", "]", values.iter().map(|v| (None, v)).collect()),
+        scalar => {
+            let mut spans = prefix;
+            spans.push(Span::raw(scalar.to_string()));
+            lines.push(line(spans, vec![Span::raw(suffix)]));
+            return;
+        }
+    };
+    if entries.is_empty() {
+        lines.push(line(
+            prefix,
+            vec![Span::raw(format!("{open}{close}{suffix}"))],
+        ));
+        return;
+    }
+    lines.push(line(prefix, vec![Span::raw(open)]));
+    let count = entries.len();
+    for (i, (key, value)) in entries.into_iter().enumerate() {
+        let prefix = match key {
+            Some(key) => vec![
+                Span::styled(Value::from(key.as_str()).to_string(), theme.accent),
+                Span::raw(": "),
+            ],
+            None => Vec::new(),
+        };
+        let suffix = if i + 1 == count { "" } else { "," };
+        json_lines(value, indent + 2, prefix, suffix, theme, lines);
+    }
+    lines.push(line(Vec::new(), vec![Span::raw(close), Span::raw(suffix)]));
+}
+
 pub trait ResultDisplayExt<T> {
     fn display_error(
         self,
@@ -74,6 +155,7 @@ pub trait ResultDisplayExt<T> {
         events: &mut KeybindEvents,
         keybinds: &Keybinds,
         help_prefixes: &[String],
+        theme: Theme,
     ) -> impl Future<Output = Option<T>>;
 }
 
@@ -84,12 +166,14 @@ impl<T> ResultDisplayExt<T> for Result<T> {
         events: &mut KeybindEvents,
         keybinds: &Keybinds,
         help_prefixes: &[String],
+        theme: Theme,
     ) -> Option<T> {
         match self {
             Err(e) => {
-                if let Some(e) = display_error(term, events, keybinds, help_prefixes, e)
-                    .await
-                    .err()
+                if let Some(e) =
+                    display_error(term, events, keybinds, help_prefixes, theme, e, None)
+                        .await
+                        .err()
                 {
                     tracing::error!("Error displaying error: {e:?}");
                 }
@@ -105,15 +189,17 @@ pub async fn display_error(
     events: &mut KeybindEvents,
     keybinds: &Keybinds,
     help_prefixes: &[String],
+    theme: Theme,
     e: Report,
+    mut retry: Option<NextScreen>,
 ) -> Result<Navigation> {
     tracing::error!("Error encountered: {e:?}");
     let mut widget = ErrorWidget {
         text: format!("{e:?}"),
-        pos_x: 0,
-        pos_y: 0,
-        scroll_x: 0,
-        scroll_y: 0,
+        theme,
+        pos: 0,
+        scroll: 0,
+        page: 0,
     };
     let mut events =
         KeybindEventStream::new(events, &mut widget, keybinds.error.clone(), help_prefixes);
@@ -122,30 +208,43 @@ pub async fn display_error(
         match events.next().await {
             Some(Ok(KeybindEvent::Render)) => continue,
             Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::error(e, retry),
+                        next,
+                    });
+                }
+            }
             Some(Ok(KeybindEvent::Command(command))) => match command {
-                ErrorCommand::Quit => break Ok(Navigation::PopContext),
+                ErrorCommand::Quit => break Ok(Navigation::Quit),
+                ErrorCommand::Back => break Ok(Navigation::PopContext),
                 ErrorCommand::Kill => break Ok(Navigation::Exit),
                 ErrorCommand::Up => {
-                    events.get_inner().pos_y = events.get_inner().pos_y.saturating_sub(1);
+                    let widget = events.get_inner();
+                    widget.pos = widget.pos.saturating_sub(1);
                 }
                 ErrorCommand::Down => {
-                    events.get_inner().pos_y = min(
-                        events.get_inner().scroll_y.saturating_sub(1),
-                        events.get_inner().pos_y + 1,
-                    );
+                    let widget = events.get_inner();
+                    widget.pos = min(widget.scroll, widget.pos + 1);
+                }
+                ErrorCommand::PageUp => {
+                    let widget = events.get_inner();
+                    widget.pos = widget.pos.saturating_sub(widget.page.max(1));
                 }
-                ErrorCommand::Left => {
-                    events.get_inner().pos_x = events.get_inner().pos_x.saturating_sub(1);
+                ErrorCommand::PageDown => {
+                    let widget = events.get_inner();
+                    widget.pos = min(widget.scroll, widget.pos + widget.page.max(1));
                 }
-                ErrorCommand::Right => {
-                    events.get_inner().pos_x = min(
-                        events.get_inner().scroll_x.saturating_sub(1),
-                        events.get_inner().pos_x + 1,
-                    )
+                ErrorCommand::Retry => {
+                    if let Some(retry) = retry.take() {
+                        break Ok(Navigation::Replace(retry));
+                    }
                 }
                 ErrorCommand::ShowLogs => {
                     break Ok(Navigation::Push {
-                        current: NextScreen::Error(e),
+                        current: NextScreen::error(e, retry),
                         next: NextScreen::Logs,
                     });
                 }
@@ -1,4 +1,5 @@
 use std::{
+    io::stdout,
     path::PathBuf,
     pin::{Pin, pin},
     sync::Arc,
@@ -6,50 +7,93 @@ use std::{
 
 use color_eyre::{Result, eyre::Context};
 use config::init_config;
-use entries::image::cache::ImageProtocolCache;
+use crossterm::{event::EnableMouseCapture, execute};
+use entries::{entry::entry_image_size, image::cache::ImageProtocolCache};
 use futures_util::StreamExt;
 use jellyfin::{JellyfinClient, socket::JellyfinWebSocket};
 use jellyhaj_core::{
-    config::Config,
+    config::{Config, QuitAction},
     context::TuiContext,
     keybinds::UnsupportedItemCommand,
-    state::{Navigation, NextScreen, State},
+    state::{Navigation, NextScreen, SearchKind, State, ToNavigation, global_screen},
 };
 use keybinds::{KeybindEvent, KeybindEventStream, KeybindEvents};
-use player_core::OwnedPlayerHandle;
-use player_jellyfin::player_jellyfin;
+use player_core::{OwnedPlayerHandle, PlayerHandle};
+use player_jellyfin::{player_jellyfin, prefetch_next};
 use ratatui::{
     DefaultTerminal,
     widgets::{Block, Padding, Widget},
 };
 use ratatui_fallible_widget::TermExt;
-use ratatui_image::picker::Picker;
+use ratatui_image::{FontSize, picker::Picker};
 use spawn::Spawner;
 use sqlx::SqliteConnection;
+use stats_data::Stats;
 use tokio_util::sync::CancellationToken;
-use tracing::{error_span, instrument};
+use tracing::{error_span, instrument, warn};
 
 use crate::error::ResultDisplayExt;
 pub mod error;
 
 async fn show_screen(screen: NextScreen, cx: Pin<&mut TuiContext>) -> Result<Navigation> {
+    let opened = match &screen {
+        NextScreen::ItemDetails(item) | NextScreen::FetchItemListDetails(item) => Some(item),
+        NextScreen::Play { items, index } => items.get(*index),
+        _ => None,
+    };
+    if let Some(item) = opened
+        && let Err(e) = history::record(&cx.cache, item).await
+    {
+        warn!("unable to record history: {e:?}");
+    }
     match screen {
         NextScreen::LoadHomeScreen => home_screen::load::load_home_screen(cx).await,
         NextScreen::HomeScreenData {
             resume,
             next_up,
+            recently_added,
+            favorites,
+            views,
+            latest,
+        } => home_screen::handle_home_screen_data(
+            cx,
+            resume,
+            next_up,
+            recently_added,
+            favorites,
             views,
             latest,
-        } => home_screen::handle_home_screen_data(cx, resume, next_up, views, latest),
+        ),
         NextScreen::HomeScreen(entry_screen, images_available) => {
             home_screen::display_home_screen(cx, entry_screen, images_available).await
         }
-        NextScreen::LoadUserView(user_view) => user_view::fetch_user_view(cx, user_view).await,
-        NextScreen::UserView { view, items } => user_view::display_user_view(cx, view, items).await,
+        NextScreen::LoadUserView { view, filter } => {
+            user_view::fetch_user_view(cx, view, filter).await
+        }
+        NextScreen::UserView {
+            view,
+            filter,
+            items,
+            more,
+        } => user_view::display_user_view(cx, view, filter, items, more).await,
+        NextScreen::LoadGenres(view) => user_view::genres::fetch_genres(cx, view).await,
+        NextScreen::Genres { view, genres } => {
+            user_view::genres::show_genres(cx, view, genres).await
+        }
+        NextScreen::LoadLinkedItems { link, filter } => {
+            user_view::fetch_linked_items(cx, link, filter).await
+        }
+        NextScreen::LinkedItems {
+            link,
+            filter,
+            items,
+            more,
+        } => user_view::display_linked_items(cx, link, filter, items, more).await,
         NextScreen::LoadPlayItem(load_play) => {
             player::fetch_items::fetch_screen(cx, load_play).await
         }
         NextScreen::Play { items, index } => player::play(cx, items, index).await,
+        NextScreen::NowPlaying => player::now_playing(cx).await,
         NextScreen::Error(report) => {
             let cx = cx.project();
             error::display_error(
@@ -57,7 +101,22 @@ async fn show_screen(screen: NextScreen, cx: Pin<&mut TuiContext>) -> Result<Nav
                 cx.events,
                 &cx.config.keybinds,
                 &cx.config.help_prefixes,
+                cx.config.theme,
                 report,
+                None,
+            )
+            .await
+        }
+        NextScreen::RetryableError { error, retry } => {
+            let cx = cx.project();
+            error::display_error(
+                cx.term,
+                cx.events,
+                &cx.config.keybinds,
+                &cx.config.help_prefixes,
+                cx.config.theme,
+                error,
+                Some(*retry),
             )
             .await
         }
@@ -82,6 +141,9 @@ async fn show_screen(screen: NextScreen, cx: Pin<&mut TuiContext>) -> Result<Nav
         NextScreen::FetchItemListDetailsRef(id) => {
             item_view::item_list_details::display_fetch_item_list_ref(cx, &id).await
         }
+        NextScreen::MarkPlayed(media_item) => {
+            item_view::item_list_details::mark_played(cx, media_item).await
+        }
         NextScreen::FetchItemDetails(id) => {
             item_view::item_details::display_fetch_item(cx, &id).await
         }
@@ -89,9 +151,37 @@ async fn show_screen(screen: NextScreen, cx: Pin<&mut TuiContext>) -> Result<Nav
         NextScreen::SendRefreshItem(item, refresh_item_query) => {
             refresh_item::refresh_screen(cx, item, refresh_item_query).await
         }
+        NextScreen::ScanLibrary => refresh_item::scan_library(cx).await,
+        NextScreen::LoadAddToPlaylist(item) => playlist::fetch_add_to_playlist(cx, item).await,
+        NextScreen::AddToPlaylist { item, playlists } => {
+            playlist::show_add_to_playlist(cx, item, playlists).await
+        }
+        NextScreen::SendAddToPlaylist { item, playlist } => {
+            playlist::send_add_to_playlist(cx, item, playlist).await
+        }
+        NextScreen::SendRemoveFromPlaylist { playlist, entry } => {
+            playlist::send_remove_from_playlist(cx, playlist, entry).await
+        }
+        NextScreen::FetchRemoteSubtitles(item) => {
+            remote_subtitles::fetch_remote_subtitles(cx, item).await
+        }
+        NextScreen::RemoteSubtitles { item, subtitles } => {
+            remote_subtitles::show_remote_subtitles(cx, item, subtitles).await
+        }
+        NextScreen::DownloadRemoteSubtitles { item, subtitle } => {
+            remote_subtitles::download_remote_subtitles(cx, item, subtitle).await
+        }
         NextScreen::UnsupportedItem => unsupported_item(cx).await,
         NextScreen::Stats => stats_view::show_stats(cx).await,
         NextScreen::Logs => log_screen::show_tui(cx).await,
+        NextScreen::History => history::show_history(cx).await,
+        NextScreen::Search => {
+            search::show_search(cx, String::new(), SearchKind::All, Vec::new()).await
+        }
+        NextScreen::SearchResults { term, kind, items } => {
+            search::show_search(cx, term, kind, items).await
+        }
+        NextScreen::SwitchServer => Ok(Navigation::SwitchServer),
     }
 }
 
@@ -99,31 +189,44 @@ async fn login_jellyfin(
     term: &mut DefaultTerminal,
     events: &mut KeybindEvents,
     config: &Config,
-) -> Result<Option<(JellyfinClient, JellyfinWebSocket)>> {
+    after: Option<usize>,
+) -> Result<Option<(JellyfinClient, JellyfinWebSocket, usize)>> {
     Ok(
-        if let Some(client) = login::login(term, config, events).await? {
+        if let Some((client, server)) = login::login(term, config, events, after).await? {
             let socket = client.get_socket()?;
-            Some((client, socket))
+            Some((client, socket, server))
         } else {
             None
         },
     )
 }
 
+/// Connects to a server, with `after` to the saved server following that one. Returns the index of
+/// the server connected to.
 #[instrument(skip_all, level = "debug")]
 async fn login(
     term: &mut DefaultTerminal,
     events: &mut KeybindEvents,
     config: &Config,
-) -> Option<(JellyfinClient, JellyfinWebSocket)> {
+    after: Option<usize>,
+) -> Option<(JellyfinClient, JellyfinWebSocket, usize)> {
     loop {
-        match login_jellyfin(term, events, config).await {
+        match login_jellyfin(term, events, config, after).await {
             Ok(v) => break v,
             Err(e) => {
-                match error::display_error(term, events, &config.keybinds, &config.help_prefixes, e)
-                    .await
+                match error::display_error(
+                    term,
+                    events,
+                    &config.keybinds,
+                    &config.help_prefixes,
+                    config.theme,
+                    e,
+                    None,
+                )
+                .await
                 {
                     Err(_) | Ok(Navigation::Exit) => break None,
+                    Ok(Navigation::Quit) if config.quit_action == QuitAction::Exit => break None,
                     _ => {}
                 }
             }
@@ -131,14 +234,104 @@ async fn login(
     }
 }
 
+/// Shows screens until the stack is empty, returns true if the user switched to another server
 #[instrument(skip_all, level = "debug")]
-async fn run_state(mut cx: Pin<&mut TuiContext>) {
-    let mut state = State::new();
+async fn run_state(mut cx: Pin<&mut TuiContext>) -> bool {
+    let mut state = State::new(cx.config.quit_action);
     while let Some(screen) = state.pop() {
-        state.navigate(match show_screen(screen, cx.as_mut()).await {
-            Ok(nav) => nav,
-            Err(e) => Navigation::Replace(NextScreen::Error(e)),
-        });
+        let retry = screen.retry();
+        // fetch screens turn their errors into navigation, so both are offered the retry
+        match show_screen(screen, cx.as_mut()).await.to_nav() {
+            Navigation::SwitchServer => return true,
+            Navigation::Replace(NextScreen::Error(e)) => {
+                state.navigate(Navigation::Replace(NextScreen::error(e, retry)))
+            }
+            nav => state.navigate(nav),
+        }
+    }
+    false
+}
+
+async fn start_player(
+    term: &mut DefaultTerminal,
+    events: &mut KeybindEvents,
+    config: &Config,
+    jellyfin: &JellyfinClient,
+    spawner: &Spawner,
+) -> Option<OwnedPlayerHandle> {
+    OwnedPlayerHandle::new(
+        jellyfin.clone(),
+        &config.hwdec,
+        &config.mpv_cache,
+        config.mpv_profile,
+        &config.mpv_log_level,
+        &config
+            .mpv_profile_config_file
+            .iter()
+            .chain(&config.mpv_config_file)
+            .map(PathBuf::as_path)
+            .collect::<Vec<_>>(),
+        true,
+        config.preferred_audio_lang.as_deref(),
+        config.preferred_subtitle_lang.as_deref(),
+        spawner,
+    )
+    .display_error(
+        term,
+        events,
+        &config.keybinds,
+        &config.help_prefixes,
+        config.theme,
+    )
+    .await
+}
+
+/// Services following the playback of `mpv_handle`, they stop together with the player
+fn spawn_player_services(
+    spawner: &Spawner,
+    config: &Config,
+    mpv_handle: &PlayerHandle,
+    jellyfin: &JellyfinClient,
+    cache: &Arc<tokio::sync::Mutex<SqliteConnection>>,
+    font_size: FontSize,
+    stats: &Stats,
+) {
+    spawner.spawn(
+        player_jellyfin(mpv_handle.clone(), jellyfin.clone(), spawner.clone()),
+        error_span!("player_jellyfin"),
+    );
+    if config.prefetch_next_item {
+        let image_layout = config.image_layout;
+        spawner.spawn(
+            prefetch_next(
+                mpv_handle.clone(),
+                jellyfin.clone(),
+                cache.clone(),
+                move |image_type| entry_image_size(font_size, image_layout, image_type),
+                stats.clone(),
+                spawner.clone(),
+            ),
+            error_span!("prefetch_next"),
+        );
+    }
+    #[cfg(feature = "mpris")]
+    spawner.spawn_res(
+        player_mpris::run_mpris_service(mpv_handle.clone(), jellyfin.clone()),
+        error_span!("player_mpris"),
+    );
+    #[cfg(feature = "mpris")]
+    if config.now_playing_notification {
+        spawner.spawn_res(
+            player_mpris::run_notify_service(mpv_handle.clone(), jellyfin.clone()),
+            error_span!("now_playing_notification"),
+        );
+    }
+    #[cfg(feature = "inhibit")]
+    if config.inhibit_screensaver {
+        spawner.spawn_res(
+            player_inhibit::run_inhibit_service(mpv_handle.clone()),
+            error_span!("player_inhibit"),
+        );
     }
 }
 
@@ -150,46 +343,70 @@ async fn run_app_inner(
     cache: Arc<tokio::sync::Mutex<SqliteConnection>>,
     image_picker: Picker,
 ) {
-    if let Some((jellyfin, jellyfin_socket)) = login(&mut term, &mut events, &config).await
-        && let Some(mpv_handle) = OwnedPlayerHandle::new(
-            jellyfin.clone(),
-            &config.hwdec,
-            config.mpv_profile,
-            &config.mpv_log_level,
-            config.mpv_config_file.as_deref(),
-            true,
+    let Some((mut jellyfin, mut jellyfin_socket, mut server)) =
+        login(&mut term, &mut events, &config, None).await
+    else {
+        return;
+    };
+    let Some(mut mpv_handle) =
+        start_player(&mut term, &mut events, &config, &jellyfin, &spawner).await
+    else {
+        return;
+    };
+    let font_size = image_picker.font_size();
+    let stats = Stats::default();
+    events.set_global(&config.keybinds.global);
+    events.set_sequence_timeout(config.key_sequence_timeout);
+    let image_cache = ImageProtocolCache::new(
+        config.image_fetch_timeout,
+        config.memory_image_cache_size,
+        config.image_decode_concurrency,
+        stats.clone(),
+    );
+    let image_picker = Arc::new(image_picker);
+    loop {
+        spawn_player_services(
             &spawner,
-        )
-        .display_error(
-            &mut term,
-            &mut events,
-            &config.keybinds,
-            &config.help_prefixes,
-        )
-        .await
-    {
-        spawner.spawn(
-            player_jellyfin(mpv_handle.clone(), jellyfin.clone(), spawner.clone()),
-            error_span!("player_jellyfin"),
+            &config,
+            &mpv_handle,
+            &jellyfin,
+            &cache,
+            font_size,
+            &stats,
         );
-        #[cfg(feature = "mpris")]
-        spawner.spawn_res(
-            player_mpris::run_mpris_service(mpv_handle.clone(), jellyfin.clone()),
-            error_span!("player_mpris"),
-        );
-        let cx = pin!(TuiContext {
+        // dropping the context stops the player and with it the services following it
+        let switch_server = run_state(pin!(TuiContext {
             jellyfin,
             jellyfin_socket,
-            term,
-            config,
-            events,
-            image_picker: Arc::new(image_picker),
-            cache,
-            image_cache: ImageProtocolCache::new(),
+            term: &mut term,
+            config: &config,
+            events: &mut events,
+            image_picker: image_picker.clone(),
+            cache: cache.clone(),
+            image_cache: image_cache.clone(),
             mpv_handle,
-            stats: Default::default(),
-        });
-        run_state(cx).await
+            stats: stats.clone(),
+            subtitle_providers: tokio::sync::OnceCell::new(),
+            library_runtimes: Default::default(),
+        }))
+        .await;
+        if !switch_server {
+            return;
+        }
+        let Some((next_jellyfin, next_socket, next)) =
+            login(&mut term, &mut events, &config, Some(server)).await
+        else {
+            return;
+        };
+        let Some(next_handle) =
+            start_player(&mut term, &mut events, &config, &next_jellyfin, &spawner).await
+        else {
+            return;
+        };
+        jellyfin = next_jellyfin;
+        jellyfin_socket = next_socket;
+        mpv_handle = next_handle;
+        server = next;
     }
 }
 
@@ -201,8 +418,11 @@ pub async fn run_app(
     config_file: Option<PathBuf>,
     use_builtin_config: bool,
 ) -> Result<()> {
-    let cache = config::cache().await?;
     let config = init_config(config_file, use_builtin_config)?;
+    let cache = config::cache(&config.cache_dir, config.image_cache_size).await?;
+    if config.capture_mouse() {
+        execute!(stdout(), EnableMouseCapture).context("enabling mouse capture")?;
+    }
     let image_picker =
         Picker::from_query_stdio().context("getting information for image display")?;
     let events = KeybindEvents::new()?;
@@ -244,7 +464,19 @@ pub async fn unsupported_item(cx: Pin<&mut TuiContext>) -> Result<Navigation> {
             Some(Err(e)) => break Err(e).context("getting key events from terminal"),
             Some(Ok(KeybindEvent::Render)) => continue,
             Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    break Ok(Navigation::Push {
+                        current: NextScreen::UnsupportedItem,
+                        next,
+                    });
+                }
+            }
             Some(Ok(KeybindEvent::Command(UnsupportedItemCommand::Quit))) => {
+                break Ok(Navigation::Quit);
+            }
+            Some(Ok(KeybindEvent::Command(UnsupportedItemCommand::Back))) => {
                 break Ok(Navigation::PopContext);
             }
         }
@@ -8,7 +8,7 @@ use std::{
 use clap::{Parser, Subcommand};
 use color_eyre::eyre::{Context, OptionExt, Result};
 use crossterm::{
-    event::{DisableBracketedPaste, EnableBracketedPaste},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste},
     execute,
 };
 use jellyhaj::run_app;
@@ -127,7 +127,8 @@ fn main() -> Result<()> {
             let term = ratatui::init();
             let hook = std::panic::take_hook();
             std::panic::set_hook(Box::new(move |panic| {
-                execute!(stdout(), DisableBracketedPaste).expect("resetting bracket paste failed");
+                execute!(stdout(), DisableBracketedPaste, DisableMouseCapture)
+                    .expect("resetting bracket paste failed");
                 hook(panic)
             }));
             execute!(stdout(), EnableBracketedPaste)
@@ -135,7 +136,8 @@ fn main() -> Result<()> {
                 .expect("failed to enable bracket paste");
 
             let res = run_app(term, cancel, args.config, args.use_builtin_config);
-            execute!(stdout(), DisableBracketedPaste).expect("resetting bracket paste failed");
+            execute!(stdout(), DisableBracketedPaste, DisableMouseCapture)
+                .expect("resetting bracket paste failed");
             ratatui::restore();
             res
         }
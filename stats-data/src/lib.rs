@@ -3,8 +3,11 @@ use std::sync::{Arc, atomic::AtomicU64};
 #[derive(Default)]
 pub struct StatsData {
     pub image_fetches: AtomicU64,
-    pub db_image_cache_hits: AtomicU64,
+    pub failed_image_fetches: AtomicU64,
+    pub disk_image_cache_hits: AtomicU64,
     pub memory_image_cache_hits: AtomicU64,
+    /// number of image protocols currently held in memory
+    pub memory_image_cache_size: AtomicU64,
 }
 
 pub type Stats = Arc<StatsData>;
@@ -1,6 +1,16 @@
-use std::{cmp::max, pin::Pin, sync::atomic::Ordering::Relaxed, time::Duration};
+use std::{
+    cmp::max,
+    pin::Pin,
+    sync::{Arc, atomic::Ordering::Relaxed},
+    time::Duration,
+};
 
-use jellyhaj_core::{context::TuiContext, keybinds::StatsCommand, state::Navigation};
+use jellyfin::connect::RequestStats;
+use jellyhaj_core::{
+    context::TuiContext,
+    keybinds::StatsCommand,
+    state::{Navigation, NextScreen, global_screen},
+};
 use keybinds::{KeybindEvent, KeybindEventStream, StreamExt};
 use ratatui::{
     layout::Constraint,
@@ -12,8 +22,12 @@ use ratatui_fallible_widget::TermExt;
 use stats_data::Stats;
 use tokio::select;
 
+/// longer request errors are cut off to keep the table on screen
+const MAX_ERROR_LEN: usize = 60;
+
 struct StatsWidget {
     stats: Stats,
+    requests: Arc<RequestStats>,
 }
 
 struct BorderedTable<'r> {
@@ -92,14 +106,40 @@ impl Widget for &StatsWidget {
         let block = Block::bordered().title("Program stats");
         let image_fetches = self.stats.image_fetches.load(Relaxed).to_string();
         let image_fetchers = ["Image fetches", &image_fetches];
-        let db_image_cache_hits = self.stats.db_image_cache_hits.load(Relaxed).to_string();
-        let db_image_cache_hits = ["DB image cache hits", &db_image_cache_hits];
+        let failed_image_fetches = self.stats.failed_image_fetches.load(Relaxed).to_string();
+        let failed_image_fetches = ["Failed image fetches", &failed_image_fetches];
+        let disk_image_cache_hits = self.stats.disk_image_cache_hits.load(Relaxed).to_string();
+        let disk_image_cache_hits = ["Disk image cache hits", &disk_image_cache_hits];
         let memory_image_cache_hits = self.stats.memory_image_cache_hits.load(Relaxed).to_string();
         let memory_image_cache_hits = ["In memory image cache hits", &memory_image_cache_hits];
+        let memory_image_cache_size = self.stats.memory_image_cache_size.load(Relaxed).to_string();
+        let memory_image_cache_size = ["Images in memory cache", &memory_image_cache_size];
+        let requests = self.requests.requests.load(Relaxed).to_string();
+        let requests = ["Requests", &requests];
+        let in_flight = self.requests.in_flight.load(Relaxed).to_string();
+        let in_flight = ["Requests in flight", &in_flight];
+        let failed_requests = self.requests.failed.load(Relaxed).to_string();
+        let failed_requests = ["Failed requests", &failed_requests];
+        let last_error = match self.requests.last_error() {
+            Some(error) if error.chars().count() > MAX_ERROR_LEN => {
+                let mut error: String = error.chars().take(MAX_ERROR_LEN - 1).collect();
+                error.push('…');
+                error
+            }
+            Some(error) => error,
+            None => "-".to_string(),
+        };
+        let last_error = ["Last request error", &last_error];
         let rows: [&[_]; _] = [
             &image_fetchers,
-            &db_image_cache_hits,
+            &failed_image_fetches,
+            &disk_image_cache_hits,
             &memory_image_cache_hits,
+            &memory_image_cache_size,
+            &requests,
+            &in_flight,
+            &failed_requests,
+            &last_error,
         ];
         let (col1, col2) = rows.iter().fold((0, 0), |(col1, col2), v| {
             (max(col1, v[0].len()), max(col2, v[1].len()))
@@ -119,6 +159,7 @@ pub async fn show_stats(cx: Pin<&mut TuiContext>) -> color_eyre::Result<Navigati
     let cx = cx.project();
     let mut widget = StatsWidget {
         stats: cx.stats.clone(),
+        requests: cx.jellyfin.request_stats().clone(),
     };
     let mut events = KeybindEventStream::new(
         cx.events,
@@ -135,9 +176,21 @@ pub async fn show_stats(cx: Pin<&mut TuiContext>) -> color_eyre::Result<Navigati
                 match event{
                     Some(Ok(KeybindEvent::Render)) => continue,
                     Some(Ok(KeybindEvent::Command(StatsCommand::Quit))) => {
+                        break Ok(Navigation::Quit);
+                    }
+                    Some(Ok(KeybindEvent::Command(StatsCommand::Back))) => {
                         break Ok(Navigation::PopContext);
                     }
                     Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Global(name))) => {
+                        if let Some(next) = global_screen(name) {
+                            break Ok(Navigation::Push {
+                                current: NextScreen::Stats,
+                                next,
+                            });
+                        }
+                    }
                     Some(Err(e)) => break Err(e),
                     None => break Ok(Navigation::Exit),
                 }
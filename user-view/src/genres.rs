@@ -0,0 +1,164 @@
+use std::pin::Pin;
+
+use color_eyre::eyre::{Context, Result};
+use fetch::fetch_screen;
+use futures_util::StreamExt;
+use jellyfin::{
+    Auth, JellyfinClient, JellyfinVec, genres::GetGenresQuery, items::NameIdPair,
+    user_views::UserView,
+};
+use jellyhaj_core::{
+    context::TuiContext,
+    keybinds::GenresCommand,
+    state::{
+        ItemLink, ItemSort, LinkKind, Navigation, NextScreen, ToNavigation, UserViewFilter,
+        global_screen,
+    },
+};
+use keybinds::{KeybindEvent, KeybindEventStream};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Modifier,
+    widgets::{Block, List, ListState, Padding, Paragraph, StatefulWidget, Widget},
+};
+use ratatui_fallible_widget::{FallibleWidget, TermExt};
+
+async fn fetch_library_genres(
+    jellyfin: &JellyfinClient<Auth>,
+    view: &UserView,
+) -> Result<Vec<NameIdPair>> {
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    JellyfinVec::collect(async |start| {
+        jellyfin
+            .get_genres(&GetGenresQuery {
+                user_id: user_id.into(),
+                parent_id: view.id.as_str().into(),
+                start_index: start.into(),
+                limit: 500.into(),
+                sort_by: "SortName".into(),
+                sort_order: "Ascending".into(),
+                enable_images: false.into(),
+                enable_total_record_count: true.into(),
+            })
+            .await
+            .context("requesting genres")?
+            .deserialize()
+            .await
+            .context("deserializing genres")
+    })
+    .await
+}
+
+pub async fn fetch_genres(cx: Pin<&mut TuiContext>, view: UserView) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    fetch_screen(
+        &format!("Loading genres of {}", view.name),
+        async move {
+            Ok(fetch_library_genres(jellyfin, &view)
+                .await
+                .map(move |genres| Navigation::Replace(NextScreen::Genres { view, genres }))
+                .to_nav())
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+struct Genres {
+    title: String,
+    genres: Vec<NameIdPair>,
+    state: ListState,
+}
+
+impl FallibleWidget for Genres {
+    fn render_fallible(&mut self, area: Rect, buf: &mut Buffer) -> Result<()> {
+        let block = Block::bordered()
+            .title(self.title.as_str())
+            .padding(Padding::uniform(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if self.genres.is_empty() {
+            Paragraph::new("This library has no genres").render(inner, buf);
+        } else {
+            let list = List::new(self.genres.iter().map(|genre| genre.name.as_str()))
+                .highlight_style(Modifier::REVERSED);
+            StatefulWidget::render(list, inner, buf, &mut self.state);
+        }
+        Ok(())
+    }
+}
+
+/// Genres of a library, opening one shows the items of the library with that genre
+pub async fn show_genres(
+    cx: Pin<&mut TuiContext>,
+    view: UserView,
+    genres: Vec<NameIdPair>,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let mut widget = Genres {
+        title: format!("Genres of {}", view.name),
+        genres,
+        state: ListState::default().with_selected(Some(0)),
+    };
+    let mut events = KeybindEventStream::new(
+        cx.events,
+        &mut widget,
+        cx.config.keybinds.genres.clone(),
+        &cx.config.help_prefixes,
+    );
+    loop {
+        cx.term.draw_fallible(&mut events)?;
+        let cmd = match events.next().await {
+            None => break Ok(Navigation::Exit),
+            Some(Err(e)) => break Err(e).context("getting key events from terminal"),
+            Some(Ok(KeybindEvent::Render)) => continue,
+            Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Mouse(_))) => unreachable!(),
+            Some(Ok(KeybindEvent::Global(name))) => {
+                if let Some(next) = global_screen(name) {
+                    let genres = std::mem::take(&mut events.get_inner().genres);
+                    break Ok(Navigation::Push {
+                        current: NextScreen::Genres { view, genres },
+                        next,
+                    });
+                }
+                continue;
+            }
+            Some(Ok(KeybindEvent::Command(cmd))) => cmd,
+        };
+        match cmd {
+            GenresCommand::Quit => break Ok(Navigation::Quit),
+            GenresCommand::Back => break Ok(Navigation::PopContext),
+            GenresCommand::Up => events.get_inner().state.select_previous(),
+            GenresCommand::Down => events.get_inner().state.select_next(),
+            GenresCommand::Open => {
+                let widget = events.get_inner();
+                if let Some(genre) = widget.state.selected().and_then(|i| widget.genres.get(i)) {
+                    let link = ItemLink {
+                        kind: LinkKind::Genre,
+                        id: genre.id.clone(),
+                        name: genre.name.clone(),
+                        parent: Some(view.id.clone()),
+                    };
+                    let genres = std::mem::take(&mut widget.genres);
+                    break Ok(Navigation::Push {
+                        current: NextScreen::Genres { view, genres },
+                        next: NextScreen::LoadLinkedItems {
+                            link,
+                            filter: UserViewFilter {
+                                sort: ItemSort::Name,
+                                descending: false,
+                                ..Default::default()
+                            },
+                        },
+                    });
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use color_eyre::eyre::{Context, Result};
-use entries::{entry::Entry, grid::EntryGrid, image::available::ImagesAvailable};
+use entries::{
+    entry::Entry, grid::EntryGrid, image::available::ImagesAvailable, mouse::MouseNavigation,
+};
 use fetch::fetch_screen;
-use futures_util::StreamExt;
+use futures_util::{
+    FutureExt, StreamExt,
+    future::{Fuse, FusedFuture},
+};
 use jellyfin::{
     Auth, JellyfinClient, JellyfinVec,
     items::{GetItemsQuery, MediaItem},
@@ -9,58 +14,199 @@ use jellyfin::{
 };
 use jellyhaj_core::{
     context::TuiContext,
-    entries::EntryExt,
+    entries::{EntryExt, UserDataChange, change_user_data},
     keybinds::UserViewCommand,
-    state::{Navigation, NextScreen, ToNavigation},
+    state::{
+        ItemLink, ItemSort, LinkKind, Navigation, NextScreen, ToNavigation, UserViewFilter,
+        global_screen,
+    },
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Modifier,
+    widgets::{Block, Clear, List, ListState, Padding, StatefulWidget, Widget},
 };
-use ratatui_fallible_widget::TermExt;
-use std::pin::Pin;
-use tracing::debug;
+use ratatui_fallible_widget::{FallibleWidget, TermExt};
+use std::pin::{Pin, pin};
+use tracing::{debug, warn};
+
+use keybinds::{ClickTracker, KeybindEvent, KeybindEventStream};
+
+pub mod genres;
+
+/// Sort fields offered by the sort menu, followed by the order and unplayed toggles
+const SORTS: [ItemSort; 5] = [
+    ItemSort::DateAdded,
+    ItemSort::Name,
+    ItemSort::DatePlayed,
+    ItemSort::CommunityRating,
+    ItemSort::PremiereDate,
+];
+
+fn sort_by(sort: ItemSort) -> &'static str {
+    match sort {
+        ItemSort::DateAdded => "DateLastContentAdded",
+        ItemSort::Name => "SortName",
+        ItemSort::DatePlayed => "DatePlayed",
+        ItemSort::CommunityRating => "CommunityRating",
+        ItemSort::PremiereDate => "PremiereDate",
+    }
+}
 
-use keybinds::{KeybindEvent, KeybindEventStream};
+fn sort_name(sort: ItemSort) -> &'static str {
+    match sort {
+        ItemSort::DateAdded => "Date added",
+        ItemSort::Name => "Name",
+        ItemSort::DatePlayed => "Date played",
+        ItemSort::CommunityRating => "Rating",
+        ItemSort::PremiereDate => "Release date",
+    }
+}
+
+fn sort_order(filter: UserViewFilter) -> &'static str {
+    if filter.descending {
+        "Descending"
+    } else {
+        "Ascending"
+    }
+}
+
+/// filter after selecting the entry at `index` of the sort menu
+fn select_sort(filter: UserViewFilter, index: usize) -> UserViewFilter {
+    match SORTS.get(index) {
+        Some(sort) => UserViewFilter {
+            sort: *sort,
+            ..filter
+        },
+        None if index == SORTS.len() => UserViewFilter {
+            descending: !filter.descending,
+            ..filter
+        },
+        None => UserViewFilter {
+            unplayed: !filter.unplayed,
+            ..filter
+        },
+    }
+}
+
+struct UserViewWidget {
+    grid: EntryGrid,
+    filter: UserViewFilter,
+    /// selection of the sort menu if it is open
+    sort_menu: Option<ListState>,
+}
+
+impl FallibleWidget for UserViewWidget {
+    fn render_fallible(&mut self, area: Rect, buf: &mut Buffer) -> Result<()> {
+        self.grid.render_fallible(area, buf)?;
+        if let Some(list) = &mut self.sort_menu {
+            render_sort_menu(self.filter, list, area, buf);
+        }
+        Ok(())
+    }
+}
+
+/// centered popup with the sort fields, the order and the unplayed filter
+fn render_sort_menu(filter: UserViewFilter, list: &mut ListState, area: Rect, buf: &mut Buffer) {
+    let entries = SORTS.len() + 2;
+    let [area] = Layout::horizontal([Constraint::Length(30)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(entries as u16 + 2)])
+        .flex(Flex::Center)
+        .areas(area);
+    Clear.render(area, buf);
+    let block = Block::bordered()
+        .title("Sort")
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    block.render(area, buf);
+    let checked = |checked: bool| if checked { "[x]" } else { "[ ]" };
+    let items = SORTS
+        .iter()
+        .map(|sort| {
+            let marker = if *sort == filter.sort { "●" } else { " " };
+            format!("{marker}   {}", sort_name(*sort))
+        })
+        .chain([
+            format!("{} Descending", checked(filter.descending)),
+            format!("{} Unplayed only", checked(filter.unplayed)),
+        ]);
+    let list_widget = List::new(items).highlight_style(Modifier::REVERSED);
+    StatefulWidget::render(list_widget, inner, buf, list);
+}
+
+/// if the server has items after the `loaded` ones including `page`
+fn has_more(page: &JellyfinVec<MediaItem>, loaded: usize) -> bool {
+    match page.total_record_count {
+        Some(total) => loaded < total as usize,
+        None => page.items.len() == PAGE_SIZE as usize,
+    }
+}
+
+/// items fetched per request
+const PAGE_SIZE: u32 = 100;
+
+/// rows before the end of the grid at which the next page is fetched
+const LOAD_AHEAD_ROWS: usize = 3;
 
 async fn fetch_user_view_items(
     jellyfin: &JellyfinClient<Auth>,
     view: &UserView,
-) -> Result<Vec<MediaItem>> {
+    filter: UserViewFilter,
+    start: u32,
+) -> Result<JellyfinVec<MediaItem>> {
     let user_id = jellyfin.get_auth().user.id.as_str();
-    let items = JellyfinVec::collect(async |start| {
-        jellyfin
-            .get_items(&GetItemsQuery {
-                user_id: user_id.into(),
-                start_index: start.into(),
-                limit: 100.into(),
-                recursive: None,
-                parent_id: view.id.as_str().into(),
-                exclude_item_types: None,
-                include_item_types: None,
-                enable_images: true.into(),
-                enable_image_types: "Thumb, Backdrop, Primary".into(),
-                image_type_limit: 1.into(),
-                enable_user_data: true.into(),
-                fields: None,
-                sort_by: "DateLastContentAdded".into(),
-                sort_order: "Descending".into(),
-            })
-            .await
-            .context("requesting items")?
-            .deserialize()
-            .await
-            .context("deserializing items")
-    })
-    .await?;
-    Ok(items)
+    jellyfin
+        .get_items(&GetItemsQuery {
+            user_id: user_id.into(),
+            start_index: start.into(),
+            limit: PAGE_SIZE.into(),
+            recursive: None,
+            parent_id: view.id.as_str().into(),
+            exclude_item_types: None,
+            include_item_types: None,
+            enable_images: true.into(),
+            enable_image_types: "Thumb, Backdrop, Primary".into(),
+            image_type_limit: 1.into(),
+            enable_user_data: true.into(),
+            fields: None,
+            filters: filter.unplayed.then_some("IsUnplayed"),
+            genre_ids: None,
+            studio_ids: None,
+            person_ids: None,
+            search_term: None,
+            sort_by: sort_by(filter.sort).into(),
+            sort_order: sort_order(filter).into(),
+        })
+        .await
+        .context("requesting items")?
+        .deserialize()
+        .await
+        .context("deserializing items")
 }
 
-pub async fn fetch_user_view(cx: Pin<&mut TuiContext>, view: UserView) -> Result<Navigation> {
+pub async fn fetch_user_view(
+    cx: Pin<&mut TuiContext>,
+    view: UserView,
+    filter: UserViewFilter,
+) -> Result<Navigation> {
     let cx = cx.project();
     let jellyfin = cx.jellyfin;
     fetch_screen(
         &format!("Loading user view {}", view.name),
         async move {
-            Ok(fetch_user_view_items(jellyfin, &view)
+            Ok(fetch_user_view_items(jellyfin, &view, filter, 0)
                 .await
-                .map(move |items| Navigation::Replace(NextScreen::UserView { view, items }))
+                .map(move |page| {
+                    Navigation::Replace(NextScreen::UserView {
+                        view,
+                        filter,
+                        more: has_more(&page, page.items.len()),
+                        items: page.items,
+                    })
+                })
                 .to_nav())
         },
         cx.events,
@@ -74,123 +220,467 @@ pub async fn fetch_user_view(cx: Pin<&mut TuiContext>, view: UserView) -> Result
 pub async fn display_user_view(
     cx: Pin<&mut TuiContext>,
     view: UserView,
+    filter: UserViewFilter,
+    items: Vec<MediaItem>,
+    more: bool,
+) -> Result<Navigation> {
+    let title = view.name.clone();
+    let page_view = view.clone();
+    let genres_view = view.clone();
+    display_items(
+        cx,
+        title,
+        filter,
+        Pages {
+            items,
+            more,
+            fetch: async |jellyfin: &JellyfinClient<Auth>, start| {
+                fetch_user_view_items(jellyfin, &page_view, filter, start).await
+            },
+        },
+        move |filter| NextScreen::LoadUserView {
+            view: view.clone(),
+            filter,
+        },
+        || Some(NextScreen::LoadGenres(genres_view.clone())),
+    )
+    .await
+}
+
+async fn fetch_linked(
+    jellyfin: &JellyfinClient<Auth>,
+    link: &ItemLink,
+    filter: UserViewFilter,
+    start: u32,
+) -> Result<JellyfinVec<MediaItem>> {
+    let user_id = jellyfin.get_auth().user.id.as_str();
+    let id = Some(link.id.as_str());
+    jellyfin
+        .get_items(&GetItemsQuery {
+            user_id: user_id.into(),
+            start_index: start.into(),
+            limit: PAGE_SIZE.into(),
+            recursive: true.into(),
+            parent_id: link.parent.as_deref(),
+            include_item_types: "Movie, Series, MusicAlbum".into(),
+            enable_images: true.into(),
+            enable_image_types: "Thumb, Backdrop, Primary".into(),
+            image_type_limit: 1.into(),
+            enable_user_data: true.into(),
+            filters: filter.unplayed.then_some("IsUnplayed"),
+            genre_ids: id.filter(|_| link.kind == LinkKind::Genre),
+            studio_ids: id.filter(|_| link.kind == LinkKind::Studio),
+            person_ids: id.filter(|_| link.kind == LinkKind::Person),
+            sort_by: sort_by(filter.sort).into(),
+            sort_order: sort_order(filter).into(),
+            ..Default::default()
+        })
+        .await
+        .context("requesting items")?
+        .deserialize()
+        .await
+        .context("deserializing items")
+}
+
+pub async fn fetch_linked_items(
+    cx: Pin<&mut TuiContext>,
+    link: ItemLink,
+    filter: UserViewFilter,
+) -> Result<Navigation> {
+    let cx = cx.project();
+    let jellyfin = cx.jellyfin;
+    fetch_screen(
+        &format!("Loading items of {}", link.name),
+        async move {
+            Ok(fetch_linked(jellyfin, &link, filter, 0)
+                .await
+                .map(move |page| {
+                    Navigation::Replace(NextScreen::LinkedItems {
+                        link,
+                        filter,
+                        more: has_more(&page, page.items.len()),
+                        items: page.items,
+                    })
+                })
+                .to_nav())
+        },
+        cx.events,
+        cx.config.keybinds.fetch.clone(),
+        cx.term,
+        &cx.config.help_prefixes,
+    )
+    .await
+}
+
+pub async fn display_linked_items(
+    cx: Pin<&mut TuiContext>,
+    link: ItemLink,
+    filter: UserViewFilter,
     items: Vec<MediaItem>,
+    more: bool,
 ) -> Result<Navigation> {
+    let title = link.name.clone();
+    let page_link = link.clone();
+    display_items(
+        cx,
+        title,
+        filter,
+        Pages {
+            items,
+            more,
+            fetch: async |jellyfin: &JellyfinClient<Auth>, start| {
+                fetch_linked(jellyfin, &page_link, filter, start).await
+            },
+        },
+        move |filter| NextScreen::LoadLinkedItems {
+            link: link.clone(),
+            filter,
+        },
+        || None,
+    )
+    .await
+}
+
+/// Items shown first, `fetch` loads the items after the given index if there are `more`
+struct Pages<F> {
+    items: Vec<MediaItem>,
+    more: bool,
+    fetch: F,
+}
+
+/// Grid of items, the next page is fetched when the selection nears the end, `current` recreates
+/// the screen with the given filter and `genres` opens the genres of the shown library
+async fn display_items(
+    cx: Pin<&mut TuiContext>,
+    title: String,
+    filter: UserViewFilter,
+    pages: Pages<impl AsyncFn(&JellyfinClient<Auth>, u32) -> Result<JellyfinVec<MediaItem>>>,
+    current: impl Fn(UserViewFilter) -> NextScreen,
+    genres: impl Fn() -> Option<NextScreen>,
+) -> Result<Navigation> {
+    let Pages {
+        items,
+        mut more,
+        fetch: fetch_page,
+    } = pages;
+    let cx = cx.project();
     let images_available = ImagesAvailable::new();
-    let mut grid = EntryGrid::new(
+    let entry_cx = cx.entry_context(&images_available);
+    let to_entries = |items: Vec<MediaItem>| {
         items
             .into_iter()
             .filter_map(|item| {
-                Entry::from_media_item(
-                    item,
-                    &cx.jellyfin,
-                    &cx.cache,
-                    &cx.image_cache,
-                    &images_available,
-                    &cx.image_picker,
-                    &cx.stats,
-                )
-                .transpose()
+                Entry::from_media_item(item, &entry_cx, &cx.config.icons).transpose()
             })
-            .collect::<Result<Vec<_>>>()?,
-        view.name.clone(),
+            .collect::<Result<Vec<_>>>()
+    };
+    let mut loaded = items.len();
+    let grid = EntryGrid::new(
+        to_entries(items)?,
+        if filter.unplayed {
+            format!("{title} (unplayed)")
+        } else {
+            title
+        },
         cx.image_picker.clone(),
+        cx.config.image_layout,
+        cx.config.scroll_style,
+        cx.config.prefetch_margin,
     );
-    let cx = cx.project();
+    let mut widget = UserViewWidget {
+        grid,
+        filter,
+        sort_menu: None,
+    };
+    let mut page = pin!(Fuse::terminated());
     let mut events = KeybindEventStream::new(
         cx.events,
-        &mut grid,
+        &mut widget,
         cx.config.keybinds.user_view.clone(),
         &cx.config.help_prefixes,
     );
+    events.set_mouse_input(cx.config.capture_mouse());
+    let mut clicks = ClickTracker::new(cx.config.single_click_play);
+    // a failed page is fetched again after the next command instead of immediately
+    let mut page_failed = false;
     loop {
+        if more
+            && !page_failed
+            && page.is_terminated()
+            && events.get_inner().grid.near_end(LOAD_AHEAD_ROWS)
+        {
+            page.set(fetch_page(cx.jellyfin, loaded as u32).fuse());
+        }
         cx.term.draw_fallible(&mut events)?;
         let cmd = tokio::select! {
             _ = images_available.wait_available() => {continue          }
+            fetched = &mut page, if !page.is_terminated() => {
+                match fetched {
+                    Ok(fetched) => {
+                        loaded += fetched.items.len();
+                        more = !fetched.items.is_empty() && has_more(&fetched, loaded);
+                        let entries = to_entries(fetched.items)?;
+                        events.get_inner().grid.extend(entries);
+                    }
+                    Err(e) => {
+                        warn!("error fetching the next page of items: {e:?}");
+                        page_failed = true;
+                    }
+                }
+                continue
+            }
             term = events.next() => {
                 match term {
                     Some(Ok(KeybindEvent::Command(cmd))) => cmd,
                     Some(Ok(KeybindEvent::Render)) => continue ,
                     Some(Ok(KeybindEvent::Text(_))) => unreachable!(),
+                    Some(Ok(KeybindEvent::Mouse(event))) => {
+                        let hover = cx.config.focus_follows_mouse;
+                        let widget = events.get_inner();
+                        if widget.sort_menu.is_none()
+                            && widget.grid.handle_mouse(event, hover, &mut clicks)
+                        {
+                            UserViewCommand::Play
+                        } else {
+                            continue
+                        }
+                    }
+                    Some(Ok(KeybindEvent::Global(name))) => match global_screen(name) {
+                        Some(next) => {
+                            break Ok(Navigation::Push {
+                                current: current(filter),
+                                next,
+                            });
+                        }
+                        None => continue,
+                    },
                     Some(Err(e)) => break  Err(e).context("getting key events from terminal"),
                     None => break  Ok(Navigation::PopContext)
                 }
             }
         };
         debug!("received command {cmd:?}");
+        page_failed = false;
         match cmd {
+            UserViewCommand::Quit | UserViewCommand::Back
+                if events.get_inner().sort_menu.is_some() =>
+            {
+                events.get_inner().sort_menu = None;
+                events.set_map(cx.config.keybinds.user_view.clone());
+            }
+            UserViewCommand::Genres => {
+                if let Some(next) = genres() {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next,
+                    });
+                }
+            }
+            UserViewCommand::Sort => {
+                let widget = events.get_inner();
+                if widget.sort_menu.take().is_some() {
+                    events.set_map(cx.config.keybinds.user_view.clone());
+                } else {
+                    let selected = SORTS.iter().position(|sort| *sort == filter.sort);
+                    widget.sort_menu = Some(ListState::default().with_selected(selected));
+                    events.set_map(cx.config.keybinds.user_view_sort.clone());
+                }
+            }
+            UserViewCommand::Select => {
+                if let Some(index) = events
+                    .get_inner()
+                    .sort_menu
+                    .as_ref()
+                    .and_then(ListState::selected)
+                {
+                    break Ok(Navigation::Replace(current(select_sort(filter, index))));
+                }
+            }
+            UserViewCommand::Up if events.get_inner().sort_menu.is_some() => {
+                if let Some(list) = &mut events.get_inner().sort_menu {
+                    list.select_previous();
+                }
+            }
+            UserViewCommand::Down if events.get_inner().sort_menu.is_some() => {
+                if let Some(list) = &mut events.get_inner().sort_menu {
+                    list.select_next();
+                }
+            }
             UserViewCommand::Quit => {
+                break Ok(Navigation::Quit);
+            }
+            UserViewCommand::Back => {
                 break Ok(Navigation::PopContext);
             }
             UserViewCommand::Reload => {
-                break Ok(Navigation::Replace(NextScreen::LoadUserView(view)));
+                break Ok(Navigation::Replace(current(filter)));
+            }
+            UserViewCommand::ToggleUnplayed => {
+                break Ok(Navigation::Replace(current(UserViewFilter {
+                    unplayed: !filter.unplayed,
+                    ..filter
+                })));
             }
             UserViewCommand::Prev => {
-                events.get_inner().left();
+                events.get_inner().grid.left();
             }
             UserViewCommand::Next => {
-                events.get_inner().right();
+                events.get_inner().grid.right();
             }
             UserViewCommand::Up => {
-                events.get_inner().up();
+                events.get_inner().grid.up();
             }
             UserViewCommand::Down => {
-                events.get_inner().down();
+                events.get_inner().grid.down();
+            }
+            UserViewCommand::PageUp => {
+                events.get_inner().grid.page_up();
+            }
+            UserViewCommand::PageDown => {
+                events.get_inner().grid.page_down();
+            }
+            UserViewCommand::First => {
+                events.get_inner().grid.first();
+            }
+            UserViewCommand::Last => {
+                events.get_inner().grid.last();
             }
             UserViewCommand::RefreshItem => {
-                if let Some(entry) = events.get_inner().get()
+                if let Some(entry) = events.get_inner().grid.get()
                     && let Some(id) = entry.item_id()
                 {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
                         next: NextScreen::RefreshItem(id.to_string()),
                     });
                 }
             }
+            UserViewCommand::ScanLibrary => {
+                break Ok(Navigation::Push {
+                    current: current(filter),
+                    next: NextScreen::ScanLibrary,
+                });
+            }
+            UserViewCommand::AddToPlaylist => {
+                if let Some(entry) = events.get_inner().grid.get()
+                    && let Some(id) = entry.item_id()
+                {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next: NextScreen::LoadAddToPlaylist(id.to_string()),
+                    });
+                }
+            }
+            UserViewCommand::ResetProgress => {
+                if let Some(entry) = events.get_inner().grid.get_mut()
+                    && let Err(e) = change_user_data(
+                        cx.jellyfin,
+                        entry,
+                        UserDataChange::ResetProgress,
+                        &cx.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
+            UserViewCommand::ToggleFavorite => {
+                if let Some(entry) = events.get_inner().grid.get_mut()
+                    && let Err(e) = change_user_data(
+                        cx.jellyfin,
+                        entry,
+                        UserDataChange::ToggleFavorite,
+                        &cx.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
+            UserViewCommand::ToggleWatched => {
+                if let Some(entry) = events.get_inner().grid.get_mut()
+                    && let Err(e) = change_user_data(
+                        cx.jellyfin,
+                        entry,
+                        UserDataChange::ToggleWatched,
+                        &cx.config.icons,
+                    )
+                    .await
+                {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next: NextScreen::Error(e),
+                    });
+                }
+            }
             UserViewCommand::Play => {
-                if let Some(entry) = events.get_inner().get()
+                if let Some(entry) = events.get_inner().grid.get()
                     && let Some(next) = entry.play()
                 {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
+                        next,
+                    });
+                }
+            }
+            UserViewCommand::ShufflePlay => {
+                if let Some(entry) = events.get_inner().grid.get()
+                    && let Some(next) = entry.shuffle()
+                {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
                         next,
                     });
                 }
             }
             UserViewCommand::Open => {
-                if let Some(entry) = events.get_inner().get() {
+                if let Some(entry) = events.get_inner().grid.get() {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
                         next: entry.open(),
                     });
                 }
             }
+            UserViewCommand::Activate => {
+                if let Some(entry) = events.get_inner().grid.get() {
+                    break Ok(Navigation::Push {
+                        current: current(filter),
+                        next: entry.activate(cx.config.default_item_action),
+                    });
+                }
+            }
             UserViewCommand::OpenEpisode => {
-                if let Some(entry) = events.get_inner().get()
+                if let Some(entry) = events.get_inner().grid.get()
                     && let Some(next) = entry.episode()
                 {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
                         next,
                     });
                 }
             }
             UserViewCommand::OpenSeason => {
-                if let Some(entry) = events.get_inner().get()
+                if let Some(entry) = events.get_inner().grid.get()
                     && let Some(next) = entry.season()
                 {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
                         next,
                     });
                 }
             }
             UserViewCommand::OpenSeries => {
-                if let Some(entry) = events.get_inner().get()
+                if let Some(entry) = events.get_inner().grid.get()
                     && let Some(next) = entry.series()
                 {
                     break Ok(Navigation::Push {
-                        current: NextScreen::LoadUserView(view),
+                        current: current(filter),
                         next,
                     });
                 }